@@ -0,0 +1,76 @@
+use {
+    criterion::{criterion_group, criterion_main, Criterion},
+    skytable::{query, Config},
+    std::{
+        io,
+        pin::Pin,
+        task::{Context, Poll},
+    },
+    tokio::io::{AsyncRead, AsyncWrite, ReadBuf},
+};
+
+/// A stream that accepts the handshake, discards everything written to it and answers every query with an empty
+/// response, so that only the cost of encoding and writing the query is measured
+struct Sink {
+    handshake: bool,
+}
+
+impl AsyncRead for Sink {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        if self.handshake {
+            buf.put_slice(b"\x12");
+        } else {
+            self.handshake = true;
+            buf.put_slice(b"H\x00\x00\x00");
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncWrite for Sink {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Poll::Ready(Ok(buf.len()))
+    }
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+    fn poll_shutdown(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+fn write_query(c: &mut Criterion) {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let q = query!(
+        "insert into myspace.mymodel(?, ?)",
+        "sayan",
+        vec![0xAAu8; 1024 * 1024]
+    );
+    let mut group = c.benchmark_group("write_query");
+    for (name, cfg) in [
+        ("buffered", Config::new_default("username", "password")),
+        (
+            "direct",
+            Config::new_default("username", "password").set_direct_write_threshold(0),
+        ),
+    ] {
+        let mut db = rt
+            .block_on(cfg.connect_async_over(Sink { handshake: false }))
+            .unwrap();
+        group.bench_function(name, |b| b.iter(|| rt.block_on(db.query(&q)).unwrap()));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, write_query);
+criterion_main!(benches);
@@ -0,0 +1,51 @@
+use {
+    criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion},
+    skytable::response::Value,
+    std::io::Write,
+};
+
+fn values() -> Vec<Value> {
+    (0..10_000u64)
+        .map(|i| match i % 4 {
+            0 => Value::UInt64(i * 1_000_003),
+            1 => Value::SInt64(-(i as i64)),
+            2 => Value::Float64(i as f64 / 7.0),
+            _ => Value::str(format!("user{i}")),
+        })
+        .collect()
+}
+
+fn write_values(c: &mut Criterion) {
+    let values = values();
+    let mut group = c.benchmark_group("write_values");
+    group.bench_function("to_string", |b| {
+        b.iter_batched_ref(
+            || Vec::with_capacity(256 * 1024),
+            |out| {
+                for v in &values {
+                    out.write_all(v.to_string().as_bytes()).unwrap();
+                    out.push(b'\n');
+                }
+                black_box(out.len())
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("write_to", |b| {
+        b.iter_batched_ref(
+            || Vec::with_capacity(256 * 1024),
+            |out| {
+                for v in &values {
+                    v.write_to(out).unwrap();
+                    out.push(b'\n');
+                }
+                black_box(out.len())
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, write_values);
+criterion_main!(benches);
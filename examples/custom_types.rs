@@ -47,17 +47,16 @@ fn main() {
 
     // set up schema
     // create space
-    db.query_parse::<()>(&query!("create space myspace"))
-        .unwrap();
+    db.exec(&query!("create space myspace")).unwrap();
     // create model
-    db.query_parse::<()>(&query!(
+    db.exec(&query!(
         "create model myspace.mymodel(username: string, password: string, followers: uint64, null email: string"
     ))
     .unwrap();
 
     // insert data
     let our_user = User::new("myuser".into(), "pass123".into(), 0, None);
-    db.query_parse::<()>(&query!(
+    db.exec(&query!(
         "insert into myspace.mymodel(?, ?, ?, ?)",
         our_user.clone()
     ))
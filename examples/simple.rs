@@ -14,10 +14,9 @@ fn main() {
 
     // set up schema
     // create space
-    db.query_parse::<()>(&query!("create space myspace"))
-        .unwrap();
+    db.exec(&query!("create space myspace")).unwrap();
     // create model
-    db.query_parse::<()>(&query!(
+    db.exec(&query!(
         "create model myspace.mymodel(username: string, password: string, followers: uint64"
     ))
     .unwrap();
@@ -26,7 +25,7 @@ fn main() {
 
     let (form_username, form_pass) = dummy_web_fetch_username_password();
     // insert some data
-    db.query_parse::<()>(&query!(
+    db.exec(&query!(
         "insert into myspace.mymodel(?, ?, ?)",
         &form_username,
         form_pass,
@@ -46,7 +45,7 @@ fn main() {
     dummy_respond_to_request(followers);
 
     // update followers to account for huge numbers who were angry after being rickrolled
-    db.query_parse::<()>(&query!(
+    db.exec(&query!(
         "update myspace.mymodel SET followers -= ? WHERE username = ?",
         50_000_000u64,
         &form_username
@@ -54,7 +53,7 @@ fn main() {
     .unwrap();
 
     // alright, everyone is tired from being rickrolled so we'll have to ban rick's account
-    db.query_parse::<()>(&query!(
+    db.exec(&query!(
         "delete from myspace.mymodel where username = ?",
         &form_username
     ))
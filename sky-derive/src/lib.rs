@@ -1,64 +1,701 @@
 extern crate proc_macro;
 
 use proc_macro::TokenStream;
+use proc_macro2::Span;
 use quote::quote;
-use syn::{parse_macro_input, Data, DeriveInput, Fields};
+use std::collections::HashSet;
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_macro_input,
+    punctuated::Punctuated,
+    Data, DeriveInput, Expr, Field, Fields, Ident, Lit, LitStr, Meta, MetaNameValue, NestedMeta,
+    Token, Variant,
+};
 
-#[proc_macro_derive(Query)]
+#[proc_macro_derive(Query, attributes(skytable))]
 pub fn derive_query(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
-    let name = input.ident;
+    match query(&input) {
+        Ok(ret) => TokenStream::from(ret),
+        Err(e) => TokenStream::from(e.to_compile_error()),
+    }
+}
+
+#[proc_macro_derive(Response, attributes(skytable))]
+pub fn derive_response(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match response(&input) {
+        Ok(ret) => TokenStream::from(ret),
+        Err(e) => TokenStream::from(e.to_compile_error()),
+    }
+}
+
+#[proc_macro_derive(SkyModel, attributes(skytable))]
+pub fn derive_sky_model(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match sky_model(&input) {
+        Ok(ret) => TokenStream::from(ret),
+        Err(e) => TokenStream::from(e.to_compile_error()),
+    }
+}
+
+/// A named field of a struct, with its `#[skytable(...)]` options
+struct NamedField<'a> {
+    field: &'a Field,
+    name: &'a Ident,
+    /// `#[skytable(skip)]`: the field isn't a parameter or a column, and is read as `Default::default()`
+    skip: bool,
+    /// `#[skytable(default)]`: the column may be missing from the end of a row, in which case the field is read as
+    /// `Default::default()`
+    default: bool,
+}
+
+/// Returns the fields of a struct with (at least one) named field that isn't skipped, or an error explaining why
+/// `derive` can't be used on it
+fn named_fields<'a>(derive: &str, input: &'a DeriveInput) -> syn::Result<Vec<NamedField<'a>>> {
+    let name = &input.ident;
+    let fields = match &input.data {
+        Data::Struct(data_struct) => &data_struct.fields,
+        Data::Enum(_) => {
+            return Err(syn::Error::new_spanned(
+                name,
+                format!("`{derive}` can only be derived for structs; for enums with unit variants, use `SkyEnum`"),
+            ))
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                name,
+                format!("`{derive}` can only be derived for structs with named fields"),
+            ))
+        }
+    };
+    let fields = match fields {
+        Fields::Named(fields) if !fields.named.is_empty() => &fields.named,
+        Fields::Named(_) => {
+            return Err(syn::Error::new_spanned(
+                name,
+                format!(
+                    "`{derive}` cannot be derived for empty structs; it requires at least one field"
+                ),
+            ))
+        }
+        Fields::Unit => {
+            return Err(syn::Error::new_spanned(
+                name,
+                format!(
+                    "`{derive}` cannot be derived for unit structs; it requires at least one field"
+                ),
+            ))
+        }
+        Fields::Unnamed(_) => {
+            return Err(syn::Error::new_spanned(
+                name,
+                format!("`{derive}` cannot be derived for tuple structs; use named fields instead"),
+            ))
+        }
+    };
+    let mut named = vec![];
+    for field in fields {
+        let (skip, default) = field_options(field)?;
+        named.push(NamedField {
+            field,
+            name: field.ident.as_ref().unwrap(),
+            skip,
+            default,
+        });
+    }
+    if named.iter().all(|f| f.skip) {
+        return Err(syn::Error::new_spanned(
+            name,
+            format!("`{derive}` requires at least one field without `#[skytable(skip)]`"),
+        ));
+    }
+    Ok(named)
+}
+
+/// Returns whether the field has a `#[skytable(skip)]` and a `#[skytable(default)]`
+fn field_options(field: &Field) -> syn::Result<(bool, bool)> {
+    let (mut skip, mut default) = (false, false);
+    for attr in field.attrs.iter().filter(|a| a.path.is_ident("skytable")) {
+        let list = match attr.parse_meta()? {
+            Meta::List(list) => list,
+            meta => {
+                return Err(syn::Error::new_spanned(
+                    meta,
+                    "expected `#[skytable(skip)]` or `#[skytable(default)]`",
+                ))
+            }
+        };
+        for nested in list.nested {
+            match nested {
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("skip") => skip = true,
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("default") => default = true,
+                nested => {
+                    return Err(syn::Error::new_spanned(
+                        nested,
+                        "expected `skip` or `default`",
+                    ))
+                }
+            }
+        }
+    }
+    Ok((skip, default))
+}
+
+fn query(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let fields = named_fields("Query", input)?;
+    let field_names = fields.iter().filter(|f| !f.skip).map(|f| f.name);
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::skytable::query::SQParam for #name #ty_generics #where_clause {
+            fn append_param(&self, q: &mut Vec<u8>) -> usize {
+                let mut size = 0;
+                #(size += ::skytable::query::SQParam::append_param(&self.#field_names, q);)*
+                size
+            }
+        }
+    })
+}
+
+/// Returns the number of columns that must be present in a row, that is, the columns before the first one with
+/// `#[skytable(default)]`. Only trailing columns can have a default, since a row can only be missing its last columns
+fn required_columns(columns: &[&NamedField]) -> syn::Result<usize> {
+    let mut required = None;
+    for (i, column) in columns.iter().enumerate() {
+        match (column.default, required) {
+            (true, None) => required = Some(i),
+            (false, Some(_)) => {
+                return Err(syn::Error::new_spanned(
+                    column.field,
+                    "a field without `#[skytable(default)]` can't follow one with it; only trailing fields can have a default",
+                ))
+            }
+            _ => {}
+        }
+    }
+    Ok(required.unwrap_or(columns.len()))
+}
+
+fn response(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let fields = named_fields("Response", input)?;
+    let columns: Vec<_> = fields.iter().filter(|f| !f.skip).collect();
+    let required = required_columns(&columns)?;
+    let column_names: Vec<_> = columns.iter().map(|f| f.name).collect();
+    let skipped = fields.iter().filter(|f| f.skip).map(|f| f.name);
+    let skipped = quote! { #(#skipped: ::core::default::Default::default(),)* };
+    let name = &input.ident;
     let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
-    let ret = match input.data {
-        Data::Struct(data_struct) => match data_struct.fields {
-            Fields::Named(fields) => {
-                let field_names: Vec<_> = fields.named.iter().map(|f| &f.ident).collect();
-                assert!(!field_names.is_empty(), "can't derive on empty field");
-                quote! {
-                    impl #impl_generics ::skytable::query::SQParam for #name #ty_generics #where_clause {
-                        fn append_param(&self, q: &mut Vec<u8>) -> usize {
-                            let mut size = 0;
-                            #(size += ::skytable::query::SQParam::append_param(&self.#field_names, q);)*
-                            size
+    let try_from = quote! {
+        impl #impl_generics ::core::convert::TryFrom<::skytable::response::Response> for #name #ty_generics #where_clause {
+            type Error = ::skytable::error::Error;
+            fn try_from(resp: ::skytable::response::Response) -> ::skytable::ClientResult<Self> {
+                ::skytable::response::FromResponse::from_response(resp)
+            }
+        }
+    };
+    if required != columns.len() {
+        // the row may be missing some trailing columns, so it can't be read as a tuple of a fixed size
+        let total = columns.len();
+        let (present, defaulted) = column_names.split_at(required);
+        return Ok(quote! {
+            impl #impl_generics ::skytable::response::FromResponse for #name #ty_generics #where_clause {
+                fn from_response(resp: ::skytable::response::Response) -> ::skytable::ClientResult<Self> {
+                    let row = match resp {
+                        ::skytable::response::Response::Row(r) => r.into_values(),
+                        ::skytable::response::Response::Error(e) => {
+                            return Err(::skytable::error::Error::ServerError(e))
+                        }
+                        _ => {
+                            return Err(::skytable::error::Error::ParseError(
+                                ::skytable::error::ParseError::ResponseMismatch,
+                            ))
                         }
+                    };
+                    if row.len() < #required || row.len() > #total {
+                        return Err(::skytable::error::Error::ParseError(
+                            ::skytable::error::ParseError::ColumnCountMismatch {
+                                expected: #total,
+                                actual: row.len(),
+                            },
+                        ));
                     }
+                    let mut values = row.into_iter();
+                    Ok(Self {
+                        #(#present: ::skytable::response::FromValue::from_value(values.next().unwrap())?,)*
+                        #(#defaulted: match values.next() {
+                            Some(v) => ::skytable::response::FromValue::from_value(v)?,
+                            None => ::core::default::Default::default(),
+                        },)*
+                        #skipped
+                    })
                 }
             }
-            _ => unimplemented!(),
-        },
-        _ => unimplemented!(),
+            #try_from
+        });
+    }
+    let tuple_pattern = if column_names.len() == 1 {
+        quote! { (#(#column_names),*,) }
+    } else {
+        quote! { (#(#column_names),*) }
     };
-    TokenStream::from(ret)
+    let struct_instantiation = quote! { Self { #(#column_names,)* #skipped } };
+    Ok(quote! {
+        impl #impl_generics ::skytable::response::FromResponse for #name #ty_generics #where_clause {
+            fn from_response(resp: ::skytable::response::Response) -> ::skytable::ClientResult<Self> {
+                let #tuple_pattern = ::skytable::response::FromResponse::from_response(resp)?;
+                Ok(#struct_instantiation)
+            }
+        }
+        #try_from
+    })
 }
 
-#[proc_macro_derive(Response)]
-pub fn derive_response(input: TokenStream) -> TokenStream {
+/// Both `Query` and `Response`, which read and write the same fields in the same order
+fn sky_model(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    // check the struct here, so that errors name this derive and not the ones it expands to
+    named_fields("SkyModel", input)?;
+    let mut ret = query(input)?;
+    ret.extend(response(input)?);
+    Ok(ret)
+}
+
+#[proc_macro_derive(SkyEnum, attributes(skytable, sky))]
+pub fn derive_sky_enum(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
+    match sky_enum(input) {
+        Ok(ret) => TokenStream::from(ret),
+        Err(e) => TokenStream::from(e.to_compile_error()),
+    }
+}
+
+/// Returns the `#[skytable(value = N)]` (or `#[sky(value = N)]`) of a variant, if it has one
+fn sky_value(variant: &Variant) -> syn::Result<Option<u64>> {
+    let mut value = None;
+    for attr in variant
+        .attrs
+        .iter()
+        .filter(|a| a.path.is_ident("skytable") || a.path.is_ident("sky"))
+    {
+        let list = match attr.parse_meta()? {
+            Meta::List(list) => list,
+            meta => {
+                return Err(syn::Error::new_spanned(
+                    meta,
+                    "expected `#[skytable(value = ...)]`",
+                ))
+            }
+        };
+        for nested in list.nested {
+            match nested {
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                    path,
+                    lit: Lit::Int(int),
+                    ..
+                })) if path.is_ident("value") => value = Some(int.base10_parse()?),
+                nested => {
+                    return Err(syn::Error::new_spanned(
+                        nested,
+                        "expected `value = ...` with an unsigned integer",
+                    ))
+                }
+            }
+        }
+    }
+    Ok(value)
+}
+
+fn sky_enum(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
     let name = input.ident;
     let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
-    let ret = match input.data {
-        Data::Struct(data_struct) => match data_struct.fields {
-            Fields::Named(fields) => {
-                let field_names: Vec<_> = fields.named.iter().map(|f| &f.ident).collect();
-                assert!(!field_names.is_empty(), "can't derive on empty field");
-                let tuple_pattern = if field_names.len() == 1 {
-                    quote! { (#(#field_names),*,) }
-                } else {
-                    quote! { (#(#field_names),*) }
-                };
-                let struct_instantiation = quote! { Self { #(#field_names),* } };
-                quote! {
-                    impl #impl_generics skytable::response::FromResponse for #name #ty_generics #where_clause {
-                        fn from_response(resp: skytable::response::Response) -> skytable::ClientResult<Self> {
-                            let #tuple_pattern = skytable::response::FromResponse::from_response(resp)?;
-                            Ok(#struct_instantiation)
-                        }
+    let data = match input.data {
+        Data::Enum(data) => data,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                name,
+                "`SkyEnum` can only be derived for enums",
+            ))
+        }
+    };
+    let mut variants = vec![];
+    let mut values: Vec<Option<u64>> = vec![];
+    for variant in &data.variants {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(syn::Error::new_spanned(
+                variant,
+                "`SkyEnum` only supports unit variants",
+            ));
+        }
+        let value = sky_value(variant)?;
+        if let Some(v) = value {
+            if let Some(i) = values.iter().position(|other| *other == Some(v)) {
+                return Err(syn::Error::new_spanned(
+                    variant,
+                    format!("`value = {v}` is already used by `{}`", variants[i]),
+                ));
+            }
+        }
+        variants.push(&variant.ident);
+        values.push(value);
+    }
+    if variants.is_empty() {
+        return Err(syn::Error::new_spanned(
+            name,
+            "`SkyEnum` needs at least one variant",
+        ));
+    }
+    let expected = name.to_string();
+    let mismatch = quote! {
+        Err(::skytable::error::Error::ParseError(
+            ::skytable::error::ParseError::TypeMismatchGot { expected: #expected, got: v },
+        ))
+    };
+    let (append, from_value) = if values.iter().all(Option::is_none) {
+        // stored as the name of the variant
+        let names: Vec<_> = variants.iter().map(|v| v.to_string()).collect();
+        (
+            quote! {
+                match self {
+                    #(Self::#variants => ::skytable::query::SQParam::append_param(&#names, q),)*
+                }
+            },
+            quote! {
+                if let ::skytable::response::Value::String(s) = &v {
+                    match s.as_str() {
+                        #(#names => return Ok(Self::#variants),)*
+                        _ => {}
                     }
                 }
+                #mismatch
+            },
+        )
+    } else if values.iter().all(Option::is_some) {
+        // stored as the integer given for each variant
+        let values: Vec<_> = values.into_iter().flatten().collect();
+        (
+            quote! {
+                match self {
+                    #(Self::#variants => ::skytable::query::SQParam::append_param(&#values, q),)*
+                }
+            },
+            quote! {
+                let n: Option<u64> = match &v {
+                    ::skytable::response::Value::UInt8(n) => Some(*n as u64),
+                    ::skytable::response::Value::UInt16(n) => Some(*n as u64),
+                    ::skytable::response::Value::UInt32(n) => Some(*n as u64),
+                    ::skytable::response::Value::UInt64(n) => Some(*n),
+                    _ => None,
+                };
+                match n {
+                    #(Some(#values) => Ok(Self::#variants),)*
+                    _ => #mismatch,
+                }
+            },
+        )
+    } else {
+        return Err(syn::Error::new_spanned(
+            name,
+            "either every variant or no variant must have a `#[skytable(value = ...)]`",
+        ));
+    };
+    Ok(quote! {
+        impl #impl_generics ::skytable::query::SQParam for #name #ty_generics #where_clause {
+            fn append_param(&self, q: &mut Vec<u8>) -> usize {
+                #append
+            }
+        }
+        impl #impl_generics ::skytable::response::FromValue for #name #ty_generics #where_clause {
+            fn from_value(v: ::skytable::response::Value) -> ::skytable::ClientResult<Self> {
+                #from_value
+            }
+        }
+        impl #impl_generics ::core::convert::TryFrom<::skytable::response::Value> for #name #ty_generics #where_clause {
+            type Error = ::skytable::error::Error;
+            fn try_from(v: ::skytable::response::Value) -> ::skytable::ClientResult<Self> {
+                ::skytable::response::FromValue::from_value(v)
+            }
+        }
+        impl #impl_generics ::core::convert::TryFrom<::skytable::response::Response> for #name #ty_generics #where_clause {
+            type Error = ::skytable::error::Error;
+            fn try_from(resp: ::skytable::response::Response) -> ::skytable::ClientResult<Self> {
+                ::skytable::response::FromResponse::from_response(resp)
             }
-            _ => unimplemented!(),
-        },
-        _ => unimplemented!(),
+        }
+    })
+}
+
+/// The arguments of `query!` with named parameters: the query string, followed by `name = value` bindings
+struct NamedQuery {
+    query: LitStr,
+    bindings: Punctuated<Binding, Token![,]>,
+}
+
+struct Binding {
+    name: Ident,
+    value: Expr,
+}
+
+impl Parse for NamedQuery {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let query = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let bindings = Punctuated::parse_terminated(input)?;
+        Ok(Self { query, bindings })
+    }
+}
+
+impl Parse for Binding {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name = input.parse()?;
+        input.parse::<Token![=]>()?;
+        let value = input.parse()?;
+        Ok(Self { name, value })
+    }
+}
+
+/// Implementation detail of `query!` with named parameters
+#[doc(hidden)]
+#[proc_macro]
+pub fn __query_named(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as NamedQuery);
+    match query_named(input) {
+        Ok(ret) => TokenStream::from(ret),
+        Err(e) => {
+            // this expands to an expression, so every error has to be in a block to be reported
+            let errors = e.to_compile_error();
+            TokenStream::from(quote! {{ #errors }})
+        }
+    }
+}
+
+fn is_ident_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_'
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Replace every `:name` in the query with a `?`, returning the new query and the names in the order they appear.
+/// A `:` right after an identifier (like in `username:string`) or another `:` (like in `a::b`), or inside a quoted
+/// string, isn't a placeholder
+fn rewrite_named(query: &str) -> Result<(String, Vec<&str>), &'static str> {
+    let mut rewritten = String::with_capacity(query.len());
+    let mut names = vec![];
+    let mut quote = None;
+    let mut escaped = false;
+    let mut prev = None;
+    let mut chars = query.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match quote {
+            Some(q) => {
+                if escaped {
+                    escaped = false;
+                } else if c == '\\' {
+                    escaped = true;
+                } else if c == q {
+                    quote = None;
+                }
+            }
+            None if c == '\'' || c == '"' => quote = Some(c),
+            None if c == '?' => {
+                return Err("`?` placeholders can't be mixed with named parameters")
+            }
+            None if c == ':'
+                && !prev.is_some_and(|p| is_ident_char(p) || p == ':')
+                && chars.peek().is_some_and(|(_, c)| is_ident_start(*c)) =>
+            {
+                let start = i + 1;
+                let mut end = start;
+                while let Some((j, c)) = chars.next_if(|(_, c)| is_ident_char(*c)) {
+                    end = j + c.len_utf8();
+                }
+                names.push(&query[start..end]);
+                rewritten.push('?');
+                prev = Some('?');
+                continue;
+            }
+            None => {}
+        }
+        rewritten.push(c);
+        prev = Some(c);
+    }
+    Ok((rewritten, names))
+}
+
+fn query_named(input: NamedQuery) -> syn::Result<proc_macro2::TokenStream> {
+    let query = input.query.value();
+    let (rewritten, placeholders) =
+        rewrite_named(&query).map_err(|e| syn::Error::new_spanned(&input.query, e))?;
+    let mut errors: Option<syn::Error> = None;
+    let mut error = |e: syn::Error| match &mut errors {
+        Some(errors) => errors.combine(e),
+        None => errors = Some(e),
     };
-    TokenStream::from(ret)
+    let mut bound = HashSet::new();
+    for binding in &input.bindings {
+        let name = binding.name.to_string();
+        if !bound.insert(name.clone()) {
+            error(syn::Error::new_spanned(
+                &binding.name,
+                format!("the parameter `{name}` is bound more than once"),
+            ));
+        } else if !placeholders.contains(&name.as_str()) {
+            error(syn::Error::new_spanned(
+                &binding.name,
+                format!("the parameter `{name}` isn't used in the query; add `:{name}` to it"),
+            ));
+        }
+    }
+    let mut missing = HashSet::new();
+    for name in &placeholders {
+        if !bound.contains(*name) && missing.insert(*name) {
+            error(syn::Error::new_spanned(
+                &input.query,
+                format!("no value is bound for the parameter `:{name}`; add `{name} = ...`"),
+            ));
+        }
+    }
+    if let Some(errors) = errors {
+        return Err(errors);
+    }
+    // evaluate every value once, in the order they're written, then push them in the order the query needs them
+    let local = |name: &str| Ident::new(&format!("__sky_param_{name}"), Span::mixed_site());
+    let values = input.bindings.iter().map(|b| &b.value);
+    let locals: Vec<_> = input
+        .bindings
+        .iter()
+        .map(|b| local(&b.name.to_string()))
+        .collect();
+    let pushes = placeholders.iter().map(|name| local(name));
+    let q = Ident::new("q", Span::mixed_site());
+    Ok(quote! {
+        match (#(#values,)*) {
+            (#(#locals,)*) => {
+                let mut #q = ::skytable::Query::from(#rewritten);
+                #(#q.push_param(&#pushes);)*
+                #q
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn errors(input: &str) -> Vec<String> {
+        let input = syn::parse_str(input).unwrap();
+        match query_named(input) {
+            Ok(_) => vec![],
+            Err(e) => e.into_iter().map(|e| e.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn rewrite_placeholders() {
+        assert_eq!(
+            rewrite_named("select * from myspace.users where username = :name and age > :age"),
+            Ok((
+                "select * from myspace.users where username = ? and age > ?".to_owned(),
+                vec!["name", "age"]
+            ))
+        );
+        // the same name can be used more than once, and is pushed each time
+        assert_eq!(
+            rewrite_named("update u set a = :v, b = :v"),
+            Ok(("update u set a = ?, b = ?".to_owned(), vec!["v", "v"]))
+        );
+        assert_eq!(rewrite_named(":a"), Ok(("?".to_owned(), vec!["a"])));
+        assert_eq!(rewrite_named(""), Ok((String::new(), vec![])));
+    }
+
+    #[test]
+    fn rewrite_quoted() {
+        assert_eq!(
+            rewrite_named(r#"select ':not_a_name', "also :not", :name"#),
+            Ok((
+                r#"select ':not_a_name', "also :not", ?"#.to_owned(),
+                vec!["name"]
+            ))
+        );
+        // an escaped quote doesn't end the string
+        assert_eq!(
+            rewrite_named(r"select 'it\'s :not', :name"),
+            Ok((r"select 'it\'s :not', ?".to_owned(), vec!["name"]))
+        );
+        // nor does the other kind of quote
+        assert_eq!(
+            rewrite_named(r#"select '" :not', :name"#),
+            Ok((r#"select '" :not', ?"#.to_owned(), vec!["name"]))
+        );
+        // a `?` inside a string isn't a positional placeholder
+        assert_eq!(
+            rewrite_named("select 'why?', :name"),
+            Ok(("select 'why?', ?".to_owned(), vec!["name"]))
+        );
+    }
+
+    #[test]
+    fn rewrite_colons_that_are_not_placeholders() {
+        for query in [
+            "create model myspace.users(username: string)",
+            "create model myspace.users(username:string)",
+            "select a::b",
+            "select ::b",
+            "select :: b",
+            "select : b",
+            "select :1",
+            "select a:",
+        ] {
+            assert_eq!(
+                rewrite_named(query),
+                Ok((query.to_owned(), vec![])),
+                "{query}"
+            );
+        }
+        assert_eq!(
+            rewrite_named("select :a::b"),
+            Ok(("select ?::b".to_owned(), vec!["a"]))
+        );
+    }
+
+    #[test]
+    fn rewrite_mixed_with_positional() {
+        assert_eq!(
+            rewrite_named("select ?, :name"),
+            Err("`?` placeholders can't be mixed with named parameters")
+        );
+        assert_eq!(
+            errors(r#""select * from u where a = ? and b = :b", b = 1"#),
+            ["`?` placeholders can't be mixed with named parameters"]
+        );
+    }
+
+    #[test]
+    fn bindings() {
+        assert!(errors(r#""select :a, :b, :a", b = 2, a = 1"#).is_empty());
+        assert_eq!(
+            errors(r#""select :a", a = 1, a = 2"#),
+            ["the parameter `a` is bound more than once"]
+        );
+        assert_eq!(
+            errors(r#""select :a", a = 1, b = 2"#),
+            ["the parameter `b` isn't used in the query; add `:b` to it"]
+        );
+        // a missing binding is reported once, however often it's used
+        assert_eq!(
+            errors(r#""select :a, :b, :b", a = 1"#),
+            ["no value is bound for the parameter `:b`; add `b = ...`"]
+        );
+        // every problem is reported at once
+        assert_eq!(
+            errors(r#""select :a", b = 1, b = 2"#),
+            [
+                "the parameter `b` isn't used in the query; add `:b` to it",
+                "the parameter `b` is bound more than once",
+                "no value is bound for the parameter `:a`; add `a = ...`",
+            ]
+        );
+    }
 }
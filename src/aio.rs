@@ -23,20 +23,34 @@
 
 use {
     crate::{
-        error::{ClientResult, ConnectionSetupError, Error},
-        protocol::{ClientHandshake, DecodeState, Decoder, RState, ServerHandshake},
-        response::{FromResponse, Response},
+        error::{ClientResult, ConnectionSetupError, Error, ProtocolError},
+        protocol::{
+            wire, ClientHandshake, DecodeState, Decoder, MRespState, PipelineResult, RState,
+            ServerHandshake,
+        },
+        query::{Pipeline, PreparedQuery, SQParam},
+        queryable::Queryable,
+        response::{Entity, FromResponse, GlobalInfo, Response},
+        schema::ModelSchema,
+        tls::TlsStreamAsync,
         Config, Query,
     },
-    native_tls::Certificate,
-    std::ops::{Deref, DerefMut},
+    std::{
+        ops::{ControlFlow, Deref, DerefMut},
+        path::Path,
+        pin::Pin,
+        task::Poll,
+        time::{Duration, Instant},
+    },
     tokio::{
         io::{AsyncReadExt, AsyncWriteExt},
         net::TcpStream,
     },
-    tokio_native_tls::{TlsConnector, TlsStream},
 };
 
+#[cfg(feature = "async-std")]
+use std::task::Context;
+
 #[derive(Debug)]
 /// An async `skyhash/TCP` connection
 ///
@@ -52,7 +66,17 @@ pub struct ConnectionAsync(TcpConnection<TcpStream>);
 /// - Protocol version: `Skyhash/2.0`
 /// - Query mode: `QTDEX-1A/BQL-S1`
 /// - Authentication plugin: `pwd`
-pub struct ConnectionTlsAsync(TcpConnection<TlsStream<TcpStream>>);
+pub struct ConnectionTlsAsync(TcpConnection<TlsStreamAsync>);
+
+#[cfg(feature = "async-std")]
+#[derive(Debug)]
+/// An async `skyhash/TCP` connection that uses `async-std` for I/O
+///
+/// **Specification**
+/// - Protocol version: `Skyhash/2.0`
+/// - Query mode: `QTDEX-1A/BQL-S1`
+/// - Authentication plugin: `pwd`
+pub struct ConnectionAsyncStd(TcpConnection<AsyncStdStream>);
 
 impl Deref for ConnectionAsync {
     type Target = TcpConnection<TcpStream>;
@@ -66,7 +90,7 @@ impl DerefMut for ConnectionAsync {
     }
 }
 impl Deref for ConnectionTlsAsync {
-    type Target = TcpConnection<TlsStream<TcpStream>>;
+    type Target = TcpConnection<TlsStreamAsync>;
     fn deref(&self) -> &Self::Target {
         &self.0
     }
@@ -77,56 +101,326 @@ impl DerefMut for ConnectionTlsAsync {
     }
 }
 
+#[cfg(feature = "async-std")]
+impl Deref for ConnectionAsyncStd {
+    type Target = TcpConnection<AsyncStdStream>;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+#[cfg(feature = "async-std")]
+impl DerefMut for ConnectionAsyncStd {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl ConnectionAsync {
+    /// Close the connection, flushing anything that's still buffered and shutting down the TCP stream, instead of just
+    /// dropping it (see [`TcpConnection::close`])
+    pub async fn close(self) -> ClientResult<()> {
+        self.0.close().await
+    }
+}
+impl ConnectionTlsAsync {
+    /// Close the connection, ending the TLS session with a `close_notify` alert and then shutting down the TCP stream
+    /// (see [`TcpConnection::close`])
+    pub async fn close(self) -> ClientResult<()> {
+        self.0.close().await
+    }
+}
+#[cfg(feature = "async-std")]
+impl ConnectionAsyncStd {
+    /// Close the connection, flushing anything that's still buffered and shutting down the TCP stream, instead of just
+    /// dropping it (see [`TcpConnection::close`])
+    pub async fn close(self) -> ClientResult<()> {
+        self.0.close().await
+    }
+}
+
+#[cfg(feature = "async-std")]
+#[derive(Debug)]
+/// An `async-std` TCP stream, adapted to tokio's I/O traits so that it can be driven by a [`TcpConnection`]
+pub struct AsyncStdStream(async_std::net::TcpStream);
+
+#[cfg(feature = "async-std")]
+impl tokio::io::AsyncRead for AsyncStdStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        use async_std::io::Read;
+        let n = match Pin::new(&mut self.0).poll_read(cx, buf.initialize_unfilled()) {
+            Poll::Ready(r) => r?,
+            Poll::Pending => return Poll::Pending,
+        };
+        buf.advance(n);
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(feature = "async-std")]
+impl tokio::io::AsyncWrite for AsyncStdStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        use async_std::io::Write;
+        Pin::new(&mut self.0).poll_write(cx, buf)
+    }
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        use async_std::io::Write;
+        Pin::new(&mut self.0).poll_flush(cx)
+    }
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        use async_std::io::Write;
+        Pin::new(&mut self.0).poll_close(cx)
+    }
+}
+
 impl Config {
+    /// Open a TCP connection to the first endpoint that accepts one (see [`Config::with_hosts`]), returning the stream
+    /// and the host that it's connected to
+    async fn connect_tcp_async(&self) -> std::io::Result<(TcpStream, &str)> {
+        let mut last_err = None;
+        for (host, port) in self.hosts() {
+            let addrs = match tokio::net::lookup_host((host, port)).await {
+                Ok(addrs) => self.order_addrs(addrs),
+                Err(e) => {
+                    last_err = Some(e);
+                    continue;
+                }
+            };
+            match TcpStream::connect(&addrs[..]).await {
+                Ok(stream) => return Ok((stream, host)),
+                Err(e) => last_err = Some(e),
+            }
+        }
+        // there's always at least one endpoint
+        Err(last_err.unwrap())
+    }
     /// Establish an async connection to the database using the current configuration
+    ///
+    /// ## Example
+    /// ```no_run
+    /// use skytable::Config;
+    ///
+    /// async fn run() {
+    ///     let mut db = Config::new_default("username", "password").connect_async().await.unwrap();
+    /// }
+    /// ```
     pub async fn connect_async(&self) -> ClientResult<ConnectionAsync> {
-        let mut tcpstream = TcpStream::connect((self.host(), self.port())).await?;
-        let handshake = ClientHandshake::new(self);
-        tcpstream.write_all(handshake.inner()).await?;
-        let mut resp = [0u8; 4];
-        tcpstream.read_exact(&mut resp).await?;
-        match ServerHandshake::parse(resp)? {
-            ServerHandshake::Error(e) => return Err(ConnectionSetupError::HandshakeError(e).into()),
-            ServerHandshake::Okay(_suggestion) => {
-                return Ok(ConnectionAsync(TcpConnection::new(tcpstream)))
+        let (tcpstream, _) = self.connect_tcp_async().await?;
+        self.connect_async_over(tcpstream)
+            .await
+            .map(ConnectionAsync)
+    }
+    #[cfg(feature = "socks")]
+    /// Establish an async connection to the database through the SOCKS5 proxy at `proxy_addr` (like
+    /// [`Config::connect_via_socks5`]). This is available with the `socks` feature
+    ///
+    /// ## Example
+    /// ```no_run
+    /// use skytable::Config;
+    ///
+    /// async fn run() {
+    ///     let mut db = Config::new("subnetx2_db1", 2008, "username", "password")
+    ///         .connect_async_via_socks5("127.0.0.1:1080")
+    ///         .await
+    ///         .unwrap();
+    /// }
+    /// ```
+    pub async fn connect_async_via_socks5(
+        &self,
+        proxy_addr: impl tokio_socks::ToProxyAddrs + Clone,
+    ) -> ClientResult<ConnectionAsync> {
+        let mut last_err = None;
+        for endpoint in self.hosts() {
+            match tokio_socks::tcp::Socks5Stream::connect(proxy_addr.clone(), endpoint).await {
+                Ok(stream) => {
+                    return self
+                        .connect_async_over(stream.into_inner())
+                        .await
+                        .map(ConnectionAsync)
+                }
+                Err(e) => last_err = Some(e),
             }
         }
+        Err(match last_err.unwrap() {
+            tokio_socks::Error::Io(e) => Error::IoError(e),
+            e => ConnectionSetupError::Other(format!("socks5 proxy error: {e}")).into(),
+        })
     }
-    /// Establish an async TLS connection to the database using the current configuration.
-    /// Pass the certificate in PEM format.
-    pub async fn connect_tls_async(&self, cert: &str) -> ClientResult<ConnectionTlsAsync> {
-        let stream = TcpStream::connect((self.host(), self.port())).await?;
-        // set up acceptor
-        let mut builder = native_tls::TlsConnector::builder();
-        builder
-            .add_root_certificate(Certificate::from_pem(cert.as_bytes()).map_err(|e| {
-                ConnectionSetupError::Other(format!("failed to parse certificate: {e}"))
-            })?)
-            .danger_accept_invalid_hostnames(true)
-            .build()
-            .map_err(|e| {
-                ConnectionSetupError::Other(format!("failed to set up TLS acceptor: {e}"))
-            })?;
-        let connector = builder.build().map_err(|e| {
-            ConnectionSetupError::Other(format!("failed to set up TLS acceptor: {e}"))
-        })?;
-        // init
-        let mut stream = TlsConnector::from(connector)
-            .connect(self.host(), stream)
-            .await
-            .map_err(|e| ConnectionSetupError::Other(format!("TLS handshake failed: {e}")))?;
-        // handshake
+    #[cfg(feature = "async-std")]
+    /// Establish an async connection to the database using the current configuration, with `async-std` (instead of
+    /// tokio) doing the I/O. This is available with the `async-std` feature
+    ///
+    /// ## Example
+    /// ```no_run
+    /// use skytable::Config;
+    ///
+    /// async fn run() {
+    ///     let mut db = Config::new_default("username", "password").connect_async_std().await.unwrap();
+    /// }
+    /// ```
+    pub async fn connect_async_std(&self) -> ClientResult<ConnectionAsyncStd> {
+        use async_std::net::ToSocketAddrs;
+        let mut last_err = None;
+        for (host, port) in self.hosts() {
+            let addrs = match (host, port).to_socket_addrs().await {
+                Ok(addrs) => self.order_addrs(addrs),
+                Err(e) => {
+                    last_err = Some(e);
+                    continue;
+                }
+            };
+            match async_std::net::TcpStream::connect(&addrs[..]).await {
+                Ok(stream) => {
+                    return self
+                        .connect_async_over(AsyncStdStream(stream))
+                        .await
+                        .map(ConnectionAsyncStd)
+                }
+                Err(e) => last_err = Some(e),
+            }
+        }
+        // there's always at least one endpoint
+        Err(last_err.unwrap().into())
+    }
+    /// Establish an async connection over a stream that you have already set up, by running the handshake on it
+    ///
+    /// The stream only has to implement tokio's [`AsyncRead`](tokio::io::AsyncRead) and
+    /// [`AsyncWrite`](tokio::io::AsyncWrite), so this is how you can use the async API with something other than a tokio
+    /// [`TcpStream`]: a Unix socket, a proxied stream, or a socket from another runtime (such as `smol`) wrapped in a
+    /// compatibility adapter like the one in the `async-compat` crate. For `async-std`, see `connect_async_std`.
+    ///
+    /// ## Example
+    /// ```no_run
+    /// use skytable::Config;
+    ///
+    /// async fn run() {
+    ///     let stream = tokio::net::TcpStream::connect("127.0.0.1:2003").await.unwrap();
+    ///     let mut db = Config::new_default("username", "password")
+    ///         .connect_async_over(stream)
+    ///         .await
+    ///         .unwrap();
+    /// }
+    /// ```
+    pub async fn connect_async_over<C: AsyncWriteExt + AsyncReadExt + Unpin>(
+        &self,
+        mut stream: C,
+    ) -> ClientResult<TcpConnection<C>> {
         let handshake = ClientHandshake::new(self);
         stream.write_all(handshake.inner()).await?;
-        let mut resp = [0u8; 4];
+        let mut resp = [0u8; wire::SERVER_HANDSHAKE_LEN];
         stream.read_exact(&mut resp).await?;
         match ServerHandshake::parse(resp)? {
-            ServerHandshake::Error(e) => return Err(ConnectionSetupError::HandshakeError(e).into()),
-            ServerHandshake::Okay(_suggestion) => {
-                return Ok(ConnectionTlsAsync(TcpConnection::new(stream)))
-            }
+            ServerHandshake::Error(e) => Err(ConnectionSetupError::from_handshake_code(e).into()),
+            ServerHandshake::Okay(_suggestion) => Ok(TcpConnection::new(stream, self)),
         }
     }
+    /// Establish an async TLS connection to the database using the current configuration.
+    /// Pass the certificate in PEM format.
+    ///
+    /// ## Example
+    /// ```no_run
+    /// use skytable::{config::DEFAULT_TLS_PORT, Config};
+    ///
+    /// async fn run() {
+    ///     let cert = tokio::fs::read_to_string("cert.pem").await.unwrap();
+    ///     let mut db = Config::new("127.0.0.1", DEFAULT_TLS_PORT, "username", "password")
+    ///         .connect_tls_async(&cert)
+    ///         .await
+    ///         .unwrap();
+    /// }
+    /// ```
+    pub async fn connect_tls_async(&self, cert: &str) -> ClientResult<ConnectionTlsAsync> {
+        let (stream, host) = self.connect_tcp_async().await?;
+        let stream = crate::tls::connect_async(host, cert, stream).await?;
+        // handshake
+        self.connect_async_over(stream)
+            .await
+            .map(ConnectionTlsAsync)
+    }
+    /// Establish an async TLS connection to the database using the current configuration, reading the PEM certificate
+    /// from the file at the given path
+    ///
+    /// ## Example
+    /// ```no_run
+    /// use skytable::{config::DEFAULT_TLS_PORT, Config};
+    ///
+    /// async fn run() {
+    ///     let mut db = Config::new("127.0.0.1", DEFAULT_TLS_PORT, "username", "password")
+    ///         .connect_async_tls_from_file("cert.pem")
+    ///         .await
+    ///         .unwrap();
+    /// }
+    /// ```
+    pub async fn connect_async_tls_from_file(
+        &self,
+        cert_path: impl AsRef<Path>,
+    ) -> ClientResult<ConnectionTlsAsync> {
+        let cert = tokio::fs::read_to_string(cert_path).await?;
+        self.connect_tls_async(&cert).await
+    }
+}
+
+/// Run independent queries concurrently, each on its own connection from the pool, and return their responses in the
+/// same order as the queries
+///
+/// Unlike a [`Pipeline`], which sends all its queries over one connection and has the server run them one after the
+/// other, every query here is spawned as a separate tokio task. At most as many queries as the pool's maximum size run
+/// at once; the rest wait for a connection to be returned to the pool, and fail with [`Error::Timeout`] if they wait
+/// longer than the pool's connection timeout. A failed query doesn't affect the others
+///
+/// ## Example
+/// ```no_run
+/// use skytable::{aio, pool, query, Config};
+///
+/// async fn run() {
+///     let pool = pool::get_async(8, Config::new_default("username", "password")).await.unwrap();
+///     let resps = aio::query_all(
+///         &pool,
+///         vec![
+///             query!("select * from myspace.users where username = ?", "sayan"),
+///             query!("select * from myspace.users where username = ?", "elana"),
+///         ],
+///     )
+///     .await;
+///     assert_eq!(resps.len(), 2);
+/// }
+/// ```
+pub async fn query_all<M>(
+    pool: &bb8::Pool<M>,
+    queries: impl IntoIterator<Item = Query>,
+) -> Vec<ClientResult<Response>>
+where
+    M: bb8::ManageConnection<Error = Error>,
+    M::Connection: Queryable,
+{
+    let tasks: Vec<_> = queries
+        .into_iter()
+        .map(|q| {
+            let pool = pool.clone();
+            tokio::spawn(async move {
+                let mut db = pool.get().await.map_err(|e| match e {
+                    bb8::RunError::User(e) => e,
+                    bb8::RunError::TimedOut => Error::Timeout,
+                })?;
+                Queryable::query(&mut *db, &q).await
+            })
+        })
+        .collect();
+    let mut resps = Vec::with_capacity(tasks.len());
+    for task in tasks {
+        resps.push(
+            task.await
+                .unwrap_or_else(|e| std::panic::resume_unwind(e.into_panic())),
+        );
+    }
+    resps
 }
 
 #[derive(Debug)]
@@ -134,37 +428,188 @@ impl Config {
 pub struct TcpConnection<C: AsyncWriteExt + AsyncReadExt + Unpin> {
     con: C,
     buf: Vec<u8>,
+    redact_logs: bool,
+    max_retained_buffer: Option<usize>,
+    direct_write_threshold: Option<usize>,
+    read_buffer_size: usize,
+    max_response_size: Option<usize>,
+    lossy_utf8: bool,
+    queries_sent: u64,
+    bytes_sent: u64,
+    bytes_received: u64,
+    current_space: Option<String>,
+    /// set when a packet is sent and cleared once all of its responses are read, so that it stays set if the future was
+    /// dropped (or failed) in between
+    broken: bool,
+    /// the number of responses to the last packet that haven't been read yet, and the number of responses at the start of
+    /// the buffer that were already read (see `resync`)
+    unread_responses: usize,
+    read_in_buffer: usize,
+    /// when a connection pool last checked this connection with a status query (see `pool::check_async`)
+    pub(crate) last_status_check: Option<Instant>,
 }
 
 impl<C: AsyncWriteExt + AsyncReadExt + Unpin> TcpConnection<C> {
-    fn new(con: C) -> Self {
+    fn new(con: C, cfg: &Config) -> Self {
         Self {
             con,
-            buf: Vec::with_capacity(crate::BUFSIZE),
+            buf: Vec::with_capacity(cfg.read_buffer_size()),
+            redact_logs: cfg.redact_logs(),
+            max_retained_buffer: cfg.max_retained_buffer(),
+            direct_write_threshold: cfg.direct_write_threshold(),
+            read_buffer_size: cfg.read_buffer_size(),
+            max_response_size: cfg.max_response_size(),
+            lossy_utf8: cfg.lossy_utf8(),
+            queries_sent: 0,
+            bytes_sent: 0,
+            bytes_received: 0,
+            current_space: None,
+            broken: false,
+            unread_responses: 0,
+            read_in_buffer: 0,
+            last_status_check: None,
         }
     }
     /// Run a query and return a raw [`Response`]
     pub async fn query(&mut self, q: &Query) -> ClientResult<Response> {
+        self.check_broken()?;
         self.buf.clear();
-        q.write_packet(&mut self.buf).unwrap();
+        match self.direct_write_threshold {
+            Some(min_size) if q.payload().len() >= min_size => {
+                // only the header goes through the buffer; the payload is written straight from the query
+                q.write_header(&mut self.buf).unwrap();
+                q.log_unbuffered(self.redact_logs);
+                self.send_packet_then(1, q.payload()).await?;
+            }
+            _ => {
+                q.write_packet(&mut self.buf).unwrap();
+                q.log_packet(&self.buf, self.redact_logs);
+                self.send_packet(1).await?;
+            }
+        }
+        self.read_response().await
+    }
+    /// Run a query and return a raw [`Response`], failing with [`Error::Timeout`] if the query doesn't complete within
+    /// `timeout`. Like with `tokio::time::timeout`, the connection is then marked as broken (see [`Self::is_broken`]).
+    /// This uses tokio's timer, so it must be called from within a tokio runtime
+    ///
+    /// ## Example
+    /// ```no_run
+    /// use {skytable::{query, Config}, std::time::Duration};
+    ///
+    /// async fn run() {
+    ///     let mut db = Config::new_default("username", "password").connect_async().await.unwrap();
+    ///     let q = query!("select all * from myspace.mymodel limit ?", 1000u64);
+    ///     let resp = db.query_timeout(&q, Duration::from_secs(30)).await.unwrap();
+    /// }
+    /// ```
+    pub async fn query_timeout(&mut self, q: &Query, timeout: Duration) -> ClientResult<Response> {
+        match tokio::time::timeout(timeout, self.query(q)).await {
+            Ok(resp) => resp,
+            Err(_) => Err(Error::Timeout),
+        }
+    }
+    /// Run a query that has no parameters, straight from its query string, and parse the response into the indicated type
+    /// (like [`Self::query_parse`]). The packet is encoded directly into the connection's buffer, so unlike
+    /// `query_parse(&query!(...))`, no [`Query`](struct@Query) is allocated
+    ///
+    /// ## Example
+    /// ```no_run
+    /// use skytable::Config;
+    ///
+    /// async fn run() {
+    ///     let mut db = Config::new_default("username", "password").connect_async().await.unwrap();
+    ///     db.run_str::<()>("create space x").await.unwrap();
+    /// }
+    /// ```
+    pub async fn run_str<T: FromResponse>(&mut self, q: &str) -> ClientResult<T> {
+        self.check_broken()?;
+        crate::query::write_str_packet(q, &mut self.buf);
+        crate::query::log_str_packet(&self.buf, self.redact_logs);
+        self.send_packet(1).await?;
+        self.read_response()
+            .await
+            .and_then(FromResponse::from_response)
+    }
+    /// Run a prepared statement with the given parameters and return a raw [`Response`]
+    ///
+    /// This is equivalent to running a [`Query`](struct@Query) with the same query string and parameters, but the statement's query
+    /// string is only encoded once (see [`PreparedQuery`])
+    pub async fn run_prepared(
+        &mut self,
+        pq: &PreparedQuery,
+        params: impl SQParam,
+    ) -> ClientResult<Response> {
+        self.check_broken()?;
+        let param_cnt = pq.write_packet_into(&mut self.buf, params);
+        pq.log_packet(&self.buf, param_cnt, self.redact_logs);
+        self.send_packet(1).await?;
+        self.read_response().await
+    }
+    /// Send a hand-crafted packet as is and decode a single response with the standard decoder
+    ///
+    /// **This is an escape hatch** for fuzzing, proxies and protocol experiments. The packet isn't validated in any way,
+    /// so a malformed packet (or one that makes the server send more than one response) can leave the connection in an
+    /// unusable state. Raw packets are also never logged, since their parameters can't be redacted. Use
+    /// [`Self::query`] for everything else
+    pub async fn send_raw(&mut self, packet: &[u8]) -> ClientResult<Response> {
+        self.check_broken()?;
+        self.buf.clear();
+        self.buf.extend_from_slice(packet);
+        self.send_packet(1).await?;
+        self.read_response().await
+    }
+    /// Fail with an I/O error if the connection is broken (see [`Self::is_broken`]), before anything is written to the
+    /// buffer, so that a broken connection's buffer still holds what was read for the failed query
+    fn check_broken(&self) -> ClientResult<()> {
+        if self.broken {
+            return Err(Error::IoError(std::io::Error::new(
+                std::io::ErrorKind::BrokenPipe,
+                "the connection was left in an unknown state by a cancelled or failed query",
+            )));
+        }
+        Ok(())
+    }
+    /// Write the encoded packet in the buffer to the connection
+    async fn send_packet(&mut self, query_count: usize) -> ClientResult<()> {
+        self.send_packet_then(query_count, &[]).await
+    }
+    /// Write the encoded packet in the buffer to the connection, followed by `tail` (which is written as is, without
+    /// copying it into the buffer)
+    async fn send_packet_then(&mut self, query_count: usize, tail: &[u8]) -> ClientResult<()> {
+        self.check_broken()?;
+        self.broken = true;
+        // if the packet is only partly written, the server is still waiting for the rest, so there's no way to resync
+        self.unread_responses = 0;
         self.con.write_all(&self.buf).await?;
+        if !tail.is_empty() {
+            self.con.write_all(tail).await?;
+        }
+        self.unread_responses = query_count;
+        self.read_in_buffer = 0;
+        self.queries_sent += query_count as u64;
+        self.bytes_sent += (self.buf.len() + tail.len()) as u64;
         self.buf.clear();
+        Ok(())
+    }
+    /// Read and decode a single response
+    async fn read_response(&mut self) -> ClientResult<Response> {
         let mut state = RState::default();
         let mut cursor = 0;
         let mut expected = Decoder::MIN_READBACK;
         loop {
-            let mut buf = [0u8; crate::BUFSIZE];
-            let n = self.con.read(&mut buf).await?;
-            if n == 0 {
-                return Err(Error::IoError(std::io::ErrorKind::ConnectionReset.into()));
-            }
-            if n < expected {
+            if self.read_more().await? < expected {
                 continue;
             }
-            self.buf.extend_from_slice(&buf[..n]);
-            let mut decoder = Decoder::new(&self.buf, cursor);
+            let mut decoder = Decoder::new(&self.buf, cursor)
+                .lossy_utf8(self.lossy_utf8)
+                .max_size(self.max_response_size);
             match decoder.validate_response(state) {
-                DecodeState::Completed(resp) => return Ok(resp),
+                DecodeState::Completed(resp) => {
+                    self.broken = false;
+                    self.release_buffer();
+                    return Ok(resp);
+                }
                 DecodeState::ChangeState(_state) => {
                     expected = 1;
                     state = _state;
@@ -174,13 +619,497 @@ impl<C: AsyncWriteExt + AsyncReadExt + Unpin> TcpConnection<C> {
             }
         }
     }
+    /// Execute a pipeline and return one [`Response`] for every query in it, in the order the queries were added
+    ///
+    /// An empty pipeline is not sent to the server, and returns no responses
+    pub async fn execute_pipeline(&mut self, pipeline: &Pipeline) -> ClientResult<Vec<Response>> {
+        let mut resps = Vec::with_capacity(pipeline.query_count());
+        self.run_pipeline(pipeline, |resp| resps.push(resp)).await?;
+        Ok(resps)
+    }
+    /// Execute a pipeline, calling `on_response` with the index of the query and its [`Response`] as soon as each response
+    /// is decoded, in the order the queries were added. Unlike [`Self::execute_pipeline`], the responses are not collected,
+    /// so the memory used doesn't grow with the number of queries
+    ///
+    /// If `on_response` returns [`ControlFlow::Break`], it won't be called again. The remaining responses are still read
+    /// (and discarded) so that the connection can be used for more queries.
+    pub async fn execute_pipeline_streaming(
+        &mut self,
+        pipeline: &Pipeline,
+        mut on_response: impl FnMut(usize, Response) -> ControlFlow<()>,
+    ) -> ClientResult<()> {
+        let mut idx = 0;
+        let mut stopped = false;
+        self.run_pipeline(pipeline, |resp| {
+            if !stopped {
+                stopped = on_response(idx, resp).is_break();
+            }
+            idx += 1;
+        })
+        .await
+    }
+    /// Send a pipeline and pass every decoded response to `on_response`
+    async fn run_pipeline(
+        &mut self,
+        pipeline: &Pipeline,
+        mut on_response: impl FnMut(Response),
+    ) -> ClientResult<()> {
+        if pipeline.query_count() == 0 {
+            return Ok(());
+        }
+        self.check_broken()?;
+        self.buf.clear();
+        pipeline.write_packet(&mut self.buf).unwrap();
+        pipeline.log_packet(&self.buf, self.redact_logs);
+        self.send_packet(pipeline.query_count()).await?;
+        let mut state = MRespState::default();
+        let mut cursor = 0;
+        loop {
+            self.read_more().await?;
+            let mut decoder = Decoder::new(&self.buf, cursor)
+                .lossy_utf8(self.lossy_utf8)
+                .max_size(self.max_response_size);
+            let decoded = state.decoded();
+            let result =
+                decoder.validate_pipe(pipeline.query_count(), &mut state, &mut on_response);
+            self.unread_responses -= state.decoded() - decoded;
+            self.read_in_buffer += state.decoded() - decoded;
+            match result {
+                PipelineResult::Completed => {
+                    self.broken = false;
+                    self.release_buffer();
+                    return Ok(());
+                }
+                PipelineResult::Pending if state.at_boundary() => {
+                    // everything read so far has been decoded
+                    self.buf.clear();
+                    self.read_in_buffer = 0;
+                    cursor = 0;
+                }
+                PipelineResult::Pending => cursor = decoder.position(),
+                PipelineResult::Error(e) => return Err(Error::ProtocolError(e)),
+            }
+        }
+    }
+    /// Run a query, only checking that it succeeded. Unlike `query_parse::<()>`, this accepts any successful response
+    /// (such as a count of affected rows) and not just [`Response::Empty`]
+    pub async fn exec(&mut self, q: &Query) -> ClientResult<()> {
+        self.exec_affected(q).await.map(|_| ())
+    }
+    /// Run a query, returning the number of affected rows if the server returned one (see [`Self::exec`])
+    pub async fn exec_affected(&mut self, q: &Query) -> ClientResult<Option<u64>> {
+        self.query(q).await.and_then(Response::into_affected)
+    }
+    /// Run a write query (`insert`, `update` or `delete`) and return the number of rows that it changed
+    ///
+    /// If the server returns a count, it is returned as is. A [`Response::Empty`] is counted as one row, since a DML query
+    /// that doesn't return a count operates on exactly one row. Any other response is a
+    /// [`ParseError::ResponseMismatch`](crate::error::ParseError::ResponseMismatch)
+    pub async fn query_affected(&mut self, q: &Query) -> ClientResult<u64> {
+        self.query(q).await.and_then(Response::into_affected_count)
+    }
     /// Run and parse a query into the indicated type. The type must implement [`FromResponse`]
     pub async fn query_parse<T: FromResponse>(&mut self, q: &Query) -> ClientResult<T> {
         self.query(q).await.and_then(FromResponse::from_response)
     }
+    /// Run a query that selects at most one row (usually by its primary key), and parse the row into the indicated type.
+    ///
+    /// When no row matches, Skytable returns error code [`ERR_ROW_NOT_FOUND`](crate::error::ERR_ROW_NOT_FOUND) (and not
+    /// an empty result), which is returned as `Ok(None)` here. Every other error is returned as is
+    pub async fn query_row_opt<T: FromResponse>(&mut self, q: &Query) -> ClientResult<Option<T>> {
+        self.query(q).await.and_then(Response::into_row_opt)
+    }
+    /// Run a DDL query (like `create space` or `create model`), returning `true` if it created the object and `false` if
+    /// the server reported that it already exists (error code
+    /// [`ERR_OBJECT_ALREADY_EXISTS`](crate::error::ERR_OBJECT_ALREADY_EXISTS)). Every other error is returned as is, so
+    /// this is an easy way to make schema setup idempotent. This is the same as `query_parse::<bool>`
+    pub async fn ensure_created(&mut self, q: &Query) -> ClientResult<bool> {
+        self.query_parse::<bool>(q).await
+    }
+    /// Switch to the given space, or `space.model` (like running `use myspace`), so that later queries can refer to its
+    /// models without qualifying them. The names are validated first (see [`ident`](crate::query::ident))
+    pub async fn use_entity(&mut self, entity: &str) -> ClientResult<()> {
+        self.query_parse::<()>(&Query::use_entity(entity)?).await?;
+        self.current_space = entity.split('.').next().map(ToOwned::to_owned);
+        Ok(())
+    }
+    /// Switch to the given space (like running `use myspace`) and remember it as this connection's default space, so
+    /// that later queries can refer to its models without qualifying them. See [`Self::current_space`]
+    pub async fn set_default_space(&mut self, space: &str) -> ClientResult<()> {
+        self.query_parse::<()>(&Query::use_space(space)?).await?;
+        self.current_space = Some(space.to_owned());
+        Ok(())
+    }
+    /// Returns the space that was last switched to with [`Self::set_default_space`] or [`Self::use_entity`], without
+    /// asking the server. A `use` query that you run yourself isn't tracked, so use [`Self::whereami`] if you do that
+    pub fn current_space(&self) -> Option<&str> {
+        self.current_space.as_deref()
+    }
+    /// Run a query in the given space, first switching to it (and making it the default space) if it isn't already
+    /// the current space
+    pub async fn query_in(&mut self, space: &str, q: &Query) -> ClientResult<Response> {
+        if self.current_space() != Some(space) {
+            self.set_default_space(space).await?;
+        }
+        self.query(q).await
+    }
+    /// Returns the space or model that this connection is currently using (see [`Entity`])
+    pub async fn whereami(&mut self) -> ClientResult<Entity> {
+        self.query_parse(&Query::new("use $current")).await
+    }
+    /// Run `inspect global`, returning the spaces and users on the server (see [`GlobalInfo`])
+    pub async fn inspect_global(&mut self) -> ClientResult<GlobalInfo> {
+        self.query_parse(&Query::new("inspect global")).await
+    }
+    /// Fetch the columns of the model `space.model` with `inspect model`, to check queries against them (see
+    /// [`Query::check_against`])
+    pub async fn describe_model(&mut self, entity: &str) -> ClientResult<ModelSchema> {
+        self.query_parse(&crate::schema::inspect_model(entity)?)
+            .await
+    }
+    /// Close the connection by flushing and shutting down the stream (which, for TLS, also ends the TLS session),
+    /// instead of just dropping it. Skyhash has no disconnect message, so the server sees the connection close like it
+    /// would on `Drop`, but this lets you handle any error and know that the connection is gone once it returns
+    pub async fn close(mut self) -> ClientResult<()> {
+        crate::syncio::ignore_not_connected(self.con.shutdown().await)
+    }
+    /// Returns true if a query on this connection was cancelled or failed before its response was completely read
+    ///
+    /// If you use `tokio::select!` or `tokio::time::timeout` with a query and the query's future is dropped before it
+    /// completes, the rest of its response may still arrive later and would be mistaken for the response to the next
+    /// query. Such a connection is marked as broken and every later query on it fails with an I/O error, so you should
+    /// discard it and connect again. The connection pool does this for you. [`Self::resync`] can sometimes recover the
+    /// connection instead
+    pub fn is_broken(&self) -> bool {
+        self.broken
+    }
+    /// Try to make a broken connection (see [`Self::is_broken`]) usable again, by reading and discarding the rest of the
+    /// responses to the query (or pipeline) that failed. If this succeeds, the connection is no longer broken
+    ///
+    /// This is best-effort, and is meant for advanced use and tests: reconnecting is usually simpler. It only works if
+    /// the responses can still arrive (like after a timeout). It fails, leaving the connection broken, if the data that
+    /// was already read can't be decoded (after a protocol error), if more data follows the last response, or if the
+    /// query was only partly sent. It waits for the server, so it only completes once the responses arrive or the
+    /// connection fails. Nothing happens if the connection isn't broken
+    ///
+    /// ## Example
+    /// ```no_run
+    /// use {skytable::{query, Config}, std::time::Duration};
+    ///
+    /// async fn run() {
+    ///     let mut db = Config::new_default("username", "password").connect_async().await.unwrap();
+    ///     let q = query!("sysctl report status");
+    ///     if db.query_timeout(&q, Duration::from_secs(1)).await.is_err() && db.is_broken() {
+    ///         // wait for the rest of the response, instead of reconnecting
+    ///         db.resync().await.unwrap();
+    ///     }
+    /// }
+    /// ```
+    pub async fn resync(&mut self) -> ClientResult<()> {
+        if !self.broken {
+            return Ok(());
+        }
+        if self.unread_responses == 0 {
+            return Err(Error::IoError(std::io::Error::new(
+                std::io::ErrorKind::BrokenPipe,
+                "the query was only partly sent, so the connection can't be resynced",
+            )));
+        }
+        // the buffer starts at a response boundary, so decode from there, skipping the responses that were already read
+        let mut state = RState::default();
+        let mut cursor = 0;
+        let mut decoded = 0;
+        while self.unread_responses != 0 {
+            if cursor == self.buf.len() {
+                self.read_more().await?;
+            }
+            let mut decoder = Decoder::new(&self.buf, cursor)
+                .lossy_utf8(self.lossy_utf8)
+                .max_size(self.max_response_size);
+            let result = decoder.validate_response(state);
+            cursor = decoder.position();
+            match result {
+                DecodeState::Completed(_) => {
+                    state = RState::default();
+                    decoded += 1;
+                    if decoded > self.read_in_buffer {
+                        self.read_in_buffer = decoded;
+                        self.unread_responses -= 1;
+                    }
+                }
+                DecodeState::ChangeState(new_state) => {
+                    state = new_state;
+                    self.read_more().await?;
+                }
+                DecodeState::Error(e) => return Err(e.into()),
+            }
+        }
+        if cursor != self.buf.len() {
+            return Err(ProtocolError::invalid_data()
+                .locate(&self.buf, cursor + 1)
+                .into());
+        }
+        self.buf.clear();
+        self.read_in_buffer = 0;
+        self.broken = false;
+        Ok(())
+    }
+    /// Check that the server hasn't closed the connection, without sending a query
+    ///
+    /// This only polls the socket once, so it doesn't wait for the server: it fails if the server closed the connection
+    /// (or the socket has an error), or if the server sent data that no query asked for, and succeeds otherwise. It is
+    /// much cheaper than a query, but it can't tell whether the server is still responsive. A failed check marks the
+    /// connection as broken. The async connection pools can use this instead of a status query (see
+    /// [`ConnectionMgrTcp::with_socket_check`](crate::pool::ConnectionMgrTcp::with_socket_check))
+    pub async fn check_alive(&mut self) -> ClientResult<()> {
+        self.check_broken()?;
+        let mut byte = [0u8; 1];
+        let mut buf = tokio::io::ReadBuf::new(&mut byte);
+        let con = &mut self.con;
+        let polled = std::future::poll_fn(|cx| {
+            Poll::Ready(tokio::io::AsyncRead::poll_read(
+                Pin::new(&mut *con),
+                cx,
+                &mut buf,
+            ))
+        })
+        .await;
+        let e = match polled {
+            Poll::Pending => return Ok(()),
+            Poll::Ready(Ok(())) if buf.filled().is_empty() => {
+                std::io::ErrorKind::ConnectionReset.into()
+            }
+            Poll::Ready(Ok(())) => std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "the server sent data that no query asked for",
+            ),
+            Poll::Ready(Err(e)) => e,
+        };
+        self.broken = true;
+        Err(Error::IoError(e))
+    }
+    /// Returns the number of queries sent on this connection, counting every query in a pipeline
+    pub fn queries_sent(&self) -> u64 {
+        self.queries_sent
+    }
+    /// Returns the number of bytes sent on this connection (excluding the handshake)
+    pub fn bytes_sent(&self) -> u64 {
+        self.bytes_sent
+    }
+    /// Returns the number of bytes received on this connection (excluding the handshake)
+    pub fn bytes_received(&self) -> u64 {
+        self.bytes_received
+    }
     /// Call this if the internally allocated buffer is growing too large and impacting your performance. However, normally
     /// you will not need to call this
     pub fn reset_buffer(&mut self) {
         self.buf.shrink_to_fit()
     }
+    /// Read the next chunk of the response (at most `read_buffer_size` bytes) into the end of the buffer, returning the
+    /// number of bytes read. This fails if the buffered response grows beyond `max_response_size`
+    async fn read_more(&mut self) -> ClientResult<usize> {
+        // unlike reading into a resized buffer, this doesn't leave the buffer padded if the future is dropped midway
+        self.buf.reserve(self.read_buffer_size);
+        let n = (&mut self.con)
+            .take(self.read_buffer_size as u64)
+            .read_buf(&mut self.buf)
+            .await;
+        let n = match n? {
+            0 => return Err(Error::IoError(std::io::ErrorKind::ConnectionReset.into())),
+            n => n,
+        };
+        self.bytes_received += n as u64;
+        match self.max_response_size {
+            Some(limit) if self.buf.len() > limit => Err(ProtocolError::response_too_large(limit)
+                .locate(&self.buf, self.buf.len())
+                .into()),
+            _ => Ok(n),
+        }
+    }
+    /// Shrink the buffer if it retains more memory than allowed by the configuration
+    fn release_buffer(&mut self) {
+        if let Some(max) = self.max_retained_buffer {
+            if self.buf.capacity() > max {
+                self.buf.clear();
+                self.buf.shrink_to(max);
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn t_connect_async_over() {
+    let (client, mut server) = tokio::io::duplex(1024);
+    let server = tokio::spawn(async move {
+        let mut buf = [0u8; 64];
+        let n = server.read(&mut buf).await.unwrap();
+        assert_eq!(buf[0], b'H');
+        server.write_all(b"H\x00\x00\x00").await.unwrap();
+        // the query
+        let _ = server.read(&mut buf[n..]).await.unwrap();
+        server.write_all(b"\x0D5\nsayan").await.unwrap();
+    });
+    let cfg = Config::new_default("username", "password");
+    let mut db = cfg.connect_async_over(client).await.unwrap();
+    let username: String = db
+        .query_parse(&crate::query!("select username from myspace.mymodel"))
+        .await
+        .unwrap();
+    assert_eq!(username, "sayan");
+    server.await.unwrap();
+}
+
+#[cfg(feature = "async-std")]
+#[test]
+fn t_connect_async_std() {
+    use async_std::{io::ReadExt, io::WriteExt, net::TcpListener};
+    async_std::task::block_on(async {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = async_std::task::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 64];
+            let n = stream.read(&mut buf).await.unwrap();
+            assert_eq!(buf[0], b'H');
+            stream.write_all(b"H\x00\x00\x00").await.unwrap();
+            let _ = stream.read(&mut buf[n..]).await.unwrap();
+            stream.write_all(b"\x0D5\nsayan").await.unwrap();
+        });
+        let mut db = Config::new("127.0.0.1", port, "username", "password")
+            .connect_async_std()
+            .await
+            .unwrap();
+        let username: String = db
+            .query_parse(&crate::query!("select username from myspace.mymodel"))
+            .await
+            .unwrap();
+        assert_eq!(username, "sayan");
+        server.await;
+    });
+}
+
+#[tokio::test]
+async fn t_close() {
+    let cfg = Config::new_default("username", "password");
+    let (client, mut server) = tokio::io::duplex(1024);
+    let db = TcpConnection::new(client, &cfg);
+    db.close().await.unwrap();
+    // the other end sees EOF
+    let mut buf = [0u8; 8];
+    assert_eq!(server.read(&mut buf).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn t_query_timeout() {
+    let cfg = Config::new_default("username", "password");
+    let (client, mut server) = tokio::io::duplex(1024);
+    let mut db = TcpConnection::new(client, &cfg);
+    let q = crate::query!("sysctl report status");
+    server.write_all(b"\x12").await.unwrap();
+    assert_eq!(
+        db.query_timeout(&q, Duration::from_secs(10)).await.unwrap(),
+        Response::Empty
+    );
+    assert!(!db.is_broken());
+    assert!(matches!(
+        db.query_timeout(&q, Duration::from_millis(10)).await,
+        Err(Error::Timeout)
+    ));
+    assert!(db.is_broken());
+}
+
+#[tokio::test]
+async fn t_failover() {
+    // nothing listens on this port once the listener is dropped
+    let dead = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let live = listener.local_addr().unwrap().port();
+    let server = tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut buf = [0u8; 256];
+        let _ = stream.read(&mut buf).await.unwrap();
+        stream.write_all(b"H\x00\x00\x00").await.unwrap();
+    });
+    let cfg = Config::new_default("username", "password")
+        .with_hosts(&[("127.0.0.1", dead), ("127.0.0.1", live)]);
+    cfg.connect_async().await.unwrap();
+    server.await.unwrap();
+    match cfg.with_hosts(&[("127.0.0.1", dead)]).connect_async().await {
+        Err(Error::IoError(e)) => assert_eq!(e.kind(), std::io::ErrorKind::ConnectionRefused),
+        r => panic!("expected an error, got {:?}", r.map(|_| ())),
+    }
+}
+
+#[tokio::test]
+async fn t_cancelled_query() {
+    let cfg = Config::new_default("username", "password");
+    let (client, mut server) = tokio::io::duplex(1024);
+    let mut db = TcpConnection::new(client, &cfg);
+    let q = crate::query!("select * from myspace.mymodel");
+    // the server answers the first query
+    server.write_all(b"\x12").await.unwrap();
+    db.query(&q).await.unwrap();
+    assert!(!db.is_broken());
+    // but not the second, so it times out
+    let timeout = std::time::Duration::from_millis(10);
+    assert!(tokio::time::timeout(timeout, db.query(&q)).await.is_err());
+    assert!(db.is_broken());
+    // the late response must not be read as the response to a new query
+    server.write_all(b"\x12").await.unwrap();
+    match db.query(&q).await {
+        Err(Error::IoError(e)) => assert_eq!(e.kind(), std::io::ErrorKind::BrokenPipe),
+        r => panic!("expected an error, got {:?}", r),
+    }
+}
+
+#[tokio::test]
+async fn t_check_alive() {
+    let cfg = Config::new_default("username", "password");
+    let (client, server) = tokio::io::duplex(1024);
+    let mut db = TcpConnection::new(client, &cfg);
+    // nothing to read, so the connection is still open
+    db.check_alive().await.unwrap();
+    assert!(!db.is_broken());
+    drop(server);
+    match db.check_alive().await {
+        Err(Error::IoError(e)) => assert_eq!(e.kind(), std::io::ErrorKind::ConnectionReset),
+        r => panic!("expected an error, got {:?}", r),
+    }
+    assert!(db.is_broken());
+    // data that arrives without a query means the connection is out of sync
+    let (client, mut server) = tokio::io::duplex(1024);
+    let mut db = TcpConnection::new(client, &cfg);
+    server.write_all(b"\x12").await.unwrap();
+    match db.check_alive().await {
+        Err(Error::IoError(e)) => assert_eq!(e.kind(), std::io::ErrorKind::InvalidData),
+        r => panic!("expected an error, got {:?}", r),
+    }
+    assert!(db.is_broken());
+}
+
+#[tokio::test]
+async fn t_resync_cancelled_query() {
+    let cfg = Config::new_default("username", "password");
+    let (client, mut server) = tokio::io::duplex(1024);
+    let mut db = TcpConnection::new(client, &cfg);
+    let q = crate::query!("select * from myspace.mymodel");
+    // half of the row arrives before the query is cancelled
+    server.write_all(b"\x115\n\x00\x01").await.unwrap();
+    let timeout = std::time::Duration::from_millis(10);
+    assert!(tokio::time::timeout(timeout, db.query(&q)).await.is_err());
+    assert!(db.is_broken());
+    // the rest of the row is discarded
+    server
+        .write_all(b"\x01\x0D5\nsayan\x0220\n\x0E0\n")
+        .await
+        .unwrap();
+    db.resync().await.unwrap();
+    assert!(!db.is_broken());
+    server.write_all(b"\x12").await.unwrap();
+    assert_eq!(db.query(&q).await.unwrap(), Response::Empty);
 }
@@ -29,6 +29,26 @@
 //! // establish a connection to a specific host `subnetx2_db1` and port `2008`
 //! let mut db = Config::new("subnetx2_db1", 2008, "username", "password").connect().unwrap();
 //! ```
+//!
+//! ## Connection variants
+//!
+//! The same [`Config`] is used for every kind of connection:
+//!
+//! | | Plain (`skyhash/TCP`) | TLS (`skyhash/TLS`) |
+//! |---|---|---|
+//! | Sync | [`Config::connect`] | [`Config::connect_tls`], [`Config::connect_tls_from_file`] |
+//! | Async | [`Config::connect_async`] | [`Config::connect_tls_async`], [`Config::connect_async_tls_from_file`] |
+//!
+//! ## Authentication
+//!
+//! Every connection authenticates with a username and password. The Skyhash 2.0 handshake only defines the password
+//! (`pwd`) authentication plugin and Skytable servers always have authentication enabled, so there is no anonymous
+//! mode: even for a local development server, connect as `root` (or another user) with its password.
+
+use std::{
+    fmt, io,
+    net::{SocketAddr, TcpStream, ToSocketAddrs},
+};
 
 /// The default host
 ///
@@ -39,13 +59,31 @@ pub const DEFAULT_TCP_PORT: u16 = 2003;
 /// The default TLS port (skyhash/tls)
 pub const DEFAULT_TLS_PORT: u16 = 2002;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Clone, PartialEq)]
 /// Configuration for a Skytable connection
+///
+/// The [`Debug`](fmt::Debug) output replaces the password with `"***"`, so a config can be logged safely. Use
+/// [`Config::debug_unredacted`] if you really need to see the password
 pub struct Config {
     host: Box<str>,
     port: u16,
+    fallback_hosts: Vec<(Box<str>, u16)>,
     username: Box<str>,
     password: Box<str>,
+    redact_logs: bool,
+    max_retained_buffer: Option<usize>,
+    read_buffer_size: usize,
+    max_response_size: Option<usize>,
+    direct_write_threshold: Option<usize>,
+    lossy_utf8: bool,
+    addr_preference: AddrPreference,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum AddrPreference {
+    Any,
+    Ipv4,
+    Ipv6,
 }
 
 impl Config {
@@ -58,10 +96,75 @@ impl Config {
         Self {
             host: host.into(),
             port,
+            fallback_hosts: Vec::new(),
+            username: username.into(),
+            password: password.into(),
+            redact_logs: false,
+            max_retained_buffer: None,
+            read_buffer_size: crate::BUFSIZE,
+            max_response_size: None,
+            direct_write_threshold: None,
+            lossy_utf8: false,
+            addr_preference: AddrPreference::Any,
+        }
+    }
+    /// Returns a copy of this configuration that uses the given username and password, keeping every other setting. This
+    /// is useful when the same server is accessed with different credentials (for example, one user per tenant)
+    ///
+    /// ## Example
+    /// ```no_run
+    /// use skytable::Config;
+    ///
+    /// let base = Config::new("subnetx2_db1", 2008, "root", "password").set_redact_logs(true);
+    /// let mut tenant_db = base.with_credentials("tenant1", "tenant1pass").connect().unwrap();
+    /// ```
+    pub fn with_credentials(&self, username: &str, password: &str) -> Self {
+        Self {
             username: username.into(),
             password: password.into(),
+            ..self.clone()
+        }
+    }
+    /// Returns a copy of this configuration that connects to the first of the given endpoints (as `(host, port)`) that
+    /// accepts a connection, trying them in order, and keeps every other setting. This gives you client-side failover
+    /// for a setup with more than one server, without a load balancer. If every endpoint fails, the error for the last
+    /// one is returned
+    ///
+    /// Failover only happens while connecting (including when a [pool](crate::pool) opens a new connection): once
+    /// connected, a connection stays with its server. The first endpoint becomes the [`host`](Self::host) and
+    /// [`port`](Self::port) of the returned configuration, and an empty list leaves the endpoints unchanged
+    ///
+    /// ## Example
+    /// ```no_run
+    /// use skytable::Config;
+    ///
+    /// let cfg = Config::new_default("username", "password")
+    ///     .with_hosts(&[("subnetx2_db1", 2003), ("subnetx2_db2", 2003)]);
+    /// let mut db = cfg.connect().unwrap();
+    /// ```
+    pub fn with_hosts(&self, hosts: &[(&str, u16)]) -> Self {
+        match hosts.split_first() {
+            Some(((host, port), fallbacks)) => Self {
+                host: (*host).into(),
+                port: *port,
+                fallback_hosts: fallbacks
+                    .iter()
+                    .map(|(host, port)| ((*host).into(), *port))
+                    .collect(),
+                ..self.clone()
+            },
+            None => self.clone(),
         }
     }
+    /// Returns every endpoint (as `(host, port)`) in the order that they are tried when connecting, starting with the
+    /// [`host`](Self::host) and [`port`](Self::port) (see [`Self::with_hosts`])
+    pub fn hosts(&self) -> impl Iterator<Item = (&str, u16)> {
+        core::iter::once((self.host(), self.port())).chain(
+            self.fallback_hosts
+                .iter()
+                .map(|(host, port)| (host.as_ref(), *port)),
+        )
+    }
     /// Returns the host setting for this this configuration
     pub fn host(&self) -> &str {
         self.host.as_ref()
@@ -78,4 +181,272 @@ impl Config {
     pub fn password(&self) -> &str {
         self.password.as_ref()
     }
+    /// Set whether query parameters should be left out of logged packets (only has an effect with the `logging` feature).
+    /// Use this if your parameters may contain sensitive data
+    pub fn set_redact_logs(mut self, redact: bool) -> Self {
+        self.redact_logs = redact;
+        self
+    }
+    /// Returns true if query parameters are left out of logged packets
+    pub fn redact_logs(&self) -> bool {
+        self.redact_logs
+    }
+    /// Set the maximum capacity (in bytes) that a connection's internal buffer may retain once a query completes. If a large
+    /// response grows the buffer beyond this limit, it is shrunk back down to the limit.
+    ///
+    /// By default, the buffer is never shrunk automatically (see `reset_buffer` on the connection types)
+    pub fn set_max_retained_buffer(mut self, max: usize) -> Self {
+        self.max_retained_buffer = Some(max);
+        self
+    }
+    /// Returns the maximum capacity that a connection's internal buffer may retain, if set
+    pub fn max_retained_buffer(&self) -> Option<usize> {
+        self.max_retained_buffer
+    }
+    /// Set the maximum number of bytes (at least one) that a connection reads from the socket at a time while waiting for a
+    /// response. The default is 8 KB.
+    ///
+    /// A larger size means fewer reads (and so fewer system calls) for large responses, at the cost of the connection's
+    /// buffer always having room for at least this many bytes, even when responses are small. If you also use
+    /// [`Config::set_max_retained_buffer`], keep the limit above this size or the buffer will be reallocated on every query
+    pub fn set_read_buffer_size(mut self, size: usize) -> Self {
+        self.read_buffer_size = size.max(1);
+        self
+    }
+    /// Returns the maximum number of bytes that a connection reads at a time
+    pub fn read_buffer_size(&self) -> usize {
+        self.read_buffer_size
+    }
+    /// Set the maximum size (in bytes) of a single response. A response that grows beyond this, or that declares a string,
+    /// binary value, list or row count that couldn't fit in it, fails with
+    /// [`ProtocolErrorKind::ResponseTooLarge`](crate::error::ProtocolErrorKind::ResponseTooLarge) as soon as that's detected,
+    /// instead of being buffered. This protects you from a misbehaving (or malicious) server. By default, there's no limit
+    ///
+    /// The connection can't be used anymore after this error, since the rest of the response is still unread
+    pub fn set_max_response_size(mut self, max: usize) -> Self {
+        self.max_response_size = Some(max);
+        self
+    }
+    /// Returns the maximum size of a single response, if set
+    pub fn max_response_size(&self) -> Option<usize> {
+        self.max_response_size
+    }
+    /// Write the query string and parameters of a query straight from the [`Query`](crate::Query) to the socket, instead of
+    /// copying the whole packet into the connection's buffer first, if they take up at least `min_size` bytes once encoded
+    ///
+    /// This saves a copy (and keeps the buffer from growing) for queries with large parameters, like big binary values,
+    /// but it takes two writes to send the query instead of one, so small queries are better off being buffered. By
+    /// default, every query is buffered. Pipelines are always buffered
+    pub fn set_direct_write_threshold(mut self, min_size: usize) -> Self {
+        self.direct_write_threshold = Some(min_size);
+        self
+    }
+    /// Returns the minimum encoded size for a query to be written directly, if set
+    pub fn direct_write_threshold(&self) -> Option<usize> {
+        self.direct_write_threshold
+    }
+    /// Decode strings with invalid UTF-8 lossily (replacing invalid sequences with `U+FFFD`) instead of failing the query.
+    /// By default, an invalid string fails the query with an [`Error::ProtocolError`]
+    ///
+    /// [`Error::ProtocolError`]: crate::error::Error::ProtocolError
+    pub fn set_lossy_utf8(mut self, lossy: bool) -> Self {
+        self.lossy_utf8 = lossy;
+        self
+    }
+    /// Returns true if strings with invalid UTF-8 are decoded lossily
+    pub fn lossy_utf8(&self) -> bool {
+        self.lossy_utf8
+    }
+    /// If the host resolves to both IPv4 and IPv6 addresses, try the IPv4 addresses first. This is useful in dual-stack
+    /// environments where IPv6 is broken. By default, addresses are tried in the order returned by the resolver
+    pub fn set_prefer_ipv4(mut self, prefer: bool) -> Self {
+        self.set_addr_preference(AddrPreference::Ipv4, prefer);
+        self
+    }
+    /// If the host resolves to both IPv4 and IPv6 addresses, try the IPv6 addresses first. By default, addresses are tried
+    /// in the order returned by the resolver
+    pub fn set_prefer_ipv6(mut self, prefer: bool) -> Self {
+        self.set_addr_preference(AddrPreference::Ipv6, prefer);
+        self
+    }
+    /// Returns true if IPv4 addresses are tried first
+    pub fn prefer_ipv4(&self) -> bool {
+        self.addr_preference == AddrPreference::Ipv4
+    }
+    /// Returns true if IPv6 addresses are tried first
+    pub fn prefer_ipv6(&self) -> bool {
+        self.addr_preference == AddrPreference::Ipv6
+    }
+    fn set_addr_preference(&mut self, preference: AddrPreference, prefer: bool) {
+        if prefer {
+            self.addr_preference = preference;
+        } else if self.addr_preference == preference {
+            self.addr_preference = AddrPreference::Any;
+        }
+    }
+    /// Returns a [`Debug`](fmt::Debug) representation of this config that includes the password
+    ///
+    /// ## Example
+    /// ```
+    /// use skytable::Config;
+    ///
+    /// let cfg = Config::new_default("username", "s3cret");
+    /// assert!(!format!("{:?}", cfg).contains("s3cret"));
+    /// assert!(format!("{:?}", cfg.debug_unredacted()).contains("s3cret"));
+    /// ```
+    pub fn debug_unredacted(&self) -> impl fmt::Debug + '_ {
+        struct Unredacted<'a>(&'a Config);
+        impl fmt::Debug for Unredacted<'_> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.0.fmt_debug(f, &self.0.password)
+            }
+        }
+        Unredacted(self)
+    }
+    fn fmt_debug(&self, f: &mut fmt::Formatter<'_>, password: &dyn fmt::Debug) -> fmt::Result {
+        f.debug_struct("Config")
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("fallback_hosts", &self.fallback_hosts)
+            .field("username", &self.username)
+            .field("password", password)
+            .field("redact_logs", &self.redact_logs)
+            .field("max_retained_buffer", &self.max_retained_buffer)
+            .field("read_buffer_size", &self.read_buffer_size)
+            .field("max_response_size", &self.max_response_size)
+            .field("direct_write_threshold", &self.direct_write_threshold)
+            .field("lossy_utf8", &self.lossy_utf8)
+            .field("addr_preference", &self.addr_preference)
+            .finish()
+    }
+    /// Order the resolved addresses according to the address family preference (keeping the resolver's order otherwise)
+    pub(crate) fn order_addrs(&self, addrs: impl Iterator<Item = SocketAddr>) -> Vec<SocketAddr> {
+        let mut addrs: Vec<_> = addrs.collect();
+        match self.addr_preference {
+            AddrPreference::Any => {}
+            AddrPreference::Ipv4 => addrs.sort_by_key(|addr| !addr.is_ipv4()),
+            AddrPreference::Ipv6 => addrs.sort_by_key(|addr| !addr.is_ipv6()),
+        }
+        addrs
+    }
+    /// Resolve the host (blocking), returning the addresses to connect to in order
+    pub(crate) fn resolve_addrs(&self, host: &str, port: u16) -> io::Result<Vec<SocketAddr>> {
+        Ok(self.order_addrs((host, port).to_socket_addrs()?))
+    }
+    /// Open a TCP connection to the first endpoint that accepts one (see [`Self::with_hosts`]), returning the stream
+    /// and the host that it's connected to
+    pub(crate) fn connect_tcp(&self) -> io::Result<(TcpStream, &str)> {
+        let mut last_err = None;
+        for (host, port) in self.hosts() {
+            match self
+                .resolve_addrs(host, port)
+                .and_then(|addrs| TcpStream::connect(&addrs[..]))
+            {
+                Ok(stream) => return Ok((stream, host)),
+                Err(e) => last_err = Some(e),
+            }
+        }
+        // there's always at least one endpoint
+        Err(last_err.unwrap())
+    }
+}
+
+impl fmt::Debug for Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_debug(f, &"***")
+    }
+}
+
+#[test]
+fn t_debug_redacts_password() {
+    let cfg = Config::new("subnetx2_db1", 2008, "root", "hunter2");
+    let debug = format!("{:?}", cfg);
+    assert!(!debug.contains("hunter2"));
+    assert!(debug.contains(r#"password: "***""#));
+    assert!(debug.contains(r#"username: "root""#));
+    assert!(format!("{:#?}", cfg).contains(r#""***""#));
+    assert_eq!(
+        format!("{:?}", cfg.debug_unredacted()),
+        debug.replace(r#""***""#, r#""hunter2""#)
+    );
+}
+
+#[test]
+fn t_with_hosts() {
+    let cfg = Config::new_default("username", "password");
+    assert_eq!(cfg.hosts().collect::<Vec<_>>(), [(DEFAULT_HOST, DEFAULT_TCP_PORT)]);
+    let cfg = cfg.with_hosts(&[("db1", 2003), ("db2", 2004)]);
+    assert_eq!((cfg.host(), cfg.port()), ("db1", 2003));
+    assert_eq!(cfg.hosts().collect::<Vec<_>>(), [("db1", 2003), ("db2", 2004)]);
+    assert_eq!(cfg.with_hosts(&[]), cfg);
+    assert_eq!(
+        cfg.with_hosts(&[("db3", 2005)]).hosts().collect::<Vec<_>>(),
+        [("db3", 2005)]
+    );
+}
+
+#[test]
+fn t_addr_preference() {
+    let addrs: Vec<SocketAddr> = vec![
+        "[::1]:2003".parse().unwrap(),
+        "127.0.0.1:2003".parse().unwrap(),
+        "[::2]:2003".parse().unwrap(),
+    ];
+    let cfg = Config::new("localhost", 2003, "username", "password");
+    assert_eq!(cfg.order_addrs(addrs.iter().copied()), addrs);
+    let cfg = cfg.set_prefer_ipv4(true);
+    assert!(cfg.prefer_ipv4() && !cfg.prefer_ipv6());
+    assert_eq!(
+        cfg.order_addrs(addrs.iter().copied()),
+        [addrs[1], addrs[0], addrs[2]]
+    );
+    let cfg = cfg.set_prefer_ipv6(true);
+    assert!(cfg.prefer_ipv6() && !cfg.prefer_ipv4());
+    assert_eq!(
+        cfg.order_addrs(addrs.iter().copied()),
+        [addrs[0], addrs[2], addrs[1]]
+    );
+    // unsetting a preference that isn't active is a no-op
+    assert!(cfg.clone().set_prefer_ipv4(false).prefer_ipv6());
+    assert!(!cfg.set_prefer_ipv6(false).prefer_ipv6());
+}
+
+#[test]
+fn t_resolve_localhost() {
+    let cfg = Config::new("localhost", 2003, "username", "password");
+    let resolved = cfg.resolve_addrs("localhost", 2003).unwrap();
+    assert!(!resolved.is_empty());
+    let v4 = cfg
+        .clone()
+        .set_prefer_ipv4(true)
+        .resolve_addrs("localhost", 2003)
+        .unwrap();
+    if resolved.iter().any(SocketAddr::is_ipv4) {
+        assert!(v4[0].is_ipv4());
+    }
+    let v6 = cfg
+        .set_prefer_ipv6(true)
+        .resolve_addrs("localhost", 2003)
+        .unwrap();
+    if resolved.iter().any(SocketAddr::is_ipv6) {
+        assert!(v6[0].is_ipv6());
+    }
+    assert_eq!(v4.len(), resolved.len());
+    assert_eq!(v6.len(), resolved.len());
+}
+
+#[test]
+fn t_with_credentials() {
+    let base = Config::new("subnetx2_db1", 2008, "root", "password")
+        .set_redact_logs(true)
+        .set_prefer_ipv4(true);
+    let tenant = base.with_credentials("tenant1", "tenant1pass");
+    assert_eq!(tenant.host(), "subnetx2_db1");
+    assert_eq!(tenant.port(), 2008);
+    assert_eq!(tenant.username(), "tenant1");
+    assert_eq!(tenant.password(), "tenant1pass");
+    assert!(tenant.redact_logs() && tenant.prefer_ipv4());
+    // the base config is unchanged
+    assert_eq!(base.username(), "root");
+    assert_eq!(base.with_credentials("root", "password"), base);
 }
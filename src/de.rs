@@ -0,0 +1,298 @@
+/*
+ * Copyright 2023, Sayan Nandan <nandansayan@outlook.com>
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+
+//! Deserializing rows with `serde` (see [`Row::deserialize`])
+
+use {
+    crate::{
+        error::{ClientResult, Error, ParseError},
+        response::{Row, Value},
+    },
+    serde::de::{
+        self, value::SeqDeserializer, DeserializeSeed, Deserializer, IntoDeserializer, SeqAccess,
+        Visitor,
+    },
+    std::{fmt, vec},
+};
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self::ParseError(ParseError::Other(msg.to_string()))
+    }
+}
+
+/// A [`Deserializer`] that feeds the columns of a [`Row`] to `serde` as a sequence, so that a struct's fields (or a
+/// tuple's elements) are filled in by position. You'll usually use [`Row::deserialize`] instead of using this directly.
+///
+/// Columns are mapped like this:
+/// - integers, floats, booleans and strings to the corresponding Rust type (integers can be read into any integer type
+///   that the value fits in)
+/// - binary values to byte buffers or `Vec<u8>`
+/// - lists to sequences
+/// - null to `None` (or `()`)
+/// - strings to unit enum variants, by name
+#[derive(Debug)]
+pub struct RowDeserializer {
+    values: vec::IntoIter<Value>,
+}
+
+impl RowDeserializer {
+    /// Create a new deserializer for the given row
+    pub fn new(row: Row) -> Self {
+        Self {
+            values: row.into_values().into_iter(),
+        }
+    }
+    fn check_len(&self, expected: usize) -> ClientResult<()> {
+        let actual = self.values.len();
+        if actual == expected {
+            Ok(())
+        } else {
+            Err(Error::ParseError(ParseError::ColumnCountMismatch {
+                expected,
+                actual,
+            }))
+        }
+    }
+}
+
+impl<'de> Deserializer<'de> for RowDeserializer {
+    type Error = Error;
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> ClientResult<V::Value> {
+        self.deserialize_seq(visitor)
+    }
+    fn deserialize_seq<V: Visitor<'de>>(mut self, visitor: V) -> ClientResult<V::Value> {
+        let ret = visitor.visit_seq(&mut self)?;
+        // the visitor may stop early (for example, a fixed size array), but every column must be used
+        self.check_len(0)?;
+        Ok(ret)
+    }
+    fn deserialize_tuple<V: Visitor<'de>>(self, len: usize, visitor: V) -> ClientResult<V::Value> {
+        self.check_len(len)?;
+        self.deserialize_seq(visitor)
+    }
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> ClientResult<V::Value> {
+        self.deserialize_tuple(len, visitor)
+    }
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> ClientResult<V::Value> {
+        self.deserialize_tuple(fields.len(), visitor)
+    }
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf option unit unit_struct
+        newtype_struct map enum identifier ignored_any
+    }
+}
+
+impl<'de> SeqAccess<'de> for RowDeserializer {
+    type Error = Error;
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> ClientResult<Option<T::Value>> {
+        match self.values.next() {
+            Some(v) => seed.deserialize(ValueDeserializer(v)).map(Some),
+            None => Ok(None),
+        }
+    }
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.values.len())
+    }
+}
+
+/// A [`Deserializer`] for a single [`Value`], used for each column by [`RowDeserializer`]
+#[derive(Debug)]
+pub struct ValueDeserializer(Value);
+
+impl<'de> IntoDeserializer<'de, Error> for Value {
+    type Deserializer = ValueDeserializer;
+    fn into_deserializer(self) -> Self::Deserializer {
+        ValueDeserializer(self)
+    }
+}
+
+impl<'de> Deserializer<'de> for ValueDeserializer {
+    type Error = Error;
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> ClientResult<V::Value> {
+        match self.0 {
+            Value::Null => visitor.visit_unit(),
+            Value::Bool(b) => visitor.visit_bool(b),
+            Value::UInt8(u) => visitor.visit_u8(u),
+            Value::UInt16(u) => visitor.visit_u16(u),
+            Value::UInt32(u) => visitor.visit_u32(u),
+            Value::UInt64(u) => visitor.visit_u64(u),
+            Value::SInt8(s) => visitor.visit_i8(s),
+            Value::SInt16(s) => visitor.visit_i16(s),
+            Value::SInt32(s) => visitor.visit_i32(s),
+            Value::SInt64(s) => visitor.visit_i64(s),
+            Value::Float32(f) => visitor.visit_f32(f),
+            Value::Float64(f) => visitor.visit_f64(f),
+            Value::Binary(b) => visitor.visit_byte_buf(b),
+            Value::String(s) => visitor.visit_string(s),
+            Value::List(l) => visitor.visit_seq(SeqDeserializer::new(l.into_iter())),
+        }
+    }
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> ClientResult<V::Value> {
+        match self.0 {
+            Value::Null => visitor.visit_none(),
+            v => visitor.visit_some(ValueDeserializer(v)),
+        }
+    }
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> ClientResult<V::Value> {
+        match self.0 {
+            // so that binary values can be read into a `Vec<u8>`
+            Value::Binary(b) => visitor.visit_seq(SeqDeserializer::new(b.into_iter())),
+            v => ValueDeserializer(v).deserialize_any(visitor),
+        }
+    }
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> ClientResult<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        _: &'static [&'static str],
+        visitor: V,
+    ) -> ClientResult<V::Value> {
+        match self.0 {
+            Value::String(s) => visitor.visit_enum(s.into_deserializer()),
+            got => Err(Error::ParseError(ParseError::TypeMismatchGot {
+                expected: "enum",
+                got,
+            })),
+        }
+    }
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf unit unit_struct tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        crate::{
+            error::{Error, ParseError},
+            response::{Row, Value},
+        },
+        serde::Deserialize,
+    };
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    enum Role {
+        Admin,
+        User,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct User {
+        username: String,
+        age: u8,
+        email: Option<String>,
+        verified: bool,
+        role: Role,
+        tags: Vec<String>,
+        avatar: Vec<u8>,
+        score: f64,
+    }
+
+    fn user_row(email: Value) -> Row {
+        Row::new(vec![
+            Value::str("sayan"),
+            Value::UInt8(20),
+            email,
+            Value::Bool(true),
+            Value::str("Admin"),
+            Value::List(vec![Value::str("rust"), Value::str("db")]),
+            Value::binary(vec![0xff, 0x00]),
+            Value::Float64(9.5),
+        ])
+    }
+
+    #[test]
+    fn t_deserialize_struct() {
+        let user: User = user_row(Value::str("sayan@example.com"))
+            .deserialize()
+            .unwrap();
+        assert_eq!(
+            user,
+            User {
+                username: "sayan".into(),
+                age: 20,
+                email: Some("sayan@example.com".into()),
+                verified: true,
+                role: Role::Admin,
+                tags: vec!["rust".into(), "db".into()],
+                avatar: vec![0xff, 0x00],
+                score: 9.5,
+            }
+        );
+        let user: User = user_row(Value::Null).deserialize().unwrap();
+        assert_eq!(user.email, None);
+        assert_ne!(user.role, Role::User);
+    }
+
+    #[test]
+    fn t_deserialize_tuple() {
+        let row = Row::new(vec![Value::str("sayan"), Value::UInt64(20)]);
+        let (username, age): (String, u32) = row.deserialize().unwrap();
+        assert_eq!((username.as_str(), age), ("sayan", 20));
+    }
+
+    #[test]
+    fn t_deserialize_errors() {
+        // column count
+        let row = Row::new(vec![Value::str("sayan")]);
+        assert!(matches!(
+            row.deserialize::<User>(),
+            Err(Error::ParseError(ParseError::ColumnCountMismatch {
+                expected: 8,
+                actual: 1
+            }))
+        ));
+        // types
+        let row = Row::new(vec![Value::str("sayan"), Value::str("twenty")]);
+        assert!(matches!(
+            row.deserialize::<(String, u8)>(),
+            Err(Error::ParseError(ParseError::Other(_)))
+        ));
+        // an integer that doesn't fit
+        let row = Row::new(vec![Value::UInt64(256)]);
+        assert!(row.deserialize::<(u8,)>().is_err());
+        // an enum that isn't stored as a string keeps the value
+        let row = Row::new(vec![Value::UInt8(1)]);
+        assert!(matches!(
+            row.deserialize::<(Role,)>(),
+            Err(Error::ParseError(ParseError::TypeMismatchGot {
+                expected: "enum",
+                got: Value::UInt8(1)
+            }))
+        ));
+    }
+}
@@ -22,12 +22,74 @@
 //! You might find Skytable's documentation on error codes helpful: [https://docs.skytable.io/protocol/errors](https://docs.skytable.io/protocol/errors)
 //!
 
-use {crate::protocol::ProtocolError, core::fmt};
+pub use crate::protocol::{ProtocolError, ProtocolErrorKind};
+
+use {crate::response::Value, core::fmt};
 
 /// A [`Result`] type alias for the client driver
 pub type ClientResult<T> = Result<T, Error>;
 
+/// The server error code returned when authentication fails (for example, because of a wrong username or password)
+pub const ERR_AUTH_FAILED: u16 = 3;
+/// The server error code returned when the user isn't allowed to do something
+pub const ERR_PERMISSION_DENIED: u16 = 5;
+/// The server error code returned when the space or model being created already exists
+pub const ERR_OBJECT_ALREADY_EXISTS: u16 = 103;
+/// The server error code returned when no row matches the primary key in a DML query (for example, a `select` or an
+/// `update`)
+pub const ERR_ROW_NOT_FOUND: u16 = 111;
+
+/// Returns a short description of a server error code, or `"unknown error"` for a code that isn't documented (the
+/// [`Display`](fmt::Display) implementation of [`Error::ServerError`] includes it)
+///
+/// ## Example
+/// ```
+/// use skytable::error::{self, Error};
+///
+/// assert_eq!(error::describe(5), "permission denied");
+/// assert_eq!(Error::ServerError(5).to_string(), "server error 5: permission denied");
+/// ```
+pub fn describe(code: u16) -> &'static str {
+    match code {
+        // system
+        0 => "server error",
+        1 => "server is out of memory",
+        2 => "unknown server error",
+        ERR_AUTH_FAILED => "authentication error",
+        4 => "transactional error",
+        ERR_PERMISSION_DENIED => "permission denied",
+        6 => "illegal client packet",
+        // network and query language
+        24 => "corrupted packet",
+        25 => "invalid input",
+        26 => "unexpected byte",
+        27 => "unexpected end of statement",
+        28 => "invalid syntax",
+        29 => "invalid collection syntax",
+        30 => "invalid type definition syntax",
+        31 => "expected an entity",
+        32 => "expected a statement",
+        33 => "unknown statement",
+        // execution
+        100 => "object not found",
+        101 => "unknown field",
+        102 => "invalid properties",
+        ERR_OBJECT_ALREADY_EXISTS => "object already exists",
+        104 => "object is not empty",
+        105 => "invalid type definition",
+        106 => "bad model definition",
+        107 => "illegal alter model query",
+        108 => "duplicate row",
+        109 => "data validation error",
+        110 => "where clause uses an unindexed column",
+        ERR_ROW_NOT_FOUND => "row not found",
+        112 => "query needs a lock",
+        _ => "unknown error",
+    }
+}
+
 #[derive(Debug)]
+#[non_exhaustive]
 /// Client driver errors
 ///
 /// This is a broad classification for all kinds of possible client driver errors, across I/O, server errors and application level parse errors
@@ -38,10 +100,34 @@ pub enum Error {
     ConnectionSetupErr(ConnectionSetupError),
     /// When running a query, a protocol error was thrown
     ProtocolError(ProtocolError),
-    /// A server error code was received
+    /// A server error code was received (the server only sends the code, without an error message; see [`describe`])
     ServerError(u16),
     /// An application level parse error
     ParseError(ParseError),
+    /// The query failed client-side validation and was never sent to the server
+    InvalidQuery(String),
+    /// The response to a query wasn't received within the query's timeout
+    Timeout,
+}
+
+impl Error {
+    /// Returns true if the server refused the connection because of the credentials, that is, if this is a
+    /// [`ConnectionSetupError::BadCredentials`] or a [`ConnectionSetupError::PermissionDenied`]. Use this to tell a wrong
+    /// password apart from other connection failures (like an unreachable server)
+    ///
+    /// ## Example
+    /// ```no_run
+    /// use skytable::Config;
+    ///
+    /// match Config::new_default("username", "password").connect() {
+    ///     Ok(_db) => println!("connected"),
+    ///     Err(e) if e.is_auth_error() => println!("wrong username or password"),
+    ///     Err(e) => println!("couldn't connect: {}", e),
+    /// }
+    /// ```
+    pub fn is_auth_error(&self) -> bool {
+        matches!(self, Self::ConnectionSetupErr(e) if e.is_auth_error())
+    }
 }
 
 impl std::error::Error for Error {}
@@ -51,19 +137,40 @@ impl fmt::Display for Error {
             Self::IoError(e) => write!(f, "io error: {e}"),
             Self::ConnectionSetupErr(e) => write!(f, "connection setup error: {e}"),
             Self::ProtocolError(e) => write!(f, "protocol error: {e}"),
-            Self::ServerError(e) => write!(f, "server error: {e}"),
+            Self::ServerError(e) => write!(f, "server error {e}: {}", describe(*e)),
             Self::ParseError(e) => write!(f, "application parse error: {e}"),
+            Self::InvalidQuery(e) => write!(f, "invalid query: {e}"),
+            Self::Timeout => write!(f, "query timed out"),
         }
     }
 }
 
 #[derive(Debug, PartialEq, Clone)]
+#[non_exhaustive]
 /// An application level parse error, usually raised by [`FromResponse`](crate::response::FromResponse)
 pub enum ParseError {
-    /// The response is non-erroring, but the type is not what was expected
+    /// The response is non-erroring, but the type is not what was expected. The conversions in this crate (including the
+    /// derive macros) return [`ParseError::TypeMismatchGot`] instead, so this is only returned by your own
+    /// implementations
     TypeMismatch,
+    /// A value couldn't be converted into the requested type. Unlike [`ParseError::TypeMismatch`], this keeps the value
+    /// that the server actually sent, so that it can be logged
+    TypeMismatchGot {
+        /// The name of the type that was requested
+        expected: &'static str,
+        /// The value that was received
+        got: Value,
+    },
     /// The response is non-erroring, but not of the kind we were looking for (for example, if you try to parse a single value from a Row, it won't work!)
     ResponseMismatch,
+    /// The row doesn't have the number of columns that the type expects (for example, when a struct has more fields than
+    /// the columns returned by a `select`)
+    ColumnCountMismatch {
+        /// The number of columns the type expects
+        expected: usize,
+        /// The number of columns in the row
+        actual: usize,
+    },
     /// Some other parse error occurred
     Other(String),
 }
@@ -73,29 +180,58 @@ impl fmt::Display for ParseError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::TypeMismatch => write!(f, "data type mismatch"),
+            Self::TypeMismatchGot { expected, got } => {
+                write!(f, "expected {expected}, got {got:?}")
+            }
             Self::ResponseMismatch => write!(f, "response type mismatch"),
+            Self::ColumnCountMismatch { expected, actual } => {
+                write!(f, "expected {expected} columns, got {actual}")
+            }
             Self::Other(e) => write!(f, "{e}"),
         }
     }
 }
 
 #[derive(Debug, PartialEq, Clone)]
+#[non_exhaustive]
 /// An error specifically returned during connection setup. This is returned usually when there is a bad configuration
 pub enum ConnectionSetupError {
     /// Some error occurred while setting up a connection
     Other(String),
-    /// Handshake failed while establishing a connection
+    /// Handshake failed while establishing a connection, with an error code that doesn't have its own variant (see
+    /// [`describe`])
     HandshakeError(u8),
+    /// The server rejected the handshake because the username or password is wrong
+    BadCredentials,
+    /// The server rejected the handshake because the user isn't allowed to connect
+    PermissionDenied,
     /// The server responded with an invalid handshake
     InvalidServerHandshake,
 }
 
+impl ConnectionSetupError {
+    /// Returns the error for a handshake that the server rejected with the given error code
+    pub(crate) fn from_handshake_code(code: u8) -> Self {
+        match code as u16 {
+            ERR_AUTH_FAILED => Self::BadCredentials,
+            ERR_PERMISSION_DENIED => Self::PermissionDenied,
+            _ => Self::HandshakeError(code),
+        }
+    }
+    /// Returns true if the server refused the connection because of the credentials (see [`Error::is_auth_error`])
+    pub fn is_auth_error(&self) -> bool {
+        matches!(self, Self::BadCredentials | Self::PermissionDenied)
+    }
+}
+
 impl std::error::Error for ConnectionSetupError {}
 impl fmt::Display for ConnectionSetupError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::Other(e) => write!(f, "{e}"),
             Self::HandshakeError(e) => write!(f, "handshake error code {e}"),
+            Self::BadCredentials => write!(f, "bad credentials"),
+            Self::PermissionDenied => write!(f, "permission denied"),
             Self::InvalidServerHandshake => write!(f, "server sent invalid handshake"),
         }
     }
@@ -104,12 +240,32 @@ impl fmt::Display for ConnectionSetupError {
 impl std::error::Error for ProtocolError {}
 impl fmt::Display for ProtocolError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Self::InvalidServerResponseForData => write!(f, "invalid data received from server"),
-            Self::InvalidServerResponseUnknownDataType => {
-                write!(f, "new or unknown data type received from server")
+        match self.kind() {
+            ProtocolErrorKind::InvalidServerResponseForData => {
+                write!(f, "invalid data received from server")?
+            }
+            ProtocolErrorKind::InvalidServerResponseUnknownDataType => {
+                write!(f, "new or unknown data type received from server")?
+            }
+            ProtocolErrorKind::ReservedDataType { code } => {
+                write!(f, "reserved data type 0x{code:02x} received from server")?
+            }
+            ProtocolErrorKind::ResponseTooLarge { limit } => {
+                write!(f, "response exceeds the maximum size of {limit} bytes")?
             }
         }
+        write!(f, " at offset {} (near:", self.offset())?;
+        for byte in self.context() {
+            write!(f, " {byte:02x}")?;
+        }
+        write!(f, ")")?;
+        if let ProtocolErrorKind::ReservedDataType { .. } = self.kind() {
+            write!(
+                f,
+                "; the server uses a newer protocol feature, so upgrade the client"
+            )?;
+        }
+        Ok(())
     }
 }
 
@@ -134,3 +290,49 @@ impl From<ConnectionSetupError> for Error {
         Self::ConnectionSetupErr(e)
     }
 }
+
+#[test]
+fn t_describe() {
+    assert_eq!(describe(0), "server error");
+    assert_eq!(describe(5), "permission denied");
+    assert_eq!(describe(ERR_OBJECT_ALREADY_EXISTS), "object already exists");
+    assert_eq!(describe(110), "where clause uses an unindexed column");
+    assert_eq!(describe(ERR_ROW_NOT_FOUND), "row not found");
+    assert_eq!(describe(28), "invalid syntax");
+    // unknown codes
+    assert_eq!(describe(7), "unknown error");
+    assert_eq!(describe(u16::MAX), "unknown error");
+    assert_eq!(
+        Error::ServerError(111).to_string(),
+        "server error 111: row not found"
+    );
+    assert_eq!(
+        Error::ServerError(999).to_string(),
+        "server error 999: unknown error"
+    );
+}
+
+#[test]
+fn t_handshake_error_code() {
+    assert_eq!(
+        ConnectionSetupError::from_handshake_code(3),
+        ConnectionSetupError::BadCredentials
+    );
+    assert_eq!(
+        ConnectionSetupError::from_handshake_code(5),
+        ConnectionSetupError::PermissionDenied
+    );
+    assert_eq!(
+        ConnectionSetupError::from_handshake_code(6),
+        ConnectionSetupError::HandshakeError(6)
+    );
+    assert!(Error::from(ConnectionSetupError::from_handshake_code(3)).is_auth_error());
+    assert!(Error::from(ConnectionSetupError::PermissionDenied).is_auth_error());
+    assert!(!Error::from(ConnectionSetupError::HandshakeError(6)).is_auth_error());
+    assert!(!Error::from(ConnectionSetupError::InvalidServerHandshake).is_auth_error());
+    // a permission error while running a query is not a connection error
+    assert!(!Error::ServerError(ERR_PERMISSION_DENIED).is_auth_error());
+    assert!(
+        !Error::from(std::io::Error::from(std::io::ErrorKind::ConnectionRefused)).is_auth_error()
+    );
+}
@@ -0,0 +1,231 @@
+/*
+ * Copyright 2023, Sayan Nandan <nandansayan@outlook.com>
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+
+//! A minimal JSON parser for the few responses (like `inspect global`) that the server sends as JSON strings
+
+use std::{iter::Peekable, str::Chars};
+
+#[derive(Debug, PartialEq, Clone)]
+/// A parsed JSON value. Numbers are kept as written, since no caller needs them as numbers yet
+pub(crate) enum Json {
+    Null,
+    Bool(bool),
+    Number(String),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    /// Parse a complete JSON document, returning `None` if it isn't valid JSON
+    pub(crate) fn parse(s: &str) -> Option<Self> {
+        let mut chars = s.chars().peekable();
+        let value = parse_value(&mut chars, 0)?;
+        skip_whitespace(&mut chars);
+        match chars.next() {
+            None => Some(value),
+            Some(_) => None,
+        }
+    }
+    /// Returns the value of `key`, if this is an object that has it (nested objects aren't searched)
+    pub(crate) fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Self::Object(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+    /// Returns the string, if this is one
+    pub(crate) fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(s) => Some(s),
+            _ => None,
+        }
+    }
+    /// Returns the elements of an array of strings
+    pub(crate) fn as_strings(&self) -> Option<Vec<String>> {
+        match self {
+            Self::Array(elements) => elements
+                .iter()
+                .map(|e| e.as_str().map(str::to_owned))
+                .collect(),
+            _ => None,
+        }
+    }
+}
+
+/// Deeply nested documents are rejected instead of overflowing the stack
+const MAX_DEPTH: usize = 128;
+
+type Input<'a> = Peekable<Chars<'a>>;
+
+fn skip_whitespace(chars: &mut Input) {
+    while chars
+        .next_if(|c| matches!(c, ' ' | '\t' | '\n' | '\r'))
+        .is_some()
+    {}
+}
+
+fn expect(chars: &mut Input, word: &str) -> Option<()> {
+    word.chars()
+        .try_for_each(|c| chars.next_if_eq(&c).map(drop))
+}
+
+fn parse_value(chars: &mut Input, depth: usize) -> Option<Json> {
+    if depth > MAX_DEPTH {
+        return None;
+    }
+    skip_whitespace(chars);
+    Some(match *chars.peek()? {
+        'n' => expect(chars, "null").map(|_| Json::Null)?,
+        't' => expect(chars, "true").map(|_| Json::Bool(true))?,
+        'f' => expect(chars, "false").map(|_| Json::Bool(false))?,
+        '"' => Json::String(parse_string(chars)?),
+        '[' => {
+            chars.next();
+            let mut elements = vec![];
+            skip_whitespace(chars);
+            if chars.next_if_eq(&']').is_none() {
+                loop {
+                    elements.push(parse_value(chars, depth + 1)?);
+                    skip_whitespace(chars);
+                    match chars.next()? {
+                        ',' => {}
+                        ']' => break,
+                        _ => return None,
+                    }
+                }
+            }
+            Json::Array(elements)
+        }
+        '{' => {
+            chars.next();
+            let mut fields = vec![];
+            skip_whitespace(chars);
+            if chars.next_if_eq(&'}').is_none() {
+                loop {
+                    skip_whitespace(chars);
+                    let key = parse_string(chars)?;
+                    skip_whitespace(chars);
+                    chars.next_if_eq(&':')?;
+                    fields.push((key, parse_value(chars, depth + 1)?));
+                    skip_whitespace(chars);
+                    match chars.next()? {
+                        ',' => {}
+                        '}' => break,
+                        _ => return None,
+                    }
+                }
+            }
+            Json::Object(fields)
+        }
+        '-' | '0'..='9' => Json::Number(parse_number(chars)?),
+        _ => return None,
+    })
+}
+
+fn parse_number(chars: &mut Input) -> Option<String> {
+    let mut number = String::new();
+    while let Some(c) = chars.next_if(|c| matches!(c, '-' | '+' | '.' | 'e' | 'E' | '0'..='9')) {
+        number.push(c);
+    }
+    // the characters are checked loosely above, so make sure that they form a number
+    number.parse::<f64>().ok()?;
+    Some(number)
+}
+
+fn parse_string(chars: &mut Input) -> Option<String> {
+    chars.next_if_eq(&'"')?;
+    let mut s = String::new();
+    loop {
+        match chars.next()? {
+            '"' => return Some(s),
+            '\\' => match chars.next()? {
+                '"' => s.push('"'),
+                '\\' => s.push('\\'),
+                '/' => s.push('/'),
+                'b' => s.push('\u{8}'),
+                'f' => s.push('\u{c}'),
+                'n' => s.push('\n'),
+                'r' => s.push('\r'),
+                't' => s.push('\t'),
+                'u' => {
+                    let high = parse_hex4(chars)?;
+                    let c = match high {
+                        0xD800..=0xDBFF => {
+                            expect(chars, "\\u")?;
+                            let low = parse_hex4(chars)?;
+                            if !(0xDC00..=0xDFFF).contains(&low) {
+                                return None;
+                            }
+                            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+                        }
+                        c => c,
+                    };
+                    s.push(char::from_u32(c)?);
+                }
+                _ => return None,
+            },
+            c if (c as u32) < 0x20 => return None,
+            c => s.push(c),
+        }
+    }
+}
+
+fn parse_hex4(chars: &mut Input) -> Option<u32> {
+    (0..4).try_fold(0, |n, _| Some(n * 16 + chars.next()?.to_digit(16)?))
+}
+
+#[test]
+fn t_parse() {
+    let doc = Json::parse(
+        r#" {"spaces": ["a", "b\"c\u00e9\ud83d\ude00"], "n": -1.5e3, "nested": {"spaces": null}, "ok": [true, false], "e": {}} "#,
+    )
+    .unwrap();
+    assert_eq!(
+        doc.get("spaces").unwrap().as_strings().unwrap(),
+        ["a", "b\"cé😀"]
+    );
+    assert_eq!(doc.get("n"), Some(&Json::Number("-1.5e3".into())));
+    assert_eq!(doc.get("nested").unwrap().get("spaces"), Some(&Json::Null));
+    assert_eq!(
+        doc.get("ok"),
+        Some(&Json::Array(vec![Json::Bool(true), Json::Bool(false)]))
+    );
+    assert_eq!(doc.get("e"), Some(&Json::Object(vec![])));
+    assert_eq!(doc.get("missing"), None);
+    assert_eq!(Json::Array(vec![]).get("spaces"), None);
+    for bad in [
+        "",
+        "{",
+        "{\"a\"}",
+        "{\"a\":1,}",
+        "[1 2]",
+        "\"unterminated",
+        "\"\\x\"",
+        "\"\\ud83d\"",
+        "nul",
+        "1-",
+        "{} {}",
+        "{'a': 1}",
+    ]
+    .iter()
+    {
+        assert_eq!(Json::parse(bad), None, "{}", bad);
+    }
+    // deep nesting is rejected instead of overflowing the stack
+    let deep = "[".repeat(10_000) + &"]".repeat(10_000);
+    assert_eq!(Json::parse(&deep), None);
+}
@@ -41,7 +41,7 @@
 //!
 //! // insert data
 //! let q = query!("insert into myspace.mymodel(?, ?, ?, ?)", our_user.clone());
-//! db.query_parse::<()>(&q).unwrap();
+//! db.exec(&q).unwrap();
 //!
 //! // select data
 //! let user: User = db.query_parse(&query!("select * from myspace.mymodel where username = ?", &our_user.userid)).unwrap();
@@ -78,7 +78,7 @@
 //! let mut db = Config::new_default("username", "password").connect().unwrap();
 //!
 //! let insert_query = query!("insert into myspace.mymodel(?, ?, ?)", "sayan", "pass123", 1_500_000_u64);
-//! db.query_parse::<()>(&insert_query).unwrap(); // insert will return empty on success
+//! db.exec(&insert_query).unwrap(); // use `exec` when you only care that the query succeeded
 //!
 //! let select_query = query!("select password, followers FROM myspace.mymodel WHERE username = ?", "sayan");
 //! let (pass, followers): (String, u64) = db.query_parse(&select_query).unwrap();
@@ -86,19 +86,46 @@
 //! assert_eq!(followers, 1_500_000_u64);
 //!
 //! let update_query = query!("update myspace.mymodel set followers += ? where username = ?", 1u64, "sayan");
-//! db.query_parse::<()>(&update_query).unwrap(); // update will return empty on success
+//! db.exec(&update_query).unwrap();
 //! ```
 //!
 //! Now you're ready to run your own queries!
 //!
 //! ## Going advanced
 //!
-//! You can use the [`macro@Query`] and [`macro@Response`] derive macros to directly pass complex types as parameters
-//! and read as responses. This should cover most of the general use-cases (otherwise you can manually implement them).
+//! You can use the [`macro@Query`] and [`macro@Response`] derive macros (or [`macro@SkyModel`] for both) to directly pass
+//! complex types as parameters and read as responses. This should cover most of the general use-cases (otherwise you can
+//! manually implement them).
 //!
 //! - Custom [`mod@query`] generation
 //! - Custom [`response`] parsing
+//! - Checking query parameters against a model's [`schema`]
 //! - [`Connection pooling`](pool)
+//! - Writing code once for any async connection with [`Queryable`], or any sync connection with [`BlockingQueryable`]
+//! - Reading large models one page at a time with a [`Paginator`](paginate::Paginator)
+//! - Building compatible tooling with the Skyhash [`wire`](protocol::wire) constants
+//!
+//! ## Optional features
+//!
+//! - `native-tls` (enabled by default): TLS connections use the platform's native TLS library (OpenSSL on Linux)
+//! - `rustls`: TLS connections use [`rustls`](https://docs.rs/rustls) instead, which needs no system libraries (for
+//!   example, for static `musl` builds). If both TLS features are enabled, `rustls` is used; disable the default
+//!   features to leave out `native-tls` entirely
+//! - `logging`: logs every encoded query at the `trace` level using the [`log`](https://docs.rs/log) crate (see
+//!   [`Config::set_redact_logs`] to leave parameters out of logged packets)
+//! - `testing`: provides a mock connection for unit testing your code without a server (see the `testing` module)
+//! - `bytes`: lets you use [`bytes::Bytes`](https://docs.rs/bytes) as a query parameter and to parse binary and string
+//!   values, so that they can be cloned cheaply
+//! - `async-std`: lets you connect with `Config::connect_async_std`, which uses `async-std` instead of tokio for the
+//!   connection's I/O
+//! - `deadpool`: adds connection pools built on `deadpool`, which (unlike the default `bb8` pools) aren't tied to tokio.
+//!   Combine it with `async-std` for pools that need no tokio at all (see the [`pool`] module)
+//! - `serde`: lets you deserialize a [`Row`](response::Row) into any type that implements `serde::Deserialize`, matching
+//!   columns to fields by position (see `Row::deserialize`)
+//! - `rust_decimal`: lets you use [`rust_decimal::Decimal`](https://docs.rs/rust_decimal) as a query parameter and to
+//!   parse values. Decimals are stored as text, so the column must be declared as a `string`
+//! - `socks`: lets you connect through a SOCKS5 proxy with `Config::connect_via_socks5` (and
+//!   `Config::connect_async_via_socks5`)
 //!
 //! ## Need help? Get help!
 //!
@@ -111,25 +138,105 @@
 // internal modules
 #[macro_use]
 mod macros;
-mod protocol;
+#[cfg(feature = "serde")]
+mod de;
+mod json;
+mod tls;
 // public modules
 pub mod aio;
 pub mod config;
 pub mod error;
+pub mod paginate;
 pub mod pool;
+pub mod protocol;
 pub mod query;
+pub mod queryable;
 pub mod response;
+pub mod schema;
 pub mod syncio;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[doc(hidden)]
+pub use sky_derive::__query_named;
 /// The `Query` derive macro enables you to directly pass complex types as parameters into queries
+///
+/// Each field is a parameter, in declaration order. Fields marked `#[skytable(skip)]` (for example, ones that are only
+/// used by your application) aren't sent.
 pub use sky_derive::Query;
 /// The `Response` derive macro enables you to directly pass complex types as parameters into queries
+///
+/// Each field is read from the column at the same position in the row. Trailing fields marked `#[skytable(default)]`
+/// may be missing from the row (for example, when a column was added to the model after some rows were selected by an
+/// older query), in which case they're set to [`Default::default()`]. Fields marked `#[skytable(skip)]` aren't read from
+/// the row at all, and are always set to [`Default::default()`].
+///
+/// ## Example
+/// ```
+/// use skytable::{response::{FromResponse, Response as Resp, Row, Value}, Response};
+///
+/// #[derive(Response)]
+/// struct User {
+///     username: String,
+///     followers: u64,
+///     #[skytable(default)]
+///     verified: bool,
+/// }
+///
+/// let user = User::from_response(Resp::Row(Row::new(vec![Value::str("sayan"), Value::uint(100)]))).unwrap();
+/// assert!(!user.verified);
+/// ```
 pub use sky_derive::Response;
+/// The `SkyEnum` derive macro lets you use an enum with unit variants as a query parameter and read it from a value
+///
+/// Each variant is stored as its name in a `string` column, or, if every variant has a `#[skytable(value = N)]` attribute,
+/// as that unsigned integer (the values must be distinct). The shorter `#[sky(value = N)]` is accepted as well. Reading
+/// a name or number that doesn't belong to any variant fails with
+/// [`ParseError::TypeMismatchGot`](crate::error::ParseError::TypeMismatchGot), with the enum's name as `expected`.
+///
+/// ## Example
+/// ```
+/// use skytable::{query, SkyEnum};
+///
+/// #[derive(SkyEnum)]
+/// enum Status {
+///     #[skytable(value = 0)]
+///     Active,
+///     #[skytable(value = 1)]
+///     Suspended,
+/// }
+///
+/// let q = query!("update myspace.users set status = ? where username = ?", Status::Suspended, "sayan");
+/// assert_eq!(q.param_cnt(), 2);
+/// ```
+pub use sky_derive::SkyEnum;
+/// The `SkyModel` derive macro is a shorthand for `#[derive(Query, Response)]`, for a struct that is both written to and
+/// read from a model. It accepts the same `#[skytable(...)]` field attributes
+///
+/// ## Example
+/// ```
+/// use skytable::{query, response::{FromResponse, Response, Row, Value}, SkyModel};
+///
+/// #[derive(SkyModel, Debug, PartialEq)]
+/// struct User {
+///     username: String,
+///     followers: u64,
+/// }
+///
+/// let user = User { username: "sayan".into(), followers: 100 };
+/// assert_eq!(query!("insert into myspace.users(?, ?)", &user).param_cnt(), 2);
+/// let row = Response::Row(Row::new(vec![Value::str("sayan"), Value::uint(100)]));
+/// assert_eq!(User::from_response(row).unwrap(), user);
+/// ```
+pub use sky_derive::SkyModel;
 // re-exports
+#[cfg(feature = "async-std")]
+pub use aio::ConnectionAsyncStd;
 pub use {
     aio::{ConnectionAsync, ConnectionTlsAsync},
     config::Config,
     error::ClientResult,
     query::Query,
+    queryable::{BlockingQueryable, Queryable},
     syncio::{Connection, ConnectionTls},
 };
 
@@ -17,6 +17,9 @@
 #[macro_export]
 /// This macro can be used to create a [`Query`](struct@crate::Query), almost like a variadic function
 /// 
+/// Every parameter must implement [`SQParam`](crate::query::SQParam). To pass all the fields of a struct as parameters,
+/// add `#[derive(Query)]` to it.
+/// 
 /// ## Examples
 /// ```
 /// use skytable::query;
@@ -29,18 +32,70 @@
 /// let query2 = query!("update myspace.mymodel set counter += ? WHERE username = ?", get_counter(), get_username());
 /// assert_eq!(query2.param_cnt(), 2);
 /// ```
+/// 
+/// ## Named parameters
+/// 
+/// Instead of `?`, a query string literal can use `:name` placeholders, with the values given as `name = value`. The
+/// placeholders are replaced with `?` and the values are added in the order the placeholders appear, so the order of
+/// the bindings doesn't matter. A name can be used more than once, and every value is evaluated exactly once, in the
+/// order the bindings are written. A placeholder without a value, a value that isn't used, a name that's bound twice, or
+/// mixing `:name` with `?` is a compile error.
+/// 
+/// A `:` that follows an identifier (like in `username:string`) or that's inside a quoted string is left alone. Each
+/// value must be a single parameter (a `#[derive(Query)]` struct adds one parameter for each of its fields).
+/// 
+/// ```
+/// use skytable::query;
+/// 
+/// let q = query!(
+///     "update myspace.mymodel set followers += :by, following += :by where username = :name",
+///     name = "sayan",
+///     by = 1u64,
+/// );
+/// assert_eq!(q.query_str(), "update myspace.mymodel set followers += ?, following += ? where username = ?");
+/// assert_eq!(q.param_cnt(), 3);
+/// ```
 macro_rules! query {
+    ($query_str:literal, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::__query_named!($query_str, $($name = $value),+)
+    };
     ($query_str:expr) => { $crate::Query::from($query_str) };
     ($query_str:expr$(, $($query_param:expr),* $(,)?)?) => {{
         let mut q = $crate::Query::from($query_str); $($(q.push_param($query_param);)*)*q
     }};
 }
 
+#[macro_export]
+/// This macro can be used to create a [`Pipeline`](crate::query::Pipeline) from a list of queries
+/// 
+/// Each query is written as a parenthesized group with the same arguments that you would pass to [`query!`], and the
+/// groups are separated by semicolons.
+/// 
+/// ## Examples
+/// ```
+/// use skytable::pipeline;
+/// 
+/// let pipeline = pipeline! {
+///     ("insert into myspace.mymodel(?, ?)", "sayan", 100u64);
+///     ("select * from myspace.mymodel where username = ?", "sayan");
+///     ("select all * from myspace.mymodel limit 10");
+/// };
+/// assert_eq!(pipeline.query_count(), 3);
+/// ```
+macro_rules! pipeline {
+    ($(($($query:tt)*));* $(;)?) => {{
+        #[allow(unused_mut)]
+        let mut p = $crate::query::Pipeline::new();
+        $(p.add_query(&$crate::query!($($query)*));)*
+        p
+    }};
+}
+
 macro_rules! pushlen {
     ($buf:expr, $len:expr) => {{
         let mut buf = ::itoa::Buffer::new();
         let r = ::itoa::Buffer::format(&mut buf, $len);
         $buf.extend(str::as_bytes(r));
-        $buf.push(b'\n');
+        $buf.push($crate::protocol::wire::LF);
     }};
 }
@@ -0,0 +1,146 @@
+/*
+ * Copyright 2023, Sayan Nandan <nandansayan@outlook.com>
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+
+//! # Pagination
+//!
+//! A [`Paginator`] runs a `select ... limit ? offset ?` query one page at a time, moving the offset forward after every
+//! page, so that a large model can be read without fetching all of its rows at once:
+//!
+//! ```no_run
+//! use skytable::{paginate::Paginator, query, Config, Response};
+//!
+//! #[derive(Response)]
+//! struct User {
+//!     username: String,
+//!     followers: u64,
+//! }
+//!
+//! async fn run() {
+//!     let mut db = Config::new_default("username", "password").connect_async().await.unwrap();
+//!     let mut pages = Paginator::new(
+//!         query!("select all * from myspace.users limit ? offset ?"),
+//!         100,
+//!     )
+//!     .unwrap();
+//!     while let Some(users) = pages.next_page::<User, _>(&mut db).await.unwrap() {
+//!         for user in users {
+//!             println!("{} has {} followers", user.username, user.followers);
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! Pages are read with any [`Queryable`] connection. For a sync connection (or any other [`BlockingQueryable`]), use
+//! [`Paginator::next_page_blocking`] instead.
+
+use crate::{
+    error::{ClientResult, Error},
+    response::{FromResponse, Response},
+    BlockingQueryable, Query, Queryable,
+};
+
+#[derive(Debug, Clone)]
+/// Reads the rows returned by a query one page at a time (see the [module level docs](self))
+pub struct Paginator {
+    q: Query,
+    limit: u64,
+    offset: u64,
+    done: bool,
+}
+
+impl Paginator {
+    /// Create a paginator for `q`, which returns at most `limit` rows per page
+    ///
+    /// The query string must end with two placeholders for the limit and the offset (like `limit ? offset ?`), which
+    /// are filled in for every page. `q` itself only has the parameters for any placeholders that come before them.
+    /// This fails with [`Error::InvalidQuery`] if `limit` is zero or if `q` doesn't have exactly two placeholders left
+    pub fn new(q: Query, limit: u64) -> ClientResult<Self> {
+        if limit == 0 {
+            return Err(Error::InvalidQuery(
+                "the page limit must not be zero".into(),
+            ));
+        }
+        if q.placeholder_count() != q.param_cnt() + 2 {
+            return Err(Error::InvalidQuery(format!(
+                "expected 2 placeholders for the limit and offset after {} parameters, but the query has {} placeholders",
+                q.param_cnt(),
+                q.placeholder_count()
+            )));
+        }
+        Ok(Self {
+            q,
+            limit,
+            offset: 0,
+            done: false,
+        })
+    }
+    /// Returns the maximum number of rows in a page
+    pub fn limit(&self) -> u64 {
+        self.limit
+    }
+    /// Returns the offset of the next page
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+    /// Returns true if the last page has been read
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+    /// Run the query for the next page and parse every row into `T`, returning `None` once all rows have been read
+    ///
+    /// A page with fewer rows than the limit is the last one. If the query fails, the offset isn't moved, so the same
+    /// page is queried again on the next call
+    pub async fn next_page<T: FromResponse, Q: Queryable>(
+        &mut self,
+        db: &mut Q,
+    ) -> ClientResult<Option<Vec<T>>> {
+        match self.page_query() {
+            Some(q) => self.read_page(db.query(&q).await?),
+            None => Ok(None),
+        }
+    }
+    /// Like [`Paginator::next_page`], but for a sync connection, blocking until the page is read
+    pub fn next_page_blocking<T: FromResponse, Q: BlockingQueryable>(
+        &mut self,
+        db: &mut Q,
+    ) -> ClientResult<Option<Vec<T>>> {
+        match self.page_query() {
+            Some(q) => self.read_page(db.query(&q)?),
+            None => Ok(None),
+        }
+    }
+    /// Returns the query for the next page, or `None` if the last page has been read
+    fn page_query(&self) -> Option<Query> {
+        if self.done {
+            None
+        } else {
+            Some(self.q.clone_with_params((self.limit, self.offset)))
+        }
+    }
+    /// Parse the response to [`Paginator::page_query`] and move the offset past its rows
+    fn read_page<T: FromResponse>(&mut self, resp: Response) -> ClientResult<Option<Vec<T>>> {
+        let page = resp.rows_as::<T>().collect::<ClientResult<Vec<T>>>()?;
+        self.offset += page.len() as u64;
+        if (page.len() as u64) < self.limit {
+            self.done = true;
+        }
+        if page.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(page))
+        }
+    }
+}
@@ -42,18 +42,99 @@
 //! ```
 //!
 //! To create a pool of TLS connections you can use the [`get_tls`] and [`get_tls_async`] methods, passing a PEM certificate
-//! as a string.
+//! as a string. If you'd rather have a bad configuration fail on startup than on the first `get()`, use
+//! [`get_validated`].
+//!
+//! ## Server restarts
+//!
+//! If the server is restarted, every pooled connection is lost. Connections are checked with a status query before
+//! being handed out, and the ones that fail with an I/O or protocol error (or that were left broken by a failed
+//! query, see [`Connection::is_broken`](crate::syncio::TcpConnection::is_broken)) are dropped, so the next `get()`
+//! transparently connects again. A server error in reply to the status query doesn't count, since the server did answer.
+//! To skip the round trip on most checkouts from an async pool, build the pool from a manager with a socket check (for
+//! example, [`ConnectionMgrTcp::with_socket_check`]), which runs the status query only periodically.
+//!
+//! ## Runtime-agnostic pools
+//!
+//! [`bb8`] expects to be driven by tokio. With the `deadpool` feature, `get_deadpool` and `get_deadpool_tls` return
+//! pools built on `deadpool` instead, which doesn't need any particular executor: the pool itself only uses
+//! runtime-independent synchronization and never starts timers (no wait, create or recycle timeouts are configured).
+//! The connections handed out are still [`ConnectionAsync`]s, so these need a tokio reactor for their I/O. If you're
+//! on `async-std` or `smol`, also enable the `async-std` feature and use `get_deadpool_async_std`, whose connections
+//! do their I/O through `async-std` and need no tokio at all.
+//!
+//! ```no_run
+//! # #[cfg(all(feature = "deadpool", feature = "async-std"))]
+//! # async fn pool() {
+//! use skytable::{pool, Config};
+//!
+//! let pool = pool::get_deadpool_async_std(32, Config::new_default("username", "password")).unwrap();
+//! let mut db = pool.get().await.unwrap();
+//! # }
+//! ```
 //!
 
-use crate::{error::Error, Config, Connection, ConnectionAsync, ConnectionTls, ConnectionTlsAsync};
+use {
+    crate::{
+        aio,
+        error::{ClientResult, ConnectionSetupError, Error},
+        Config, Connection, ConnectionAsync, ConnectionTls, ConnectionTlsAsync,
+    },
+    std::time::{Duration, Instant},
+    tokio::io::{AsyncReadExt, AsyncWriteExt},
+};
 
 const QUERY_SYSCTL_STATUS: &str = "sysctl report status";
 
+/// A pooled connection is healthy if the server answered the status query, even if it answered with an error (for
+/// example, because the user can't run `sysctl`). Only I/O and protocol errors, like a connection reset by a server
+/// restart, mean that the connection has to be replaced
+fn check_status(status: ClientResult<()>) -> ClientResult<()> {
+    match status {
+        Err(Error::ServerError(_)) => Ok(()),
+        status => status,
+    }
+}
+
+/// Check a pooled async connection. With a full check interval (see [`ConnectionMgrTcp::with_socket_check`]), the
+/// status query only runs if it hasn't succeeded on this connection within the interval, and otherwise only the socket
+/// is checked
+async fn check_async<C: AsyncWriteExt + AsyncReadExt + Unpin>(
+    conn: &mut aio::TcpConnection<C>,
+    full_check_interval: Option<Duration>,
+) -> ClientResult<()> {
+    if let (Some(interval), Some(last)) = (full_check_interval, conn.last_status_check) {
+        if last.elapsed() < interval {
+            return conn.check_alive().await;
+        }
+    }
+    check_status(conn.query_parse::<()>(&query!(QUERY_SYSCTL_STATUS)).await)?;
+    conn.last_status_check = Some(Instant::now());
+    Ok(())
+}
+
+#[cfg(all(feature = "deadpool", feature = "async-std"))]
+use crate::ConnectionAsyncStd;
+#[cfg(feature = "deadpool")]
+use deadpool::managed::{self, BuildError, Metrics, RecycleError, RecycleResult};
+
 /// Returns a TCP (skyhash/TCP) connection pool using [`r2d2`]'s default settings and the given maximum pool size
 pub fn get(pool_size: u32, config: Config) -> Result<r2d2::Pool<ConnectionMgrTcp>, r2d2::Error> {
     let mgr = ConnectionMgrTcp::new(config);
     r2d2::Pool::builder().max_size(pool_size).build(mgr)
 }
+/// Returns a TCP (skyhash/TCP) connection pool like [`get`], but only after establishing one connection to check that
+/// the configuration works. Problems like a wrong address or bad credentials are returned as is, so that you can fail
+/// fast on startup instead of discovering them on the first `get()`
+pub fn get_validated(pool_size: u32, config: Config) -> ClientResult<r2d2::Pool<ConnectionMgrTcp>> {
+    let mgr = ConnectionMgrTcp::new(config);
+    // connect directly, since r2d2 would just retry until it times out and then discard the error
+    drop(r2d2::ManageConnection::connect(&mgr)?);
+    r2d2::Pool::builder()
+        .max_size(pool_size)
+        .build(mgr)
+        .map_err(|e| ConnectionSetupError::Other(e.to_string()).into())
+}
 /// Returns an async TCP (skyhash/TCP) connection pool using [`bb8`]'s default settings and the given maximum pool size
 pub async fn get_async(
     pool_size: u32,
@@ -81,16 +162,76 @@ pub async fn get_tls_async(
     bb8::Pool::builder().max_size(pool_size).build(mgr).await
 }
 
+#[cfg(feature = "deadpool")]
+/// Returns an async TCP (skyhash/TCP) connection pool using `deadpool`'s default settings and the given maximum pool
+/// size. This is available with the `deadpool` feature
+pub fn get_deadpool(
+    pool_size: usize,
+    config: Config,
+) -> Result<managed::Pool<ConnectionMgrTcp>, BuildError> {
+    let mgr = ConnectionMgrTcp::new(config);
+    managed::Pool::builder(mgr).max_size(pool_size).build()
+}
+#[cfg(feature = "deadpool")]
+/// Returns an async TLS (skyhash/TLS) connection pool using `deadpool`'s default settings and the given maximum pool
+/// size. This is available with the `deadpool` feature
+pub fn get_deadpool_tls(
+    pool_size: usize,
+    config: Config,
+    pem_cert: &str,
+) -> Result<managed::Pool<ConnectionMgrTls>, BuildError> {
+    let mgr = ConnectionMgrTls::new(config, pem_cert.into());
+    managed::Pool::builder(mgr).max_size(pool_size).build()
+}
+#[cfg(all(feature = "deadpool", feature = "async-std"))]
+/// Returns an async TCP (skyhash/TCP) connection pool whose connections use `async-std` for I/O, using `deadpool`'s
+/// default settings and the given maximum pool size. This is available with the `deadpool` and `async-std` features
+pub fn get_deadpool_async_std(
+    pool_size: usize,
+    config: Config,
+) -> Result<managed::Pool<ConnectionMgrAsyncStd>, BuildError> {
+    let mgr = ConnectionMgrAsyncStd::new(config);
+    managed::Pool::builder(mgr).max_size(pool_size).build()
+}
+
 #[derive(Debug, Clone, PartialEq)]
 /// A connection manager for Skyhash/TCP connections
 pub struct ConnectionMgrTcp {
     config: Config,
+    full_check_interval: Option<Duration>,
 }
 
 impl ConnectionMgrTcp {
     /// Create a new connection manager for Skyhash/TCP connections
     pub fn new(config: Config) -> Self {
-        Self { config }
+        Self {
+            config,
+            full_check_interval: None,
+        }
+    }
+    /// Check async connections on checkout by looking at the socket instead of running a status query, unless the status
+    /// query hasn't succeeded on the connection within `full_check_interval`
+    ///
+    /// By default, every checkout from an async pool (`bb8` or `deadpool`) runs a status query, which costs a round trip
+    /// to the server. With this, most checkouts only check that the server hasn't closed the connection (see
+    /// [`TcpConnection::check_alive`](crate::aio::TcpConnection::check_alive)), which doesn't wait for the server. A
+    /// server that is still connected but no longer answers is only noticed by the next status query. Sync (`r2d2`)
+    /// pools always run the status query
+    ///
+    /// ## Example
+    /// ```no_run
+    /// use {skytable::{pool::ConnectionMgrTcp, Config}, std::time::Duration};
+    ///
+    /// async fn pool() {
+    ///     let mgr = ConnectionMgrTcp::new(Config::new_default("username", "password"))
+    ///         .with_socket_check(Duration::from_secs(30));
+    ///     let pool = bb8::Pool::builder().max_size(32).build(mgr).await.unwrap();
+    ///     let mut db = pool.get().await.unwrap();
+    /// }
+    /// ```
+    pub fn with_socket_check(mut self, full_check_interval: Duration) -> Self {
+        self.full_check_interval = Some(full_check_interval);
+        self
     }
 }
 
@@ -101,10 +242,10 @@ impl r2d2::ManageConnection for ConnectionMgrTcp {
         self.config.connect()
     }
     fn is_valid(&self, conn: &mut Self::Connection) -> Result<(), Self::Error> {
-        conn.query_parse::<()>(&query!(QUERY_SYSCTL_STATUS))
+        check_status(conn.query_parse::<()>(&query!(QUERY_SYSCTL_STATUS)))
     }
-    fn has_broken(&self, _: &mut Self::Connection) -> bool {
-        false
+    fn has_broken(&self, conn: &mut Self::Connection) -> bool {
+        conn.is_broken()
     }
 }
 
@@ -116,10 +257,25 @@ impl bb8::ManageConnection for ConnectionMgrTcp {
         self.config.connect_async().await
     }
     async fn is_valid(&self, conn: &mut Self::Connection) -> Result<(), Self::Error> {
-        conn.query_parse::<()>(&query!(QUERY_SYSCTL_STATUS)).await
+        check_async(conn, self.full_check_interval).await
     }
-    fn has_broken(&self, _: &mut Self::Connection) -> bool {
-        false
+    fn has_broken(&self, conn: &mut Self::Connection) -> bool {
+        conn.is_broken()
+    }
+}
+
+#[cfg(feature = "deadpool")]
+impl managed::Manager for ConnectionMgrTcp {
+    type Type = ConnectionAsync;
+    type Error = Error;
+    async fn create(&self) -> Result<Self::Type, Self::Error> {
+        self.config.connect_async().await
+    }
+    async fn recycle(&self, conn: &mut Self::Type, _: &Metrics) -> RecycleResult<Self::Error> {
+        if conn.is_broken() {
+            return Err(RecycleError::message("connection is broken"));
+        }
+        Ok(check_async(conn, self.full_check_interval).await?)
     }
 }
 
@@ -128,6 +284,7 @@ impl bb8::ManageConnection for ConnectionMgrTcp {
 pub struct ConnectionMgrTls {
     config: Config,
     pem_cert: String,
+    full_check_interval: Option<Duration>,
 }
 
 impl ConnectionMgrTls {
@@ -136,7 +293,18 @@ impl ConnectionMgrTls {
     /// The `pem_cert` argument must contain your TLS certificate in a PEM format.
     /// **NOTE: The `pem_cert` argument does NOT accept a file path!**
     pub fn new(config: Config, pem_cert: String) -> Self {
-        Self { config, pem_cert }
+        Self {
+            config,
+            pem_cert,
+            full_check_interval: None,
+        }
+    }
+    /// Check connections on checkout by looking at the socket instead of running a status query, unless the status
+    /// query hasn't succeeded on the connection within `full_check_interval` (see
+    /// [`ConnectionMgrTcp::with_socket_check`])
+    pub fn with_socket_check(mut self, full_check_interval: Duration) -> Self {
+        self.full_check_interval = Some(full_check_interval);
+        self
     }
 }
 
@@ -147,10 +315,10 @@ impl r2d2::ManageConnection for ConnectionMgrTls {
         self.config.connect_tls(&self.pem_cert)
     }
     fn is_valid(&self, conn: &mut Self::Connection) -> Result<(), Self::Error> {
-        conn.query_parse::<()>(&query!(QUERY_SYSCTL_STATUS))
+        check_status(conn.query_parse::<()>(&query!(QUERY_SYSCTL_STATUS)))
     }
-    fn has_broken(&self, _: &mut Self::Connection) -> bool {
-        false
+    fn has_broken(&self, conn: &mut Self::Connection) -> bool {
+        conn.is_broken()
     }
 }
 
@@ -162,9 +330,204 @@ impl bb8::ManageConnection for ConnectionMgrTls {
         self.config.connect_tls_async(&self.pem_cert).await
     }
     async fn is_valid(&self, conn: &mut Self::Connection) -> Result<(), Self::Error> {
-        conn.query_parse::<()>(&query!(QUERY_SYSCTL_STATUS)).await
+        check_async(conn, self.full_check_interval).await
+    }
+    fn has_broken(&self, conn: &mut Self::Connection) -> bool {
+        conn.is_broken()
+    }
+}
+
+#[cfg(feature = "deadpool")]
+impl managed::Manager for ConnectionMgrTls {
+    type Type = ConnectionTlsAsync;
+    type Error = Error;
+    async fn create(&self) -> Result<Self::Type, Self::Error> {
+        self.config.connect_tls_async(&self.pem_cert).await
+    }
+    async fn recycle(&self, conn: &mut Self::Type, _: &Metrics) -> RecycleResult<Self::Error> {
+        if conn.is_broken() {
+            return Err(RecycleError::message("connection is broken"));
+        }
+        Ok(check_async(conn, self.full_check_interval).await?)
+    }
+}
+
+#[cfg(all(feature = "deadpool", feature = "async-std"))]
+#[derive(Debug, Clone, PartialEq)]
+/// A connection manager for Skyhash/TCP connections that use `async-std` for I/O. Unlike the other managers, this one
+/// doesn't need tokio to be running
+pub struct ConnectionMgrAsyncStd {
+    config: Config,
+    full_check_interval: Option<Duration>,
+}
+
+#[cfg(all(feature = "deadpool", feature = "async-std"))]
+impl ConnectionMgrAsyncStd {
+    /// Create a new connection manager for Skyhash/TCP connections that use `async-std` for I/O
+    pub fn new(config: Config) -> Self {
+        Self {
+            config,
+            full_check_interval: None,
+        }
+    }
+    /// Check connections on checkout by looking at the socket instead of running a status query, unless the status
+    /// query hasn't succeeded on the connection within `full_check_interval` (see
+    /// [`ConnectionMgrTcp::with_socket_check`])
+    pub fn with_socket_check(mut self, full_check_interval: Duration) -> Self {
+        self.full_check_interval = Some(full_check_interval);
+        self
     }
-    fn has_broken(&self, _: &mut Self::Connection) -> bool {
-        false
+}
+
+#[cfg(all(feature = "deadpool", feature = "async-std"))]
+impl managed::Manager for ConnectionMgrAsyncStd {
+    type Type = ConnectionAsyncStd;
+    type Error = Error;
+    async fn create(&self) -> Result<Self::Type, Self::Error> {
+        self.config.connect_async_std().await
+    }
+    async fn recycle(&self, conn: &mut Self::Type, _: &Metrics) -> RecycleResult<Self::Error> {
+        if conn.is_broken() {
+            return Err(RecycleError::message("connection is broken"));
+        }
+        Ok(check_async(conn, self.full_check_interval).await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        error::{ConnectionSetupError, Error},
+        Config,
+    };
+    use std::{
+        io::{Read, Write},
+        iter,
+        net::{TcpListener, TcpStream},
+        sync::mpsc,
+        thread,
+    };
+
+    /// Accept the handshake and answer each query with the next response, closing the connection (like a server that's
+    /// shut down) once they run out. Returns false if the client closed the connection first
+    fn serve<'a>(mut stream: TcpStream, responses: impl Iterator<Item = &'a [u8]>) -> bool {
+        let mut responses = responses.peekable();
+        let mut buf = [0u8; 1024];
+        while responses.peek().is_some() {
+            match stream.read(&mut buf) {
+                Ok(0) | Err(_) => return false,
+                Ok(_) if buf[0] == b'H' => stream.write_all(b"H\x00\x00\x00").unwrap(),
+                Ok(_) => stream.write_all(responses.next().unwrap()).unwrap(),
+            }
+        }
+        true
+    }
+
+    #[test]
+    fn t_get_validated_bad_credentials() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0u8; 64];
+            let _ = stream.read(&mut buf).unwrap();
+            // reject the handshake
+            stream.write_all(b"H\x00\x01\x03").unwrap();
+        });
+        let ret = super::get_validated(4, Config::new("127.0.0.1", port, "user", "wrong"));
+        assert!(matches!(
+            ret,
+            Err(Error::ConnectionSetupErr(
+                ConnectionSetupError::BadCredentials
+            ))
+        ));
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn t_pool_recovers_after_restart() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let (restarted_tx, restarted_rx) = mpsc::channel();
+        let server = thread::spawn(move || {
+            // the status check fails with a server error, which doesn't make the connection unusable
+            let (stream, _) = listener.accept().unwrap();
+            assert!(serve(
+                stream,
+                [&b"\x10\x05\x00"[..], b"\x12"].iter().copied()
+            ));
+            // the server restarts, so the connection is gone
+            restarted_tx.send(()).unwrap();
+            let (stream, _) = listener.accept().unwrap();
+            serve(stream, iter::repeat(&b"\x12"[..]));
+        });
+        let q = crate::query!("create space myspace");
+        let pool = super::get(1, Config::new("127.0.0.1", port, "user", "pass")).unwrap();
+        pool.get().unwrap().query_parse::<()>(&q).unwrap();
+        restarted_rx.recv().unwrap();
+        // the dead connection is dropped, and a new one is handed out
+        let mut db = pool.get().unwrap();
+        db.query_parse::<()>(&q).unwrap();
+        assert!(!db.is_broken());
+        drop(db);
+        drop(pool);
+        server.join().unwrap();
+    }
+
+    #[tokio::test]
+    async fn t_socket_check() {
+        use bb8::ManageConnection;
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let (close_tx, close_rx) = tokio::sync::oneshot::channel::<()>();
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = stream.read(&mut buf).await.unwrap();
+            stream.write_all(b"H\x00\x00\x00").await.unwrap();
+            // only the first check runs the status query
+            let _ = stream.read(&mut buf).await.unwrap();
+            stream.write_all(b"\x12").await.unwrap();
+            close_rx.await.unwrap();
+        });
+        let mgr = super::ConnectionMgrTcp::new(Config::new("127.0.0.1", port, "user", "pass"))
+            .with_socket_check(std::time::Duration::from_secs(3600));
+        let mut db = mgr.connect().await.unwrap();
+        mgr.is_valid(&mut db).await.unwrap();
+        mgr.is_valid(&mut db).await.unwrap();
+        assert_eq!(db.queries_sent(), 1);
+        // the server goes away, which the socket check notices without a query
+        close_tx.send(()).unwrap();
+        server.await.unwrap();
+        assert!(mgr.is_valid(&mut db).await.is_err());
+        assert_eq!(db.queries_sent(), 1);
+        assert!(mgr.has_broken(&mut db));
+    }
+
+    #[cfg(feature = "deadpool")]
+    #[test]
+    fn t_deadpool_construction() {
+        // building the pool is lazy, so no server (or runtime) is needed here
+        let pool = super::get_deadpool(8, Config::new_default("user", "pass")).unwrap();
+        let status = pool.status();
+        assert_eq!(status.max_size, 8);
+        assert_eq!(status.size, 0);
+        let pool = super::get_deadpool_tls(4, Config::new_default("user", "pass"), "cert").unwrap();
+        assert_eq!(pool.status().max_size, 4);
+    }
+
+    #[cfg(all(feature = "deadpool", feature = "async-std"))]
+    #[test]
+    fn t_deadpool_async_std_construction() {
+        let pool = super::get_deadpool_async_std(2, Config::new_default("user", "pass")).unwrap();
+        assert_eq!(pool.status().max_size, 2);
+        // no tokio runtime exists here; a failed checkout must still surface as a backend error
+        let ret = async_std::task::block_on(async {
+            let config = Config::new("127.0.0.1", 1, "user", "pass");
+            let pool = super::get_deadpool_async_std(1, config).unwrap();
+            pool.get().await.map(|_| ())
+        });
+        assert!(matches!(ret, Err(deadpool::managed::PoolError::Backend(_))));
     }
 }
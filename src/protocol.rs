@@ -14,6 +14,13 @@
  * limitations under the License.
 */
 
+//! # Protocol
+//!
+//! This module has the errors that can happen when decoding a server's response (see [`ProtocolError`]) and, in
+//! [`wire`], the constants that make up Skyhash packets.
+
+pub mod wire;
+
 use crate::{
     config::Config,
     error::{ClientResult, ConnectionSetupError, Error},
@@ -23,13 +30,83 @@ use crate::{
 pub(crate) type ProtocolResult<T> = Result<T, ProtocolError>;
 
 /// Errors that can happen when handling protocol level encoding and decoding
+///
+/// Use [`ProtocolError::kind`] to find out what went wrong. Every error also carries the offset of the last byte that
+/// was read before the error was detected, and a few of the bytes around it, to help with bug reports. The offset is
+/// relative to the start of the data read for the current response.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ProtocolError {
+    kind: ProtocolErrorKind,
+    at: usize,
+    near: Vec<u8>,
+}
+
+/// The kind of a [`ProtocolError`]
 #[derive(Debug, PartialEq, Clone)]
-pub enum ProtocolError {
+#[non_exhaustive]
+pub enum ProtocolErrorKind {
     /// The server returned an invalid response for the data item
     InvalidServerResponseForData,
     /// The server possibly returned an unknown data type and we can't decode it. Note that this might happen when you use an older client version with
     /// a newer version of Skytable
     InvalidServerResponseUnknownDataType,
+    /// The server sent a data type code that Skyhash reserves for future use. This means that the server uses a newer
+    /// protocol feature than this client understands, and you should upgrade the client
+    ReservedDataType {
+        /// The reserved type code
+        code: u8,
+    },
+    /// The response is (or declares a size or an element count that would make it) larger than the limit set with
+    /// [`Config::set_max_response_size`]
+    ResponseTooLarge {
+        /// The maximum response size, in bytes
+        limit: usize,
+    },
+}
+
+impl ProtocolError {
+    /// The number of bytes on either side of the error offset that are captured
+    const CONTEXT: usize = 8;
+    pub(crate) const fn new(kind: ProtocolErrorKind) -> Self {
+        Self {
+            kind,
+            at: 0,
+            near: Vec::new(),
+        }
+    }
+    pub(crate) const fn invalid_data() -> Self {
+        Self::new(ProtocolErrorKind::InvalidServerResponseForData)
+    }
+    pub(crate) const fn unknown_data_type() -> Self {
+        Self::new(ProtocolErrorKind::InvalidServerResponseUnknownDataType)
+    }
+    pub(crate) const fn reserved_data_type(code: u8) -> Self {
+        Self::new(ProtocolErrorKind::ReservedDataType { code })
+    }
+    pub(crate) const fn response_too_large(limit: usize) -> Self {
+        Self::new(ProtocolErrorKind::ResponseTooLarge { limit })
+    }
+    /// Returns the kind of this error
+    pub fn kind(&self) -> &ProtocolErrorKind {
+        &self.kind
+    }
+    /// Returns the offset of the last byte read before the error was detected
+    pub fn offset(&self) -> usize {
+        self.at
+    }
+    /// Returns the bytes around the offset (see [`ProtocolError::offset`])
+    pub fn context(&self) -> &[u8] {
+        &self.near
+    }
+    /// Record where in `buf` the error happened, given the decoder's position
+    pub(crate) fn locate(mut self, buf: &[u8], position: usize) -> Self {
+        let position = position.min(buf.len());
+        self.at = position.saturating_sub(1);
+        self.near = buf
+            [self.at.saturating_sub(Self::CONTEXT)..(position + Self::CONTEXT).min(buf.len())]
+            .to_vec();
+        self
+    }
 }
 
 impl Value {
@@ -140,36 +217,86 @@ enum ResponseState {
 }
 
 #[derive(Debug, PartialEq)]
-pub enum DecodeState {
+pub(crate) enum DecodeState {
     ChangeState(RState),
     Completed(Response),
     Error(ProtocolError),
 }
 
 #[derive(Debug, PartialEq)]
-pub struct RState(ResponseState);
+pub(crate) struct RState(ResponseState);
 impl Default for RState {
     fn default() -> Self {
         RState(ResponseState::Initial)
     }
 }
 
+#[derive(Debug, PartialEq, Default)]
+pub(crate) struct MRespState {
+    decoded: usize,
+    pending: Option<RState>,
+}
+
+impl MRespState {
+    /// Returns true if no response is partially decoded, which means that the decoded bytes are no longer needed
+    pub fn at_boundary(&self) -> bool {
+        self.pending.is_none()
+    }
+    /// Returns the number of responses that have been completely decoded
+    pub fn decoded(&self) -> usize {
+        self.decoded
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum PipelineResult {
+    Completed,
+    Pending,
+    Error(ProtocolError),
+}
+
 /*
     Decoder
 */
 
 #[derive(Debug, PartialEq)]
-pub struct Decoder<'a> {
+pub(crate) struct Decoder<'a> {
     b: &'a [u8],
     i: usize,
+    lossy_utf8: bool,
+    max_size: Option<usize>,
 }
 
 impl<'a> Decoder<'a> {
     pub const MIN_READBACK: usize = 1;
     pub fn new(b: &'a [u8], i: usize) -> Self {
-        Self { b, i }
+        Self {
+            b,
+            i,
+            lossy_utf8: false,
+            max_size: None,
+        }
+    }
+    pub fn lossy_utf8(self, lossy_utf8: bool) -> Self {
+        Self { lossy_utf8, ..self }
+    }
+    pub fn max_size(self, max_size: Option<usize>) -> Self {
+        Self { max_size, ..self }
+    }
+    /// Fail if a declared size (or element count, since every element takes at least a byte) is beyond the limit
+    fn check_size(&self, declared: u64) -> ProtocolResult<()> {
+        match self.max_size {
+            Some(limit) if declared > limit as u64 => Err(ProtocolError::response_too_large(limit)),
+            _ => Ok(()),
+        }
     }
-    pub fn validate_response(&mut self, RState(state): RState) -> DecodeState {
+    pub fn validate_response(&mut self, state: RState) -> DecodeState {
+        match self.resume(state) {
+            DecodeState::Error(e) => DecodeState::Error(e.locate(self.b, self.i)),
+            state => state,
+        }
+    }
+    fn resume(&mut self, RState(state): RState) -> DecodeState {
         match state {
             ResponseState::Initial => self.begin(),
             ResponseState::PError => self.resume_error(),
@@ -178,17 +305,42 @@ impl<'a> Decoder<'a> {
             ResponseState::PMultiRow(mr) => self.resume_rows(mr),
         }
     }
+    pub fn validate_pipe(
+        &mut self,
+        expected: usize,
+        state: &mut MRespState,
+        mut on_response: impl FnMut(Response),
+    ) -> PipelineResult {
+        while state.decoded != expected {
+            if self.i == self.b.len() {
+                return PipelineResult::Pending;
+            }
+            match self.validate_response(state.pending.take().unwrap_or_default()) {
+                DecodeState::Completed(resp) => {
+                    state.decoded += 1;
+                    on_response(resp);
+                }
+                DecodeState::ChangeState(new_state) => {
+                    state.pending = Some(new_state);
+                    return PipelineResult::Pending;
+                }
+                DecodeState::Error(e) => return PipelineResult::Error(e),
+            }
+        }
+        PipelineResult::Completed
+    }
     pub fn position(&self) -> usize {
         self.i
     }
     fn begin(&mut self) -> DecodeState {
         match self._cursor_next() {
-            // TODO(@ohsayan): this is reserved!
-            0x0F => return DecodeState::Error(ProtocolError::InvalidServerResponseUnknownDataType),
-            0x10 => self.resume_error(),
-            0x11 => self.resume_row(RowState::new(ValueStateMeta::zero(), vec![], None)),
-            0x12 => return DecodeState::Completed(Response::Empty),
-            0x13 => self.resume_rows(MultiRowState::default()),
+            wire::TYPE_RESERVED => {
+                DecodeState::Error(ProtocolError::reserved_data_type(wire::TYPE_RESERVED))
+            }
+            wire::RESP_ERROR => self.resume_error(),
+            wire::RESP_ROW => self.resume_row(RowState::new(ValueStateMeta::zero(), vec![], None)),
+            wire::RESP_EMPTY => DecodeState::Completed(Response::Empty),
+            wire::RESP_MULTIROW => self.resume_rows(MultiRowState::default()),
             code => match self.start_decode(true, code, vec![], None) {
                 Ok(ValueDecodeStateAny::Decoded(v)) => DecodeState::Completed(Response::Value(v)),
                 Ok(ValueDecodeStateAny::Pending(pv)) => {
@@ -224,6 +376,9 @@ impl<'a> Decoder<'a> {
                 Ok(ValueDecodeStateAny::Decoded(v)) => {
                     row_state.meta.md1 = v.u64();
                     row_state.meta.md1_flag = true;
+                    if let Err(e) = self.check_size(row_state.meta.md1) {
+                        return DecodeState::Error(e);
+                    }
                 }
                 Err(e) => return DecodeState::Error(e),
             }
@@ -271,6 +426,9 @@ impl<'a> Decoder<'a> {
                 Ok(ValueDecodeStateAny::Decoded(v)) => {
                     multirow.md1_target = v.u64();
                     multirow.md_state += 1;
+                    if let Err(e) = self.check_size(multirow.md1_target) {
+                        return DecodeState::Error(e);
+                    }
                 }
                 Err(e) => return DecodeState::Error(e),
             }
@@ -284,6 +442,9 @@ impl<'a> Decoder<'a> {
                 Ok(ValueDecodeStateAny::Decoded(v)) => {
                     multirow.md2_col_cnt = v.u64();
                     multirow.md_state += 1;
+                    if let Err(e) = self.check_size(multirow.md2_col_cnt) {
+                        return DecodeState::Error(e);
+                    }
                 }
                 Err(e) => return DecodeState::Error(e),
             }
@@ -334,7 +495,7 @@ impl<'a> Decoder<'a> {
             if okay {
                 Ok(ValueDecodeStateAny::Pending(value.pack_pending(meta)))
             } else {
-                Err(ProtocolError::InvalidServerResponseForData)
+                Err(ProtocolError::invalid_data())
             }
         }
     }
@@ -346,6 +507,7 @@ impl<'a> Decoder<'a> {
             match self.__resume_decode(meta.md1, ValueStateMeta::zero())? {
                 ValueDecodeStateAny::Decoded(s) => {
                     let s = s.u64();
+                    self.check_size(s)?;
                     meta.md1_flag = true;
                     meta.md1 = s;
                 }
@@ -362,7 +524,7 @@ impl<'a> Decoder<'a> {
         if self._remaining() as u64 >= meta.md1 {
             let buf = &self.b[meta.start..self._cursor() + meta.md1 as usize];
             self._cursor_incr_by(meta.md1 as usize);
-            T::finish(buf).map(ValueDecodeStateAny::Decoded)
+            T::finish(buf, self.lossy_utf8).map(ValueDecodeStateAny::Decoded)
         } else {
             Ok(ValueDecodeStateAny::Pending(ValueState::new(
                 T::empty(),
@@ -413,12 +575,12 @@ trait DecodeDelimited {
 }
 
 trait DecodePsize {
-    fn finish(b: &[u8]) -> ProtocolResult<Value>;
+    fn finish(b: &[u8], lossy_utf8: bool) -> ProtocolResult<Value>;
     fn empty() -> Value;
 }
 
 impl DecodePsize for Vec<u8> {
-    fn finish(b: &[u8]) -> ProtocolResult<Value> {
+    fn finish(b: &[u8], _: bool) -> ProtocolResult<Value> {
         Ok(Value::Binary(b.to_owned()))
     }
     fn empty() -> Value {
@@ -427,11 +589,14 @@ impl DecodePsize for Vec<u8> {
 }
 
 impl DecodePsize for String {
-    fn finish(b: &[u8]) -> ProtocolResult<Value> {
+    fn finish(b: &[u8], lossy_utf8: bool) -> ProtocolResult<Value> {
+        if lossy_utf8 {
+            return Ok(Value::String(String::from_utf8_lossy(b).into_owned()));
+        }
         core::str::from_utf8(b)
             .map(String::from)
             .map(Value::String)
-            .map_err(|_| ProtocolError::InvalidServerResponseForData)
+            .map_err(|_| ProtocolError::invalid_data())
     }
     fn empty() -> Value {
         Value::String(String::new())
@@ -458,21 +623,31 @@ macro_rules! impl_fstr {
         $(impl DecodeDelimited for $ty {
             fn pack_pending(self, meta: ValueStateMeta) -> ValueState { ValueState::new(Value::$variant(self), meta) }
             fn pack_completed(self, _: ValueStateMeta, b: &[u8]) -> ProtocolResult<Value> {
-                core::str::from_utf8(b).map_err(|_| ProtocolError::InvalidServerResponseForData)?.parse().map(Value::$variant).map_err(|_| ProtocolError::InvalidServerResponseForData)
+                core::str::from_utf8(b).map_err(|_| ProtocolError::invalid_data())?.parse().map(Value::$variant).map_err(|_| ProtocolError::invalid_data())
+            }
+        })*
+    };
+}
+
+/// Like [`impl_fstr`], but the server writes signed integers with at most a leading `-`, so the explicit `+` that
+/// [`str::parse`] would otherwise accept is rejected
+macro_rules! impl_sint {
+    ($($ty:ty as $variant:ident),*) => {
+        $(impl DecodeDelimited for $ty {
+            fn pack_pending(self, meta: ValueStateMeta) -> ValueState { ValueState::new(Value::$variant(self), meta) }
+            fn pack_completed(self, _: ValueStateMeta, b: &[u8]) -> ProtocolResult<Value> {
+                if b.first() == Some(&b'+') {
+                    return Err(ProtocolError::invalid_data());
+                }
+                core::str::from_utf8(b).map_err(|_| ProtocolError::invalid_data())?.parse().map(Value::$variant).map_err(|_| ProtocolError::invalid_data())
             }
         })*
     };
 }
 
 impl_uint!(u8 as UInt8, u16 as UInt16, u32 as UInt32, u64 as UInt64);
-impl_fstr!(
-    i8 as SInt8,
-    i16 as SInt16,
-    i32 as SInt32,
-    i64 as SInt64,
-    f32 as Float32,
-    f64 as Float64
-);
+impl_sint!(i8 as SInt8, i16 as SInt16, i32 as SInt32, i64 as SInt64);
+impl_fstr!(f32 as Float32, f64 as Float64);
 
 #[derive(Debug, PartialEq)]
 struct PendingValue {
@@ -504,6 +679,7 @@ impl<'a> Decoder<'a> {
                     ValueDecodeStateAny::Decoded(v) => {
                         current_meta.md1 = v.u64();
                         current_meta.md1_flag = true;
+                        self.check_size(current_meta.md1)?;
                     }
                     ValueDecodeStateAny::Pending(ValueState { v, .. }) => {
                         current_meta.md1 = v.u64();
@@ -542,7 +718,7 @@ impl<'a> Decoder<'a> {
                         )));
                     }
                     match self._cursor_next() {
-                        0x0E => {
+                        wire::TYPE_LIST => {
                             // that's a list
                             stack.push((current_list, current_meta));
                             current_list = vec![];
@@ -580,28 +756,31 @@ impl<'a> Decoder<'a> {
     ) -> ProtocolResult<ValueDecodeState> {
         let md = ValueStateMeta::new(self._cursor(), 0, false);
         let v = match code {
-            0x00 => return Ok(ValueDecodeStateAny::Decoded(Value::Null)),
-            0x01 => return self.parse_bool(stack),
-            0x02 => self.__resume_decode(0u8, md),
-            0x03 => self.__resume_decode(0u16, md),
-            0x04 => self.__resume_decode(0u32, md),
-            0x05 => self.__resume_decode(0u64, md),
-            0x06 => self.__resume_decode(0i8, md),
-            0x07 => self.__resume_decode(0i16, md),
-            0x08 => self.__resume_decode(0i32, md),
-            0x09 => self.__resume_decode(0i64, md),
-            0x0A => self.__resume_decode(0f32, md),
-            0x0B => self.__resume_decode(0f64, md),
-            0x0C => self.__resume_psize::<Vec<u8>>(md),
-            0x0D => self.__resume_psize::<String>(md),
-            0x0E => {
+            wire::TYPE_NULL => return Ok(ValueDecodeStateAny::Decoded(Value::Null)),
+            wire::TYPE_BOOL => return self.parse_bool(stack),
+            wire::TYPE_UINT8 => self.__resume_decode(0u8, md),
+            wire::TYPE_UINT16 => self.__resume_decode(0u16, md),
+            wire::TYPE_UINT32 => self.__resume_decode(0u32, md),
+            wire::TYPE_UINT64 => self.__resume_decode(0u64, md),
+            wire::TYPE_SINT8 => self.__resume_decode(0i8, md),
+            wire::TYPE_SINT16 => self.__resume_decode(0i16, md),
+            wire::TYPE_SINT32 => self.__resume_decode(0i32, md),
+            wire::TYPE_SINT64 => self.__resume_decode(0i64, md),
+            wire::TYPE_FLOAT32 => self.__resume_decode(0f32, md),
+            wire::TYPE_FLOAT64 => self.__resume_decode(0f64, md),
+            wire::TYPE_BINARY => self.__resume_psize::<Vec<u8>>(md),
+            wire::TYPE_STRING => self.__resume_psize::<String>(md),
+            wire::TYPE_LIST => {
                 if !root {
                     unreachable!("recursive structure not captured by root");
                 }
                 stack.push((vec![], ValueStateMeta::zero()));
                 return self.parse_list(stack, last);
             }
-            _ => return Err(ProtocolError::InvalidServerResponseUnknownDataType),
+            wire::TYPE_RESERVED => {
+                return Err(ProtocolError::reserved_data_type(wire::TYPE_RESERVED))
+            }
+            _ => return Err(ProtocolError::unknown_data_type()),
         }?;
         Self::check_pending(v, stack)
     }
@@ -649,9 +828,9 @@ impl<'a> Decoder<'a> {
         }
         let nx = self._cursor_next();
         if nx < 2 {
-            return Ok(ValueDecodeStateAny::Decoded(Value::Bool(nx == 1)));
+            Ok(ValueDecodeStateAny::Decoded(Value::Bool(nx == 1)))
         } else {
-            return Err(ProtocolError::InvalidServerResponseForData);
+            Err(ProtocolError::invalid_data())
         }
     }
     fn check_pending(
@@ -667,11 +846,13 @@ impl<'a> Decoder<'a> {
     }
 }
 
-pub struct ClientHandshake(Box<[u8]>);
+pub(crate) struct ClientHandshake(Box<[u8]>);
 impl ClientHandshake {
     pub(crate) fn new(cfg: &Config) -> Self {
-        let mut v = Vec::with_capacity(6 + cfg.username().len() + cfg.password().len() + 5);
-        v.extend(b"H\x00\x00\x00\x00\x00");
+        let mut v = Vec::with_capacity(
+            wire::CLIENT_HANDSHAKE_HEADER.len() + cfg.username().len() + cfg.password().len() + 5,
+        );
+        v.extend(wire::CLIENT_HANDSHAKE_HEADER);
         pushlen!(v, cfg.username().len());
         pushlen!(v, cfg.password().len());
         v.extend(cfg.username().as_bytes());
@@ -684,15 +865,15 @@ impl ClientHandshake {
 }
 
 #[derive(Debug)]
-pub enum ServerHandshake {
+pub(crate) enum ServerHandshake {
     Okay(u8),
     Error(u8),
 }
 impl ServerHandshake {
-    pub fn parse(v: [u8; 4]) -> ClientResult<Self> {
+    pub fn parse(v: [u8; wire::SERVER_HANDSHAKE_LEN]) -> ClientResult<Self> {
         Ok(match v {
-            [b'H', 0, 0, msg] => Self::Okay(msg),
-            [b'H', 0, 1, msg] => Self::Error(msg),
+            [wire::HANDSHAKE_MAGIC, 0, wire::HANDSHAKE_OKAY, msg] => Self::Okay(msg),
+            [wire::HANDSHAKE_MAGIC, 0, wire::HANDSHAKE_ERROR, msg] => Self::Error(msg),
             _ => {
                 return Err(Error::ConnectionSetupErr(
                     ConnectionSetupError::InvalidServerHandshake,
@@ -717,6 +898,37 @@ fn t_row() {
     );
 }
 
+#[test]
+fn t_aggregate() {
+    use crate::{error::ParseError, response::FromResponse};
+    // an aggregate can come back either as a plain value or as a row with a single column
+    for (raw, expected) in [
+        (&b"\x0542\n"[..], Response::Value(Value::UInt64(42))),
+        (
+            &b"\x111\n\x0542\n"[..],
+            Response::Row(Row::new(vec![Value::UInt64(42)])),
+        ),
+    ]
+    .iter()
+    .cloned()
+    {
+        let mut decoder = Decoder::new(raw, 0);
+        assert_eq!(
+            decoder.validate_response(RState::default()),
+            DecodeState::Completed(expected.clone())
+        );
+        assert_eq!(u64::from_response(expected).unwrap(), 42);
+    }
+    // but not a row with more columns
+    assert!(matches!(
+        u64::from_response(Response::Row(Row::new(vec![
+            Value::UInt64(42),
+            Value::UInt64(1)
+        ]))),
+        Err(Error::ParseError(ParseError::ResponseMismatch))
+    ));
+}
+
 #[test]
 fn t_mrow() {
     let mut decoder = Decoder::new(b"\x133\n5\n\x00\x01\x01\x0D5\nsayan\x0220\n\x0E0\n\x00\x01\x01\x0D5\nelana\x0221\n\x0E0\n\x00\x01\x01\x0D5\nemily\x0222\n\x0E0\n", 0);
@@ -747,3 +959,376 @@ fn t_mrow() {
         ]))
     );
 }
+
+#[test]
+fn t_pipe() {
+    let packet = b"\x12\x115\n\x00\x01\x01\x0D5\nsayan\x0220\n\x0E0\n\x10\x67\x00";
+    let expected = vec![
+        Response::Empty,
+        Response::Row(Row::new(vec![
+            Value::Null,
+            Value::Bool(true),
+            Value::String("sayan".into()),
+            Value::UInt8(20),
+            Value::List(vec![]),
+        ])),
+        Response::Error(103),
+    ];
+    // all at once
+    let mut resps = vec![];
+    let mut decoder = Decoder::new(packet, 0);
+    assert_eq!(
+        decoder.validate_pipe(3, &mut MRespState::default(), |r| resps.push(r)),
+        PipelineResult::Completed
+    );
+    assert_eq!(resps, expected);
+    // byte by byte
+    let mut resps = vec![];
+    let mut state = MRespState::default();
+    let mut cursor = 0;
+    for i in 1..=packet.len() {
+        let mut decoder = Decoder::new(&packet[..i], cursor);
+        match decoder.validate_pipe(3, &mut state, |r| resps.push(r)) {
+            PipelineResult::Pending => cursor = decoder.position(),
+            PipelineResult::Completed => {
+                assert_eq!(i, packet.len());
+                assert_eq!(resps, expected);
+                return;
+            }
+            PipelineResult::Error(e) => panic!("{:?}", e),
+        }
+        // the first response is a single byte, and the second one is a row
+        match i {
+            1 => assert!(state.at_boundary()),
+            2 => assert!(!state.at_boundary()),
+            _ => {}
+        }
+    }
+    panic!("pipeline did not complete");
+}
+
+#[test]
+fn t_float_edge_cases() {
+    fn decode(packet: &[u8]) -> DecodeState {
+        Decoder::new(packet, 0).validate_response(RState::default())
+    }
+    fn decode_f64(repr: &str) -> f64 {
+        match decode(format!("\x0B{repr}\n").as_bytes()) {
+            DecodeState::Completed(Response::Value(Value::Float64(f))) => f,
+            other => panic!("failed to decode {:?}: {:?}", repr, other),
+        }
+    }
+    // scientific notation, in any case and with any sign
+    assert_eq!(decode_f64("1e10"), 1e10);
+    assert_eq!(decode_f64("1E10"), 1e10);
+    assert_eq!(decode_f64("-1.5e-3"), -0.0015);
+    assert_eq!(decode_f64("2.5e+2"), 250.0);
+    // negative zero keeps its sign
+    for repr in ["-0.0", "-0", "-0e0"] {
+        let f = decode_f64(repr);
+        assert!(f == 0.0 && f.is_sign_negative(), "{}", repr);
+    }
+    // subnormals, extremes and non-finite values round-trip exactly in every notation
+    for f in [
+        5e-324,
+        -5e-324,
+        1.0e-310,
+        f64::MIN_POSITIVE,
+        f64::EPSILON,
+        f64::MAX,
+        f64::MIN,
+        0.1 + 0.2,
+        f64::INFINITY,
+        f64::NEG_INFINITY,
+    ] {
+        for repr in [format!("{f}"), format!("{f:?}"), format!("{f:e}")] {
+            assert_eq!(decode_f64(&repr).to_bits(), f.to_bits(), "{}", repr);
+        }
+    }
+    assert!(decode_f64("NaN").is_nan());
+    // f32 follows the same rules
+    for f in [1e10f32, -0.0, 1e-45, f32::MIN_POSITIVE, f32::MAX] {
+        match decode(format!("\x0A{f:e}\n").as_bytes()) {
+            DecodeState::Completed(Response::Value(Value::Float32(v))) => {
+                assert_eq!(v.to_bits(), f.to_bits())
+            }
+            other => panic!("failed to decode {:e}: {:?}", f, other),
+        }
+    }
+    // scientific notation split across reads, resuming byte by byte
+    let packet = b"\x0B-1.5e-3\n";
+    let (mut state, mut cursor) = (RState::default(), 0);
+    for i in 1..=packet.len() {
+        let mut decoder = Decoder::new(&packet[..i], cursor);
+        match decoder.validate_response(core::mem::take(&mut state)) {
+            DecodeState::ChangeState(new_state) => {
+                assert_ne!(i, packet.len());
+                state = new_state;
+                cursor = decoder.position();
+            }
+            DecodeState::Completed(resp) => {
+                assert_eq!(i, packet.len());
+                assert_eq!(resp, Response::Value(Value::Float64(-0.0015)));
+            }
+            DecodeState::Error(e) => panic!("{:?}", e),
+        }
+    }
+    // malformed floats are rejected
+    for repr in ["1e", "1.0.0", "e10", "--1", "1e10x", ""] {
+        assert!(
+            matches!(
+                decode(format!("\x0B{repr}\n").as_bytes()),
+                DecodeState::Error(e) if *e.kind() == ProtocolErrorKind::InvalidServerResponseForData
+            ),
+            "{}",
+            repr
+        );
+    }
+}
+
+#[test]
+fn t_sint_boundaries() {
+    fn decode(packet: &[u8]) -> DecodeState {
+        Decoder::new(packet, 0).validate_response(RState::default())
+    }
+    let cases = [
+        (format!("\x09{}\n", i64::MIN), Value::SInt64(i64::MIN)),
+        (format!("\x09{}\n", i64::MAX), Value::SInt64(i64::MAX)),
+        ("\x09-0\n".to_owned(), Value::SInt64(0)),
+        (format!("\x06{}\n", i8::MIN), Value::SInt8(i8::MIN)),
+        (format!("\x08{}\n", i32::MIN), Value::SInt32(i32::MIN)),
+    ];
+    for (packet, value) in cases.iter() {
+        assert_eq!(
+            decode(packet.as_bytes()),
+            DecodeState::Completed(Response::Value(value.clone()))
+        );
+        // and the same, one byte at a time
+        let mut state = RState::default();
+        let mut cursor = 0;
+        let mut resp = None;
+        for end in 1..=packet.len() {
+            let mut decoder = Decoder::new(&packet.as_bytes()[..end], cursor);
+            match decoder.validate_response(core::mem::take(&mut state)) {
+                DecodeState::ChangeState(new_state) => {
+                    state = new_state;
+                    cursor = decoder.position();
+                }
+                DecodeState::Completed(r) => resp = Some(r),
+                DecodeState::Error(e) => panic!("{:?}", e),
+            }
+        }
+        assert_eq!(resp, Some(Response::Value(value.clone())));
+    }
+    // one past the boundaries, an explicit plus sign, whitespace and stray signs are all rejected
+    let malformed = [
+        "-9223372036854775809".to_owned(),
+        "9223372036854775808".to_owned(),
+        "+1".to_owned(),
+        " 1".to_owned(),
+        "1 ".to_owned(),
+        "-".to_owned(),
+        "--1".to_owned(),
+        "".to_owned(),
+    ];
+    for repr in malformed.iter() {
+        assert!(
+            matches!(
+                decode(format!("\x09{}\n", repr).as_bytes()),
+                DecodeState::Error(e) if *e.kind() == ProtocolErrorKind::InvalidServerResponseForData
+            ),
+            "{:?}",
+            repr
+        );
+    }
+    assert!(matches!(
+        decode(b"\x06128\n"),
+        DecodeState::Error(e) if *e.kind() == ProtocolErrorKind::InvalidServerResponseForData
+    ));
+}
+
+#[test]
+fn t_lossy_utf8() {
+    let packet = b"\x0D5\nsa\xFFan";
+    assert_eq!(
+        Decoder::new(packet, 0).validate_response(RState::default()),
+        DecodeState::Error(ProtocolError::invalid_data().locate(packet, 8))
+    );
+    assert_eq!(
+        Decoder::new(packet, 0)
+            .lossy_utf8(true)
+            .validate_response(RState::default()),
+        DecodeState::Completed(Response::Value(Value::String("sa\u{FFFD}an".into())))
+    );
+}
+
+#[test]
+fn t_error_offset() {
+    // a row whose third value has an unknown type code
+    let packet = b"\x113\n\x0220\n\x0D5\nsayan\x1F\x00\x00";
+    let e = match Decoder::new(packet, 0).validate_response(RState::default()) {
+        DecodeState::Error(e) => e,
+        other => panic!("expected an error, got {:?}", other),
+    };
+    assert_eq!(e.offset(), 15);
+    assert_eq!(packet[e.offset()], 0x1F);
+    assert_eq!(e.context(), &packet[7..]);
+    assert_eq!(
+        e.to_string(),
+        "new or unknown data type received from server at offset 15 (near: 0d 35 0a 73 61 79 61 6e 1f 00 00)"
+    );
+    // a bad digit in an integer is only detected once the whole integer is read
+    let packet = b"\x0212x\n";
+    match Decoder::new(packet, 0).validate_response(RState::default()) {
+        DecodeState::Error(e) if *e.kind() == ProtocolErrorKind::InvalidServerResponseForData => {
+            assert_eq!(e.offset(), 4);
+            assert_eq!(e.context(), packet)
+        }
+        other => panic!("expected an error, got {:?}", other),
+    }
+}
+
+#[test]
+fn t_max_size() {
+    // a string that claims to be ~100GB
+    let packet = b"\x0D99999999999\n";
+    assert!(matches!(
+        Decoder::new(packet, 0).validate_response(RState::default()),
+        DecodeState::ChangeState(_)
+    ));
+    let e = match Decoder::new(packet, 0)
+        .max_size(Some(1024))
+        .validate_response(RState::default())
+    {
+        DecodeState::Error(e) => e,
+        other => panic!("expected an error, got {:?}", other),
+    };
+    assert_eq!(
+        *e.kind(),
+        ProtocolErrorKind::ResponseTooLarge { limit: 1024 }
+    );
+    assert_eq!(e.offset(), 12);
+    assert!(e
+        .to_string()
+        .starts_with("response exceeds the maximum size of 1024 bytes at offset 12"));
+    // lists, rows and multirows with absurd element counts
+    for packet in [
+        &b"\x0E99999999999\n"[..],
+        b"\x1199999999999\n",
+        b"\x1399999999999\n",
+        b"\x132\n99999999999\n",
+    ] {
+        match Decoder::new(packet, 0)
+            .max_size(Some(1024))
+            .validate_response(RState::default())
+        {
+            DecodeState::Error(e)
+                if *e.kind() == ProtocolErrorKind::ResponseTooLarge { limit: 1024 } => {}
+            other => panic!("expected the limit to be hit, got {:?}", other),
+        }
+    }
+    // anything within the limit is fine
+    match Decoder::new(b"\x0D5\nsayan", 0)
+        .max_size(Some(5))
+        .validate_response(RState::default())
+    {
+        DecodeState::Completed(Response::Value(Value::String(s))) => assert_eq!(s, "sayan"),
+        other => panic!("expected a string, got {:?}", other),
+    }
+}
+
+#[test]
+fn t_reserved_data_type() {
+    let e = match Decoder::new(b"\x0F", 0).validate_response(RState::default()) {
+        DecodeState::Error(e) => e,
+        other => panic!("expected an error, got {:?}", other),
+    };
+    assert_eq!(
+        *e.kind(),
+        ProtocolErrorKind::ReservedDataType { code: 0x0F }
+    );
+    assert_eq!(e.offset(), 0);
+    assert_eq!(e.context(), [0x0F]);
+    assert_eq!(
+        e.to_string(),
+        "reserved data type 0x0f received from server at offset 0 (near: 0f); the server uses a newer protocol \
+         feature, so upgrade the client"
+    );
+    // also within a row
+    let packet = b"\x112\n\x0220\n\x0F";
+    match Decoder::new(packet, 0).validate_response(RState::default()) {
+        DecodeState::Error(e)
+            if *e.kind() == ProtocolErrorKind::ReservedDataType { code: 0x0F } =>
+        {
+            assert_eq!(e.offset(), 7)
+        }
+        other => panic!("expected a reserved data type error, got {:?}", other),
+    }
+}
+
+#[test]
+fn t_client_handshake() {
+    let cfg = Config::new_default("root", "password12345678");
+    assert_eq!(
+        ClientHandshake::new(&cfg).inner(),
+        b"H\x00\x00\x00\x00\x004\n16\nrootpassword12345678"
+    );
+}
+
+#[test]
+fn t_wire_constants() {
+    use wire::*;
+    // the handshake, built from the constants
+    let cfg = Config::new_default("root", "password12345678");
+    let mut expected = CLIENT_HANDSHAKE_HEADER.to_vec();
+    expected.extend([b'4', LF, b'1', b'6', LF]);
+    expected.extend(b"rootpassword12345678");
+    assert_eq!(ClientHandshake::new(&cfg).inner(), &expected[..]);
+    assert!(matches!(
+        ServerHandshake::parse([HANDSHAKE_MAGIC, 0, HANDSHAKE_OKAY, 0]),
+        Ok(ServerHandshake::Okay(0))
+    ));
+    assert!(matches!(
+        ServerHandshake::parse([HANDSHAKE_MAGIC, 0, HANDSHAKE_ERROR, 5]),
+        Ok(ServerHandshake::Error(5))
+    ));
+    // the packets
+    let q = crate::query!("select * from myspace.mymodel where id = ?", 1u64);
+    let packet = q.debug_encode_packet();
+    assert_eq!(packet[0], QUERY_PACKET);
+    assert!(packet.ends_with(&[PARAM_UINT, b'1', LF]));
+    let mut pipeline = crate::query::Pipeline::new();
+    pipeline.add_query(&q);
+    let mut packet = vec![];
+    pipeline.write_packet(&mut packet).unwrap();
+    assert_eq!(packet[0], PIPELINE_PACKET);
+    // and the responses
+    let packet = [RESP_ROW, b'2', LF, TYPE_NULL, TYPE_BOOL, 1];
+    assert_eq!(
+        Decoder::new(&packet, 0).validate_response(RState::default()),
+        DecodeState::Completed(Response::Row(Row::new(vec![
+            Value::Null,
+            Value::Bool(true)
+        ])))
+    );
+}
+
+#[test]
+fn t_error() {
+    // an error response is the code alone, as a little-endian u16
+    let mut decoder = Decoder::new(b"\x10\x67\x00", 0);
+    assert_eq!(
+        decoder.validate_response(RState::default()),
+        DecodeState::Completed(Response::Error(103))
+    );
+    assert_eq!(decoder.position(), 3);
+    // and it can arrive in pieces
+    let state = match Decoder::new(b"\x10\x67", 0).validate_response(RState::default()) {
+        DecodeState::ChangeState(state) => state,
+        other => panic!("expected a pending state, got {:?}", other),
+    };
+    assert_eq!(
+        Decoder::new(b"\x10\x67\x00", 1).validate_response(state),
+        DecodeState::Completed(Response::Error(103))
+    );
+}
@@ -0,0 +1,158 @@
+/*
+ * Copyright 2023, Sayan Nandan <nandansayan@outlook.com>
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+
+//! # Skyhash wire constants
+//!
+//! The bytes that frame Skyhash 2 packets, for tooling (like proxies) that has to speak the same protocol as this
+//! client. The encoder and the decoder both use these constants.
+//!
+//! Nothing in Skyhash is big-endian: every size or count is sent as ASCII decimal digits followed by an [`LF`] (for
+//! example, `5\n`), and the only fixed-width integer, the code of an error response, is a little-endian `u16`.
+//!
+//! ## Handshake
+//!
+//! The client starts with [`CLIENT_HANDSHAKE_HEADER`], then the lengths of the username and password (each followed by
+//! an [`LF`]) and then the username and password themselves. The server replies with [`SERVER_HANDSHAKE_LEN`] bytes:
+//! [`HANDSHAKE_MAGIC`], `0`, [`HANDSHAKE_OKAY`] or [`HANDSHAKE_ERROR`], and a status or error code.
+//!
+//! ## Queries
+//!
+//! A query is sent as [`QUERY_PACKET`], the size of the rest of the packet, the length of the query string, the query
+//! string and then the parameters. A pipeline is sent as [`PIPELINE_PACKET`] and the size of the rest of the packet,
+//! followed by every query as its query string length, parameters length, query string and parameters. Each parameter
+//! starts with one of the `PARAM_*` codes.
+//!
+//! ## Responses
+//!
+//! Every response starts with a `RESP_*` code, or, if it's a single value, with the `TYPE_*` code of the value.
+
+/// The line feed that ends every size or count
+pub const LF: u8 = b'\n';
+
+/*
+    handshake
+*/
+
+/// The first byte of the client's handshake and of the server's reply
+pub const HANDSHAKE_MAGIC: u8 = b'H';
+/// The protocol version, for Skyhash 2
+pub const HANDSHAKE_VERSION: u8 = 0;
+/// The protocol, for Skyhash 2
+pub const HANDSHAKE_PROTOCOL: u8 = 0;
+/// The data exchange mode, for the simple (request/response) mode
+pub const HANDSHAKE_EXCHANGE_MODE: u8 = 0;
+/// The query mode, for the simple query mode
+pub const HANDSHAKE_QUERY_MODE: u8 = 0;
+/// The `pwd` (username and password) auth plugin, which is the only one that Skyhash 2 defines
+pub const HANDSHAKE_AUTH_PWD: u8 = 0;
+/// The fixed part of the client's handshake, before the username and password
+pub const CLIENT_HANDSHAKE_HEADER: [u8; 6] = [
+    HANDSHAKE_MAGIC,
+    HANDSHAKE_VERSION,
+    HANDSHAKE_PROTOCOL,
+    HANDSHAKE_EXCHANGE_MODE,
+    HANDSHAKE_QUERY_MODE,
+    HANDSHAKE_AUTH_PWD,
+];
+/// The length of the server's reply to the handshake
+pub const SERVER_HANDSHAKE_LEN: usize = 4;
+/// The server accepted the handshake
+pub const HANDSHAKE_OKAY: u8 = 0;
+/// The server rejected the handshake, and the last byte of its reply is the error code
+pub const HANDSHAKE_ERROR: u8 = 1;
+
+/*
+    packets
+*/
+
+/// The first byte of a packet with a single query
+pub const QUERY_PACKET: u8 = b'S';
+/// The first byte of a pipeline packet
+pub const PIPELINE_PACKET: u8 = b'P';
+
+/*
+    parameters
+*/
+
+/// A `null` parameter, with no payload
+pub const PARAM_NULL: u8 = 0;
+/// A boolean parameter, followed by `0` or `1`
+pub const PARAM_BOOL: u8 = 1;
+/// An unsigned integer parameter, followed by its digits and an [`LF`]
+pub const PARAM_UINT: u8 = 2;
+/// A signed integer parameter, followed by its digits and an [`LF`]
+pub const PARAM_SINT: u8 = 3;
+/// A floating point parameter, followed by its decimal representation and an [`LF`]
+pub const PARAM_FLOAT: u8 = 4;
+/// A binary parameter, followed by its length, an [`LF`] and the bytes
+pub const PARAM_BINARY: u8 = 5;
+/// A string parameter, followed by its length, an [`LF`] and the UTF-8 bytes
+pub const PARAM_STRING: u8 = 6;
+/// A list parameter, followed by its elements (encoded like parameters) and a [`LIST_END`]
+pub const PARAM_LIST: u8 = 7;
+/// The end of a list parameter
+pub const LIST_END: u8 = b']';
+
+/*
+    responses
+*/
+
+/// An error response, followed by the error code as a little-endian `u16`
+pub const RESP_ERROR: u8 = 0x10;
+/// A row, followed by the number of columns, an [`LF`] and the values
+pub const RESP_ROW: u8 = 0x11;
+/// An empty response (the query succeeded, and there's nothing to return)
+pub const RESP_EMPTY: u8 = 0x12;
+/// Multiple rows, followed by the number of rows and of columns (each with an [`LF`]) and the values of every row
+pub const RESP_MULTIROW: u8 = 0x13;
+
+/*
+    values
+*/
+
+/// A `null` value
+pub const TYPE_NULL: u8 = 0x00;
+/// A boolean value, followed by `0` or `1`
+pub const TYPE_BOOL: u8 = 0x01;
+/// An 8-bit unsigned integer; like every number, followed by its digits and an [`LF`]
+pub const TYPE_UINT8: u8 = 0x02;
+/// A 16-bit unsigned integer
+pub const TYPE_UINT16: u8 = 0x03;
+/// A 32-bit unsigned integer
+pub const TYPE_UINT32: u8 = 0x04;
+/// A 64-bit unsigned integer
+pub const TYPE_UINT64: u8 = 0x05;
+/// An 8-bit signed integer
+pub const TYPE_SINT8: u8 = 0x06;
+/// A 16-bit signed integer
+pub const TYPE_SINT16: u8 = 0x07;
+/// A 32-bit signed integer
+pub const TYPE_SINT32: u8 = 0x08;
+/// A 64-bit signed integer
+pub const TYPE_SINT64: u8 = 0x09;
+/// A 32-bit float
+pub const TYPE_FLOAT32: u8 = 0x0A;
+/// A 64-bit float
+pub const TYPE_FLOAT64: u8 = 0x0B;
+/// A binary value, followed by its length, an [`LF`] and the bytes
+pub const TYPE_BINARY: u8 = 0x0C;
+/// A string value, followed by its length, an [`LF`] and the UTF-8 bytes
+pub const TYPE_STRING: u8 = 0x0D;
+/// A list, followed by the number of elements, an [`LF`] and the elements
+pub const TYPE_LIST: u8 = 0x0E;
+/// Reserved for a future data type; this client rejects it
+/// (see [`ProtocolErrorKind::ReservedDataType`](crate::error::ProtocolErrorKind::ReservedDataType))
+pub const TYPE_RESERVED: u8 = 0x0F;
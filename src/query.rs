@@ -30,11 +30,21 @@
 //! ```
 //!
 
-use std::{
-    io::{self, Write},
-    num::{
-        NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize, NonZeroU16, NonZeroU32,
-        NonZeroU64, NonZeroU8, NonZeroUsize,
+use {
+    crate::{
+        error::{ClientResult, Error, ParseError, ProtocolError},
+        protocol::wire,
+        response::{Entity, Value},
+        schema::ColumnType,
+    },
+    std::{
+        io::{self, Write},
+        iter::FromIterator,
+        num::{
+            NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize, NonZeroU16, NonZeroU32,
+            NonZeroU64, NonZeroU8, NonZeroUsize,
+        },
+        time::{Duration, SystemTime, UNIX_EPOCH},
     },
 };
 
@@ -42,10 +52,20 @@ use std::{
     query impl
 */
 
+/// The header of a query serialized with [`Query::to_bytes`], followed by the version of the format
+const QUERY_BYTES_MAGIC: &[u8] = b"SKYQ";
+const QUERY_BYTES_VERSION: u8 = 1;
+
 #[derive(Debug, PartialEq, Clone)]
 /// A [`Query`] represents a Skyhash query. This is the "standard query" that you will normally use for almost all operations.
 ///
 /// Specification: `QTDEX-A/BQL-S1`
+///
+/// ## Cloning
+///
+/// A [`Query`] owns a single buffer holding the query string and the encoded parameters, so cloning it costs one allocation
+/// and a copy of that buffer. If you build a "template" query once and then append different parameters to clones of it,
+/// use [`Query::reserve`] on the template and [`Query::clone_with_params`] to avoid further reallocations.
 pub struct Query {
     buf: Vec<u8>,
     param_cnt: usize,
@@ -64,6 +84,21 @@ impl<'a> From<&'a str> for Query {
     }
 }
 
+/// Count the top-level `?` in `query`, skipping single-quoted literals. An escaped quote (`''`) closes and immediately
+/// reopens the literal, so toggling on every quote is enough to track it
+fn count_placeholders(query: &str) -> usize {
+    let mut in_literal = false;
+    let mut count = 0;
+    for b in query.bytes() {
+        match b {
+            b'\'' => in_literal = !in_literal,
+            b'?' if !in_literal => count += 1,
+            _ => {}
+        }
+    }
+    count
+}
+
 impl Query {
     /// Create a new query from a [`str`]
     pub fn new(query: &str) -> Self {
@@ -90,16 +125,166 @@ impl Query {
         self.param_cnt += param.append_param(&mut self.buf);
         self
     }
+    /// Add a new parameter to the query after checking that it can be stored in a column of the given type, so that a
+    /// value the server would reject (like `300` for a `uint8` column) fails before the query is sent
+    ///
+    /// The parameter must be a single value (and not, for example, a struct with several fields). Its type must be one
+    /// that the column accepts (see [`Query::check_against`]) and integers must be within the column's range, as must
+    /// the elements of a list. A `null` is always accepted, since [`ColumnType`] doesn't say whether the column is
+    /// nullable. If the check fails, this returns [`Error::InvalidQuery`] and the query is left unchanged
+    ///
+    /// ## Example
+    /// ```
+    /// use skytable::{schema::ColumnType, Query};
+    ///
+    /// let mut q = Query::new("insert into myspace.users(?, ?)");
+    /// q.push_param_checked("sayan", &ColumnType::String).unwrap();
+    /// let e = q.push_param_checked(300u16, &ColumnType::UInt8).unwrap_err();
+    /// assert_eq!(e.to_string(), "invalid query: 300 is out of range for a uint8 column");
+    /// assert_eq!(q.param_cnt(), 1);
+    /// ```
+    pub fn push_param_checked(
+        &mut self,
+        param: impl SQParam,
+        column: &ColumnType,
+    ) -> ClientResult<&mut Self> {
+        let start = self.buf.len();
+        let cnt = param.append_param(&mut self.buf);
+        let checked = decode_encoded_params(&self.buf[start..], cnt)
+            .map_err(|e| e.to_string())
+            .and_then(|values| match values.as_slice() {
+                [Value::Null] => Ok(()),
+                [value] => column.check_value(value),
+                values => Err(format!("expected a single parameter, got {}", values.len())),
+            });
+        match checked {
+            Ok(()) => {
+                self.param_cnt += cnt;
+                Ok(self)
+            }
+            Err(e) => {
+                self.buf.truncate(start);
+                Err(Error::InvalidQuery(e))
+            }
+        }
+    }
     /// Get the number of parameters
     pub fn param_cnt(&self) -> usize {
         self.param_cnt
     }
+    /// Returns the number of `?` placeholders in the query string
+    ///
+    /// Question marks inside single-quoted string literals (such as `'why?'`) are not counted.
+    pub fn placeholder_count(&self) -> usize {
+        count_placeholders(self.query_str())
+    }
+    /// Remove all parameters, retaining the query string
+    fn clear_params(&mut self) {
+        self.buf.truncate(self.q_window);
+        self.param_cnt = 0;
+    }
+    /// Reserve space for at least `additional` more bytes of encoded parameters
+    pub fn reserve(&mut self, additional: usize) {
+        self.buf.reserve(additional)
+    }
+    /// Clone this query and append the given parameter(s) to the clone. The clone retains this query's capacity, so if
+    /// enough space was reserved (see [`Query::reserve`]) this needs just one allocation
+    ///
+    /// ## Example
+    /// ```
+    /// use skytable::Query;
+    ///
+    /// let mut template = Query::new("select * from myspace.mymodel where username = ?");
+    /// template.reserve(64);
+    /// let q1 = template.clone_with_params("sayan");
+    /// let q2 = template.clone_with_params("elana");
+    /// assert_eq!(q1.param_cnt(), 1);
+    /// assert_eq!(q2.param_cnt(), 1);
+    /// ```
+    pub fn clone_with_params(&self, extra: impl SQParam) -> Self {
+        let mut buf = Vec::with_capacity(self.buf.capacity());
+        buf.extend_from_slice(&self.buf);
+        let mut q = Self {
+            buf,
+            param_cnt: self.param_cnt,
+            q_window: self.q_window,
+        };
+        q.push_param(extra);
+        q
+    }
+    /// Returns true if the query string is empty
+    pub fn is_empty(&self) -> bool {
+        self.q_window == 0
+    }
+    /// Returns the size of the encoded parameters, in bytes
+    pub fn params_len(&self) -> usize {
+        self.buf.len() - self.q_window
+    }
+    /// Returns the size of the full packet that will be sent to the server, in bytes (see [`Query::debug_encode_packet`])
+    pub fn encoded_len(&self) -> usize {
+        let query_window_len = itoa::Buffer::new().format(self.q_window).len();
+        let total_packet_size = query_window_len + 1 + self.buf.len();
+        let total_packet_size_len = itoa::Buffer::new().format(total_packet_size).len();
+        1 + total_packet_size_len + 1 + total_packet_size
+    }
+    /// Validate the given space and model names (see [`ident`]) and return the entity in the `space.model` form, which
+    /// can then be safely used in a query string
+    ///
+    /// ## Example
+    /// ```
+    /// use skytable::Query;
+    ///
+    /// let entity = Query::with_entity("myspace", "mymodel").unwrap();
+    /// let q = Query::new(&format!("select * from {entity} where username = ?"));
+    /// assert_eq!(q.query_str(), "select * from myspace.mymodel where username = ?");
+    /// assert!(Query::with_entity("myspace", "mymodel; drop space myspace").is_err());
+    /// ```
+    pub fn with_entity(space: &str, model: &str) -> ClientResult<String> {
+        Entity::new(space, model).map(|entity| entity.to_string())
+    }
+    /// Decode the encoded parameters back into [`Value`]s, in the order in which they were added. This lets you check
+    /// what a query (for example, one built with [`query!`](crate::query!)) will send, without a server
+    ///
+    /// The encoding doesn't record the width of numbers, so integers are decoded as [`Value::UInt64`] or
+    /// [`Value::SInt64`] and floats as [`Value::Float64`]. This fails with a [`ProtocolError`] if a custom [`SQParam`]
+    /// implementation encoded an invalid parameter
+    ///
+    /// ## Example
+    /// ```
+    /// use skytable::{query, response::Value};
+    ///
+    /// let q = query!("insert into myspace.mymodel(?, ?, ?)", "sayan", 20u8, None::<String>);
+    /// assert_eq!(
+    ///     q.decode_params().unwrap(),
+    ///     vec![Value::String("sayan".into()), Value::UInt64(20), Value::Null]
+    /// );
+    /// ```
+    pub fn decode_params(&self) -> ClientResult<Vec<Value>> {
+        decode_encoded_params(&self.buf[self.q_window..], self.param_cnt)
+    }
+    /// Build a `use` query that switches to the given space, or `space.model`, after validating the names (see [`ident`])
+    pub(crate) fn use_entity(entity: &str) -> ClientResult<Self> {
+        let entity: Entity = entity.parse()?;
+        Ok(Self::new_string(format!("use {entity}")))
+    }
+    /// Build a `use` query that switches to the given space, which must be a valid identifier (and not `space.model`)
+    pub(crate) fn use_space(space: &str) -> ClientResult<Self> {
+        ident(space)?;
+        Ok(Self::new_string(format!("use {space}")))
+    }
     #[inline(always)]
     pub(crate) fn write_packet(&self, buf: &mut impl Write) -> io::Result<()> {
         /*
             [[total packet size][query window]][[dataframe][qframe]]
             ^meta1            ^meta2           ^payload
         */
+        self.write_header(buf)?;
+        // segment 3: payload
+        buf.write_all(&self.buf)
+    }
+    /// Write everything that comes before the payload (see [`Self::payload`]) in the packet
+    #[inline(always)]
+    pub(crate) fn write_header(&self, buf: &mut impl Write) -> io::Result<()> {
         // compute the total packet size
         // q window
         let mut query_window_buffer = itoa::Buffer::new();
@@ -109,15 +294,119 @@ impl Query {
         let mut total_packet_size_buffer = itoa::Buffer::new();
         let total_packet_size_str = total_packet_size_buffer.format(total_packet_size);
         // segment 1: meta
-        buf.write_all(b"S")?;
+        buf.write_all(&[wire::QUERY_PACKET])?;
         buf.write_all(total_packet_size_str.as_bytes())?;
-        buf.write_all(b"\n")?;
+        buf.write_all(&[wire::LF])?;
         // segment 2: variable meta
         buf.write_all(query_window_str.as_bytes())?;
-        buf.write_all(b"\n")?;
-        // segment 3: payload
-        buf.write_all(&self.buf)?;
-        Ok(())
+        buf.write_all(&[wire::LF])
+    }
+    /// Returns the encoded query string and parameters, which make up the rest of the packet after the header
+    #[inline(always)]
+    pub(crate) fn payload(&self) -> &[u8] {
+        &self.buf
+    }
+    /// Log the packet for this query when it isn't encoded into a buffer (see [`Self::log_packet`]). The packet is only
+    /// encoded if it will actually be logged
+    #[inline(always)]
+    pub(crate) fn log_unbuffered(&self, redact: bool) {
+        #[cfg(feature = "logging")]
+        {
+            if log::log_enabled!(log::Level::Trace) {
+                self.log_packet(&self.debug_encode_packet(), redact)
+            }
+        }
+        #[cfg(not(feature = "logging"))]
+        {
+            let _ = redact;
+        }
+    }
+    /// Log the encoded `packet` for this query at the `trace` level (a no-op unless the `logging` feature is enabled). If
+    /// `redact` is set, the parameters are replaced by their size
+    #[inline(always)]
+    pub(crate) fn log_packet(&self, packet: &[u8], redact: bool) {
+        log_query_packet(
+            packet,
+            self.param_cnt,
+            self.buf.len() - self.q_window,
+            redact,
+        )
+    }
+    /// Serialize the query (the query string and the encoded parameters) into a portable, versioned form that can be
+    /// stored (for example, in a cache) and loaded again with [`Query::from_bytes`]
+    ///
+    /// ## Example
+    /// ```
+    /// use skytable::{query, Query};
+    ///
+    /// let q = query!("select * from myspace.users where username = ?", "sayan");
+    /// let cached = q.to_bytes();
+    /// assert_eq!(Query::from_bytes(&cached).unwrap(), q);
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut q_window = itoa::Buffer::new();
+        let mut param_cnt = itoa::Buffer::new();
+        let q_window = q_window.format(self.q_window).as_bytes();
+        let param_cnt = param_cnt.format(self.param_cnt).as_bytes();
+        let mut b = Vec::with_capacity(
+            QUERY_BYTES_MAGIC.len() + 1 + q_window.len() + param_cnt.len() + 2 + self.buf.len(),
+        );
+        b.extend(QUERY_BYTES_MAGIC);
+        b.push(QUERY_BYTES_VERSION);
+        b.extend(q_window);
+        b.push(wire::LF);
+        b.extend(param_cnt);
+        b.push(wire::LF);
+        b.extend(&self.buf);
+        b
+    }
+    /// Load a query serialized with [`Query::to_bytes`]
+    ///
+    /// This fails with a [`ParseError::Other`] if the bytes weren't produced by [`Query::to_bytes`] (or by an
+    /// incompatible version of this library), were truncated, or hold an invalid query string or parameters
+    pub fn from_bytes(bytes: &[u8]) -> ClientResult<Self> {
+        fn invalid(reason: &str) -> Error {
+            Error::ParseError(ParseError::Other(format!(
+                "invalid serialized query: {reason}"
+            )))
+        }
+        fn read_len(b: &[u8], i: &mut usize) -> Option<usize> {
+            let len = b.get(*i..)?.iter().position(|b| *b == wire::LF)?;
+            let digits = &b[*i..*i + len];
+            if digits.is_empty() || !digits.iter().all(u8::is_ascii_digit) {
+                return None;
+            }
+            *i += len + 1;
+            core::str::from_utf8(digits).ok()?.parse().ok()
+        }
+        let b = bytes
+            .strip_prefix(QUERY_BYTES_MAGIC)
+            .ok_or_else(|| invalid("missing header"))?;
+        let (&version, b) = b.split_first().ok_or_else(|| invalid("missing version"))?;
+        if version != QUERY_BYTES_VERSION {
+            return Err(invalid(&format!("unsupported version {version}")));
+        }
+        let mut i = 0;
+        let (q_window, param_cnt) = match (read_len(b, &mut i), read_len(b, &mut i)) {
+            (Some(q_window), Some(param_cnt)) => (q_window, param_cnt),
+            _ => return Err(invalid("bad lengths")),
+        };
+        let buf = &b[i..];
+        match buf.get(..q_window).map(core::str::from_utf8) {
+            Some(Ok(_)) => {}
+            Some(Err(_)) => return Err(invalid("query string is not UTF-8")),
+            None => return Err(invalid("truncated query string")),
+        }
+        let q = Self {
+            buf: buf.to_vec(),
+            param_cnt,
+            q_window,
+        };
+        match q.decode_params() {
+            Ok(params) if params.len() == param_cnt => Ok(q),
+            Ok(_) => Err(invalid("wrong parameter count")),
+            Err(_) => Err(invalid("bad parameters")),
+        }
     }
     #[inline(always)]
     /// Encodes the packet using Skyhash and returns a raw packet for debugging purposes
@@ -128,6 +417,535 @@ impl Query {
     }
 }
 
+/// Decode encoded parameters (see [`Query::decode_params`]), reserving space for `expected` of them
+fn decode_encoded_params(b: &[u8], expected: usize) -> ClientResult<Vec<Value>> {
+    fn line<'a>(b: &'a [u8], i: &mut usize) -> Option<&'a str> {
+        let len = b.get(*i..)?.iter().position(|b| *b == wire::LF)?;
+        let line = core::str::from_utf8(&b[*i..*i + len]).ok()?;
+        *i += len + 1;
+        Some(line)
+    }
+    fn next(b: &[u8], i: &mut usize) -> Option<Value> {
+        let code = *b.get(*i)?;
+        *i += 1;
+        Some(match code {
+            wire::PARAM_NULL => Value::Null,
+            wire::PARAM_BOOL => {
+                let v = *b.get(*i)?;
+                *i += 1;
+                match v {
+                    0 | 1 => Value::Bool(v == 1),
+                    _ => return None,
+                }
+            }
+            wire::PARAM_UINT => Value::UInt64(line(b, i)?.parse().ok()?),
+            wire::PARAM_SINT => Value::SInt64(line(b, i)?.parse().ok()?),
+            wire::PARAM_FLOAT => Value::Float64(line(b, i)?.parse().ok()?),
+            wire::PARAM_BINARY | wire::PARAM_STRING => {
+                let l: usize = line(b, i)?.parse().ok()?;
+                let v = b.get(*i..i.checked_add(l)?)?.to_vec();
+                *i += l;
+                if code == wire::PARAM_BINARY {
+                    Value::Binary(v)
+                } else {
+                    Value::String(String::from_utf8(v).ok()?)
+                }
+            }
+            wire::PARAM_LIST => {
+                let mut l = vec![];
+                while *b.get(*i)? != wire::LIST_END {
+                    l.push(next(b, i)?);
+                }
+                *i += 1;
+                Value::List(l)
+            }
+            _ => return None,
+        })
+    }
+    let mut i = 0;
+    let mut params = Vec::with_capacity(expected);
+    while i < b.len() {
+        match next(b, &mut i) {
+            Some(v) => params.push(v),
+            None => return Err(ProtocolError::invalid_data().locate(b, i).into()),
+        }
+    }
+    Ok(params)
+}
+
+/// Encode the packet for a query without parameters into `buf`, replacing its contents. This produces the same packet as
+/// a [`Query`] with this query string, without having to allocate one
+pub(crate) fn write_str_packet(query: &str, buf: &mut Vec<u8>) {
+    buf.clear();
+    let mut query_window_buffer = itoa::Buffer::new();
+    let query_window_str = query_window_buffer.format(query.len());
+    let mut total_packet_size_buffer = itoa::Buffer::new();
+    let total_packet_size_str =
+        total_packet_size_buffer.format(query_window_str.len() + 1 + query.len());
+    buf.push(wire::QUERY_PACKET);
+    buf.extend(total_packet_size_str.as_bytes());
+    buf.push(wire::LF);
+    buf.extend(query_window_str.as_bytes());
+    buf.push(wire::LF);
+    buf.extend(query.as_bytes());
+}
+
+/// Log a packet encoded by [`write_str_packet`]
+#[inline(always)]
+pub(crate) fn log_str_packet(packet: &[u8], redact: bool) {
+    log_query_packet(packet, 0, 0, redact)
+}
+
+/// Log an encoded query packet whose last `params_len` bytes are the `param_cnt` parameters
+#[inline(always)]
+fn log_query_packet(packet: &[u8], param_cnt: usize, params_len: usize, redact: bool) {
+    #[cfg(feature = "logging")]
+    {
+        if log::log_enabled!(log::Level::Trace) {
+            if redact {
+                log::trace!(
+                    "sending packet: {}<{} params redacted ({} bytes)>",
+                    packet[..packet.len() - params_len].escape_ascii(),
+                    param_cnt,
+                    params_len
+                );
+            } else {
+                log::trace!("sending packet: {}", packet.escape_ascii());
+            }
+        }
+    }
+    #[cfg(not(feature = "logging"))]
+    {
+        let _ = (packet, param_cnt, params_len, redact);
+    }
+}
+
+impl AsRef<Query> for Query {
+    fn as_ref(&self) -> &Query {
+        self
+    }
+}
+
+/*
+    query builder
+*/
+
+#[derive(Debug, PartialEq, Clone, Default)]
+/// A [`QueryBuilder`] builds a query string and its parameters together, for queries whose clauses depend on the input
+///
+/// Every [`QueryBuilder::bind`] writes the placeholder and encodes the parameter at the same time, so the query string
+/// and the parameters can't get out of sync as clauses are added or skipped.
+///
+/// ## Example
+/// ```
+/// use skytable::query::QueryBuilder;
+///
+/// fn find_users(min_followers: Option<u64>, verified: Option<bool>) -> skytable::Query {
+///     let mut qb = QueryBuilder::new("select all * from myspace.users where deleted = ");
+///     qb.bind(false);
+///     if let Some(min_followers) = min_followers {
+///         qb.push_sql(" and followers >= ").bind(min_followers);
+///     }
+///     if let Some(verified) = verified {
+///         qb.push_sql(" and verified = ").bind(verified);
+///     }
+///     qb.push_sql(" limit 100");
+///     qb.build().unwrap()
+/// }
+///
+/// let q = find_users(Some(100), None);
+/// assert_eq!(q.query_str(), "select all * from myspace.users where deleted = ? and followers >= ? limit 100");
+/// assert_eq!(q.param_cnt(), 2);
+/// ```
+pub struct QueryBuilder {
+    query: String,
+    params: Vec<u8>,
+    param_cnt: usize,
+}
+
+impl QueryBuilder {
+    /// Start building a query with the given text
+    pub fn new(sql: &str) -> Self {
+        Self {
+            query: sql.to_owned(),
+            params: vec![],
+            param_cnt: 0,
+        }
+    }
+    /// Append text to the query string. This should not contain placeholders, since [`QueryBuilder::bind`] adds them
+    pub fn push_sql(&mut self, sql: &str) -> &mut Self {
+        self.query.push_str(sql);
+        self
+    }
+    /// Append a placeholder to the query string and add the parameter. If `param` encodes to several parameters (like a
+    /// struct with `#[derive(Query)]`), one placeholder is added for each of them, separated by commas
+    pub fn bind(&mut self, param: impl SQParam) -> &mut Self {
+        let cnt = param.append_param(&mut self.params);
+        for i in 0..cnt {
+            if i != 0 {
+                self.query.push_str(", ");
+            }
+            self.query.push('?');
+        }
+        self.param_cnt += cnt;
+        self
+    }
+    /// Returns the query string built so far
+    pub fn query_str(&self) -> &str {
+        &self.query
+    }
+    /// Returns the number of parameters bound so far
+    pub fn param_cnt(&self) -> usize {
+        self.param_cnt
+    }
+    /// Build the [`Query`]
+    ///
+    /// This fails with [`Error::InvalidQuery`] if the number of placeholders in the query string (see
+    /// [`Query::placeholder_count`]) is not the number of bound parameters, which happens if text with a `?` was pushed
+    pub fn build(&self) -> ClientResult<Query> {
+        let placeholders = count_placeholders(&self.query);
+        if placeholders != self.param_cnt {
+            return Err(Error::InvalidQuery(format!(
+                "the query has {placeholders} placeholders, but {} parameters were bound",
+                self.param_cnt
+            )));
+        }
+        let mut q = Query::new(&self.query);
+        q.buf.extend_from_slice(&self.params);
+        q.param_cnt = self.param_cnt;
+        Ok(q)
+    }
+}
+
+/*
+    prepared query
+*/
+
+#[derive(Debug, PartialEq, Clone)]
+/// A [`PreparedQuery`] is a statement that is run several times with different parameters
+///
+/// Skyhash sends the full query string with every query, so this doesn't save any network traffic. However, the query
+/// string portion of the packet is encoded only once, when the [`PreparedQuery`] is created, and the parameters are
+/// encoded straight into the connection's buffer. Compared to building a fresh [`Query`] for every run, this avoids
+/// allocating and formatting the same prefix over and over again.
+///
+/// ## Example
+/// ```no_run
+/// use skytable::{query::PreparedQuery, Config, Query};
+///
+/// #[derive(Query)]
+/// struct User {
+///     username: String,
+///     followers: u64,
+/// }
+///
+/// let users = vec![
+///     User { username: "sayan".into(), followers: 100 },
+///     User { username: "elana".into(), followers: 200 },
+/// ];
+/// let mut db = Config::new_default("username", "password").connect().unwrap();
+/// let insert = PreparedQuery::new("insert into myspace.mymodel(?, ?)");
+/// for user in &users {
+///     db.run_prepared(&insert, user).unwrap();
+/// }
+/// ```
+pub struct PreparedQuery {
+    /// `<query window>\n<query>`
+    prefix: Box<[u8]>,
+    q_window: usize,
+}
+
+impl PreparedQuery {
+    /// Prepare a statement with the given query string
+    pub fn new(query: &str) -> Self {
+        let mut prefix = Vec::with_capacity(query.len() + 21);
+        pushlen!(prefix, query.len());
+        prefix.extend(query.as_bytes());
+        Self {
+            prefix: prefix.into_boxed_slice(),
+            q_window: query.len(),
+        }
+    }
+    /// Returns the query string for this statement
+    pub fn query_str(&self) -> &str {
+        let start = self.prefix.len() - self.q_window;
+        unsafe { core::str::from_utf8_unchecked(&self.prefix[start..]) }
+    }
+    /// Encode the packet for running this statement with `params` into `buf`, replacing its contents, and return the
+    /// number of parameters that were encoded
+    ///
+    /// Connections do this with their own buffer when you call `run_prepared`. Since the buffer is reused, running a
+    /// prepared query doesn't allocate once the buffer is large enough.
+    pub fn write_packet_into(&self, buf: &mut Vec<u8>, params: impl SQParam) -> usize {
+        /*
+            the params are encoded first, so that we know the packet size. the header and the prefix are then spliced in
+            before them (this only moves the params within the buffer)
+        */
+        buf.clear();
+        let param_cnt = params.append_param(buf);
+        let mut total_packet_size_buffer = itoa::Buffer::new();
+        let total_packet_size_str = total_packet_size_buffer.format(self.prefix.len() + buf.len());
+        let header = [wire::QUERY_PACKET]
+            .iter()
+            .chain(total_packet_size_str.as_bytes())
+            .chain(&[wire::LF])
+            .chain(self.prefix.iter());
+        buf.splice(0..0, header.copied());
+        param_cnt
+    }
+    /// Log the encoded `packet` for this statement (see [`Query::log_packet`])
+    #[inline(always)]
+    pub(crate) fn log_packet(&self, packet: &[u8], param_cnt: usize, redact: bool) {
+        let header_len = packet.iter().position(|b| *b == wire::LF).unwrap() + 1;
+        log_query_packet(
+            packet,
+            param_cnt,
+            packet.len() - header_len - self.prefix.len(),
+            redact,
+        )
+    }
+}
+
+/*
+    pipeline
+*/
+
+#[derive(Debug, PartialEq, Clone, Default)]
+/// A [`Pipeline`] is a collection of queries that are sent to the server together. The server returns a response for
+/// every query, in the order in which the queries were added to the pipeline.
+///
+/// Note that the queries in a pipeline are not run atomically, since Skyhash 2.0 has no framing for atomic blocks: if a
+/// query fails, the queries that ran before it are not rolled back and the queries after it are still run. Check every
+/// returned response for errors.
+///
+/// ## Example
+/// ```no_run
+/// use skytable::{query, query::Pipeline, Config};
+///
+/// let mut db = Config::new_default("username", "password").connect().unwrap();
+/// let mut pipeline = Pipeline::new();
+/// pipeline.add_query(&query!("insert into myspace.mymodel(?, ?)", "sayan", 100u64));
+/// pipeline.add_query(&query!("select * from myspace.mymodel where username = ?", "sayan"));
+/// let responses = db.execute_pipeline(&pipeline).unwrap();
+/// assert_eq!(responses.len(), 2);
+/// ```
+pub struct Pipeline {
+    cnt: usize,
+    buf: Vec<u8>,
+}
+
+impl Pipeline {
+    /// Create a new, empty pipeline
+    pub const fn new() -> Self {
+        Self {
+            cnt: 0,
+            buf: Vec::new(),
+        }
+    }
+    /// Returns the number of queries in this pipeline
+    pub fn query_count(&self) -> usize {
+        self.cnt
+    }
+    /// Returns the encoded queries in this pipeline, as they will be sent after the packet header. Every query is
+    /// encoded as its query window, its parameter window, the query string and the encoded parameters
+    ///
+    /// This is only meant for debugging (like [`Query::debug_encode_packet`]), for example to check what a pipeline
+    /// that misbehaves actually sends
+    ///
+    /// ## Example
+    /// ```
+    /// use skytable::{query, query::Pipeline};
+    ///
+    /// let mut pipeline = Pipeline::new();
+    /// pipeline.add_query(&query!("sysctl report status"));
+    /// assert_eq!(pipeline.debug_encoded(), b"20\n0\nsysctl report status");
+    /// ```
+    pub fn debug_encoded(&self) -> &[u8] {
+        &self.buf
+    }
+    /// Returns the size of the encoded queries in this pipeline in bytes (see [`Self::debug_encoded`]), which doesn't
+    /// include the packet header
+    pub fn len_bytes(&self) -> usize {
+        self.buf.len()
+    }
+    /// Add a query to this pipeline, returning the pipeline so that calls can be chained
+    ///
+    /// ## Example
+    /// ```
+    /// use skytable::{query, query::Pipeline};
+    ///
+    /// let mut pipeline = Pipeline::new();
+    /// pipeline
+    ///     .add_query(&query!("create space myspace"))
+    ///     .add_query(&query!("create model myspace.mymodel(username: string, followers: uint64)"));
+    /// assert_eq!(pipeline.query_count(), 2);
+    /// ```
+    pub fn add_query(&mut self, q: &Query) -> &mut Self {
+        /*
+            [query window][param window][query][params]
+        */
+        pushlen!(self.buf, q.q_window);
+        pushlen!(self.buf, q.buf.len() - q.q_window);
+        self.buf.extend(&q.buf);
+        self.cnt += 1;
+        self
+    }
+    /// Add every query from `queries` to this pipeline (like [`Extend`]), returning the number of queries that were added
+    pub fn add_queries<Q: AsRef<Query>>(&mut self, queries: impl IntoIterator<Item = Q>) -> usize {
+        let before = self.cnt;
+        for q in queries {
+            self.add_query(q.as_ref());
+        }
+        self.cnt - before
+    }
+    /// Add one `insert_stmt` query for every row to this pipeline, where each row provides all the parameters for its
+    /// query. The rows can be owned or borrowed (for example, from iterating over a `&Vec<T>`)
+    ///
+    /// ## Example
+    /// ```
+    /// use skytable::{query::Pipeline, Query};
+    ///
+    /// #[derive(Query)]
+    /// struct User {
+    ///     username: String,
+    ///     followers: u64,
+    /// }
+    ///
+    /// let users = vec![
+    ///     User { username: "sayan".into(), followers: 100 },
+    ///     User { username: "elana".into(), followers: 200 },
+    /// ];
+    /// let mut pipeline = Pipeline::new();
+    /// pipeline.insert_many("insert into myspace.mymodel(?, ?)", &users);
+    /// assert_eq!(pipeline.query_count(), 2);
+    /// ```
+    pub fn insert_many<T: SQParam>(
+        &mut self,
+        insert_stmt: &str,
+        rows: impl IntoIterator<Item = T>,
+    ) {
+        let mut q = Query::new(insert_stmt);
+        for row in rows {
+            q.clear_params();
+            q.push_param(row);
+            self.add_query(&q);
+        }
+    }
+    pub(crate) fn write_packet(&self, buf: &mut impl Write) -> io::Result<()> {
+        /*
+            [total packet size][queries]
+        */
+        let mut total_packet_size_buffer = itoa::Buffer::new();
+        let total_packet_size_str = total_packet_size_buffer.format(self.buf.len());
+        buf.write_all(&[wire::PIPELINE_PACKET])?;
+        buf.write_all(total_packet_size_str.as_bytes())?;
+        buf.write_all(&[wire::LF])?;
+        buf.write_all(&self.buf)
+    }
+    /// Returns every query in this pipeline as its query string and its encoded parameters
+    #[cfg(any(feature = "logging", feature = "testing"))]
+    pub(crate) fn queries(&self) -> impl Iterator<Item = (&str, &[u8])> {
+        fn read_len(b: &[u8], i: &mut usize) -> usize {
+            let mut len = 0;
+            while b[*i] != wire::LF {
+                len = len * 10 + (b[*i] & 0x0f) as usize;
+                *i += 1;
+            }
+            *i += 1;
+            len
+        }
+        let mut i = 0;
+        core::iter::from_fn(move || {
+            if i == self.buf.len() {
+                return None;
+            }
+            let q_window = read_len(&self.buf, &mut i);
+            let p_window = read_len(&self.buf, &mut i);
+            let q = unsafe { core::str::from_utf8_unchecked(&self.buf[i..i + q_window]) };
+            let p = &self.buf[i + q_window..i + q_window + p_window];
+            i += q_window + p_window;
+            Some((q, p))
+        })
+    }
+    /// Log the encoded `packet` for this pipeline at the `trace` level (a no-op unless the `logging` feature is enabled). If
+    /// `redact` is set, the parameters of every query are replaced by their size
+    #[inline(always)]
+    pub(crate) fn log_packet(&self, packet: &[u8], redact: bool) {
+        #[cfg(feature = "logging")]
+        {
+            if log::log_enabled!(log::Level::Trace) {
+                if redact {
+                    let mut redacted = format!("P{}\\n", self.buf.len());
+                    for (q, params) in self.queries() {
+                        redacted.push_str(&format!(
+                            "{}\\n{}\\n{}<{} bytes redacted>",
+                            q.len(),
+                            params.len(),
+                            q.as_bytes().escape_ascii(),
+                            params.len()
+                        ));
+                    }
+                    log::trace!("sending pipeline: {redacted}");
+                } else {
+                    log::trace!("sending pipeline: {}", packet.escape_ascii());
+                }
+            }
+        }
+        #[cfg(not(feature = "logging"))]
+        {
+            let _ = (packet, redact);
+        }
+    }
+}
+
+impl<Q: AsRef<Query>> Extend<Q> for Pipeline {
+    fn extend<T: IntoIterator<Item = Q>>(&mut self, iter: T) {
+        self.add_queries(iter);
+    }
+}
+
+impl<Q: AsRef<Query>> FromIterator<Q> for Pipeline {
+    fn from_iter<T: IntoIterator<Item = Q>>(iter: T) -> Self {
+        let mut pipeline = Self::new();
+        pipeline.extend(iter);
+        pipeline
+    }
+}
+
+/*
+    identifiers
+*/
+
+/// The maximum length of a Skytable identifier (space or model name)
+pub const MAX_IDENT_LEN: usize = 64;
+
+/// Validate a Skytable identifier (for example, a space or model name) and return it
+///
+/// A valid identifier is at most [`MAX_IDENT_LEN`] bytes long, is composed of ASCII alphanumeric characters and underscores,
+/// and does not start with a digit. Use this when you need to place names that come from untrusted input in a query string.
+///
+/// ## Example
+/// ```
+/// use skytable::query;
+///
+/// assert_eq!(query::ident("mymodel_2").unwrap(), "mymodel_2");
+/// assert!(query::ident("2mymodel").is_err());
+/// assert!(query::ident("my model").is_err());
+/// ```
+pub fn ident(name: &str) -> ClientResult<String> {
+    let okay = !name.is_empty()
+        && name.len() <= MAX_IDENT_LEN
+        && !name.as_bytes()[0].is_ascii_digit()
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() | (b == b'_'));
+    if okay {
+        Ok(name.to_owned())
+    } else {
+        Err(Error::InvalidQuery(format!("invalid identifier `{name}`")))
+    }
+}
+
 /*
     Query parameters
 */
@@ -170,13 +988,18 @@ impl Query {
 /// let query = query!("insert into myspace.mymodel(?, ?, ?, ?)", MyType::new("sayan".to_owned(), 0, "pass123".to_owned()), 101);
 /// assert_eq!(query.param_cnt(), 4);
 /// ```
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` can't be used as a query parameter",
+    label = "this needs to implement `SQParam`",
+    note = "for a struct, add `#[derive(Query)]` (or implement `SQParam` manually) to pass all its fields as parameters"
+)]
 pub trait SQParam {
     /// Append this element to the raw parameter buffer
     ///
     /// Return the number of parameters appended (see example above)
     fn append_param(&self, q: &mut Vec<u8>) -> usize;
 }
-// null
+/// `None` is sent as `null`, exactly like [`Null`]
 impl<T> SQParam for Option<T>
 where
     T: SQParam,
@@ -184,7 +1007,7 @@ where
     fn append_param(&self, buf: &mut Vec<u8>) -> usize {
         match self {
             None => {
-                buf.push(0);
+                buf.push(wire::PARAM_NULL);
                 1
             }
             Some(e) => e.append_param(buf),
@@ -192,53 +1015,114 @@ where
     }
 }
 
+// references
+impl<T: SQParam + ?Sized> SQParam for &T {
+    fn append_param(&self, buf: &mut Vec<u8>) -> usize {
+        T::append_param(self, buf)
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl SQParam for bytes::Bytes {
+    fn append_param(&self, buf: &mut Vec<u8>) -> usize {
+        self.as_ref().append_param(buf)
+    }
+}
+
+#[cfg(feature = "rust_decimal")]
+/// A [`Decimal`](rust_decimal::Decimal) is sent as its canonical string representation (for example, `-123.4500`), so the
+/// column must be declared as a `string`. The scale is preserved exactly, which means that `1.50` and `1.5` are sent (and
+/// read back) as different strings
+impl SQParam for rust_decimal::Decimal {
+    fn append_param(&self, buf: &mut Vec<u8>) -> usize {
+        self.to_string().append_param(buf)
+    }
+}
+
+/// Skyhash 2.0 has no 128-bit integers, so a [`u128`] is sent as its decimal string and must be stored in a `string`
+/// column (see the [`FromValue`](crate::response::FromValue) implementation to read it back)
+impl SQParam for u128 {
+    fn append_param(&self, buf: &mut Vec<u8>) -> usize {
+        self.to_string().append_param(buf)
+    }
+}
+
+/// Like [`u128`], an [`i128`] is sent as its decimal string and must be stored in a `string` column
+impl SQParam for i128 {
+    fn append_param(&self, buf: &mut Vec<u8>) -> usize {
+        self.to_string().append_param(buf)
+    }
+}
+
+/// A [`Duration`] is sent as a `uint64` number of whole seconds; any fractional part is truncated
+impl SQParam for Duration {
+    fn append_param(&self, buf: &mut Vec<u8>) -> usize {
+        self.as_secs().append_param(buf)
+    }
+}
+
+/// A [`SystemTime`] is sent as a UNIX timestamp in whole seconds. A time before the UNIX epoch is sent as a negative
+/// `sint64`, which the server will reject for a `uint64` column
+impl SQParam for SystemTime {
+    fn append_param(&self, buf: &mut Vec<u8>) -> usize {
+        match self.duration_since(UNIX_EPOCH) {
+            Ok(since) => since.as_secs().append_param(buf),
+            Err(e) => (-(e.duration().as_secs() as i64)).append_param(buf),
+        }
+    }
+}
+
 /// Use this when you need to use `null`
+///
+/// This is encoded exactly like `Option::<T>::None`. Skyhash 2.0 has no separate "unset" or "use the default" parameter:
+/// every parameter is sent as a value, and `null` is the only way to leave a (nullable) field empty. Hence, there is no
+/// `Unset` or `Default` parameter type.
 pub struct Null;
 impl SQParam for Null {
     fn append_param(&self, buf: &mut Vec<u8>) -> usize {
-        buf.push(0);
+        buf.push(wire::PARAM_NULL);
         1
     }
 }
 // bool
 impl SQParam for bool {
     fn append_param(&self, buf: &mut Vec<u8>) -> usize {
-        let a = [1, *self as u8];
+        let a = [wire::PARAM_BOOL, *self as u8];
         buf.extend(a);
         1
     }
 }
 macro_rules! imp_number {
-    ($($code:literal => $($ty:ty as $base:ty),*),* $(,)?) => {
+    ($($code:path => $($ty:ty as $base:ty),*);* $(;)?) => {
         $($(impl SQParam for $ty { fn append_param(&self, b: &mut Vec<u8>) -> usize {
             let mut buf = ::itoa::Buffer::new();
             let str = buf.format(<$base>::from(*self));
-            b.push($code); b.extend(str.as_bytes()); b.push(b'\n');
+            b.push($code); b.extend(str.as_bytes()); b.push(wire::LF);
             1
         } })*)*
     }
 }
 
 macro_rules! imp_terminated_str_type {
-    ($($code:literal => $($ty:ty),*),* $(,)?) => {
-        $($(impl SQParam for $ty { fn append_param(&self, buf: &mut Vec<u8>) -> usize { buf.push($code); buf.extend(self.to_string().as_bytes()); buf.push(b'\n'); 1} })*)*
+    ($($code:path => $($ty:ty),*);* $(;)?) => {
+        $($(impl SQParam for $ty { fn append_param(&self, buf: &mut Vec<u8>) -> usize { buf.push($code); buf.extend(self.to_string().as_bytes()); buf.push(wire::LF); 1} })*)*
     }
 }
 
 // uint, sint, float
 imp_number!(
-    2 => u8 as u8, NonZeroU8 as u8, u16 as u16, NonZeroU16 as u16, u32 as u32, NonZeroU32 as u32, u64 as u64, NonZeroU64 as u64, usize as usize, NonZeroUsize as usize,
-    3 => i8 as i8, NonZeroI8 as i8, i16 as i16, NonZeroI16 as i16, i32 as i32, NonZeroI32 as i32, i64 as i64, NonZeroI64 as i64, isize as isize, NonZeroIsize as isize,
+    wire::PARAM_UINT => u8 as u8, NonZeroU8 as u8, u16 as u16, NonZeroU16 as u16, u32 as u32, NonZeroU32 as u32, u64 as u64, NonZeroU64 as u64, usize as usize, NonZeroUsize as usize;
+    wire::PARAM_SINT => i8 as i8, NonZeroI8 as i8, i16 as i16, NonZeroI16 as i16, i32 as i32, NonZeroI32 as i32, i64 as i64, NonZeroI64 as i64, isize as isize, NonZeroIsize as isize;
 );
 
 imp_terminated_str_type!(
-    4 => f32, f64
+    wire::PARAM_FLOAT => f32, f64
 );
 
 // bin
-impl<'a> SQParam for &'a [u8] {
+impl SQParam for &[u8] {
     fn append_param(&self, buf: &mut Vec<u8>) -> usize {
-        buf.push(5);
+        buf.push(wire::PARAM_BINARY);
         pushlen!(buf, self.len());
         buf.extend(*self);
         1
@@ -246,44 +1130,637 @@ impl<'a> SQParam for &'a [u8] {
 }
 impl<const N: usize> SQParam for [u8; N] {
     fn append_param(&self, buf: &mut Vec<u8>) -> usize {
-        buf.push(5);
+        buf.push(wire::PARAM_BINARY);
         pushlen!(buf, self.len());
         buf.extend(self);
         1
     }
 }
-impl<'a, const N: usize> SQParam for &'a [u8; N] {
-    fn append_param(&self, buf: &mut Vec<u8>) -> usize {
-        buf.push(5);
-        pushlen!(buf, self.len());
-        buf.extend(*self);
-        1
-    }
-}
 impl SQParam for Vec<u8> {
     fn append_param(&self, buf: &mut Vec<u8>) -> usize {
-        buf.push(5);
+        buf.push(wire::PARAM_BINARY);
         pushlen!(buf, self.len());
         buf.extend(self);
         1
     }
 }
 // str
-impl<'a> SQParam for &'a str {
+impl SQParam for &str {
     fn append_param(&self, buf: &mut Vec<u8>) -> usize {
-        buf.push(6);
+        buf.push(wire::PARAM_STRING);
         pushlen!(buf, self.len());
         buf.extend(self.as_bytes());
         1
     }
 }
-impl<'a> SQParam for &'a String {
-    fn append_param(&self, q: &mut Vec<u8>) -> usize {
-        self.as_str().append_param(q)
-    }
-}
 impl SQParam for String {
     fn append_param(&self, buf: &mut Vec<u8>) -> usize {
         self.as_str().append_param(buf)
     }
 }
+// tuples
+macro_rules! imp_tuple {
+    ($(($($elem:ident),*)),* $(,)?) => {
+        $(
+            /// Every element of the tuple is added as its own parameter (or parameters), in order
+            #[allow(non_snake_case)]
+            impl<$($elem: SQParam),*> SQParam for ($($elem),*,) {
+                fn append_param(&self, buf: &mut Vec<u8>) -> usize {
+                    let ($($elem),*,) = self;
+                    0 $(+ $elem.append_param(buf))*
+                }
+            }
+        )*
+    }
+}
+
+// the same pyramid as the `FromResponse` impls for rows
+imp_tuple!(
+    (A),
+    (A, B),
+    (A, B, C),
+    (A, B, C, D),
+    (A, B, C, D, E),
+    (A, B, C, D, E, F),
+    (A, B, C, D, E, F, G),
+    (A, B, C, D, E, F, G, H),
+    (A, B, C, D, E, F, G, H, I),
+    (A, B, C, D, E, F, G, H, I, J),
+    (A, B, C, D, E, F, G, H, I, J, K),
+    (A, B, C, D, E, F, G, H, I, J, K, L),
+    (A, B, C, D, E, F, G, H, I, J, K, L, M),
+    (A, B, C, D, E, F, G, H, I, J, K, L, M, N),
+    (A, B, C, D, E, F, G, H, I, J, K, L, M, N, O),
+    (A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P),
+    (A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q),
+    (A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R),
+    (A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S),
+    (A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T),
+    (A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U),
+    (A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V),
+    (A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W),
+    (A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X),
+    (A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y),
+    (A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z),
+);
+
+// list
+/// A list parameter, encoded as a single Skyhash list
+///
+/// `&[u8]` and `Vec<u8>` are already encoded as binary blobs, so slices can't directly be used as list parameters; wrap them
+/// in a [`QList`] instead. Every element of the list counts towards a single parameter.
+///
+/// ## Example
+/// ```
+/// use skytable::{query, query::QList};
+///
+/// let tags = ["rust", "database"];
+/// let q = query!("insert into myspace.mymodel(?, ?)", "sayan", QList::new(&tags));
+/// assert_eq!(q.param_cnt(), 2);
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct QList<'a, T: SQParam> {
+    l: &'a [T],
+}
+
+impl<'a, T: SQParam> QList<'a, T> {
+    /// Create a new list parameter from the given slice
+    pub fn new(l: &'a [T]) -> Self {
+        Self { l }
+    }
+}
+
+impl<'a, T: SQParam> From<&'a [T]> for QList<'a, T> {
+    fn from(l: &'a [T]) -> Self {
+        Self::new(l)
+    }
+}
+
+impl<T: SQParam> SQParam for QList<'_, T> {
+    fn append_param(&self, buf: &mut Vec<u8>) -> usize {
+        buf.push(wire::PARAM_LIST);
+        for param in self.l {
+            param.append_param(buf);
+        }
+        buf.push(wire::LIST_END);
+        1
+    }
+}
+
+/// Binary data as a query parameter, for a `binary` column
+///
+/// A `Vec<u8>` or `&[u8]` is already sent as binary data, so this doesn't change the encoding. It makes it explicit
+/// that the bytes are a blob (and not, for example, text that should be sent as a `string`), and it can be read back
+/// from a [`Value::Binary`]
+///
+/// ## Example
+/// ```
+/// use skytable::{query, query::Blob};
+///
+/// let avatar = Blob::from(vec![0x89, b'P', b'N', b'G']);
+/// let q = query!("update myspace.users set avatar = ? where username = ?", &avatar, "sayan");
+/// assert_eq!(q.param_cnt(), 2);
+/// assert_eq!(avatar.as_ref(), b"\x89PNG");
+/// ```
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Default)]
+pub struct Blob(Vec<u8>);
+
+impl Blob {
+    /// Create a new blob from the given bytes
+    pub fn new(b: impl Into<Vec<u8>>) -> Self {
+        Self(b.into())
+    }
+    /// Returns the number of bytes in this blob
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+    /// Returns true if this blob has no bytes
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+    /// Consume the blob, returning its bytes
+    pub fn into_inner(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+impl From<Vec<u8>> for Blob {
+    fn from(b: Vec<u8>) -> Self {
+        Self(b)
+    }
+}
+
+impl From<&[u8]> for Blob {
+    fn from(b: &[u8]) -> Self {
+        Self(b.to_vec())
+    }
+}
+
+impl From<Blob> for Vec<u8> {
+    fn from(b: Blob) -> Self {
+        b.0
+    }
+}
+
+impl AsRef<[u8]> for Blob {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl IntoIterator for Blob {
+    type Item = u8;
+    type IntoIter = std::vec::IntoIter<u8>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Blob {
+    type Item = &'a u8;
+    type IntoIter = std::slice::Iter<'a, u8>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl SQParam for Blob {
+    fn append_param(&self, buf: &mut Vec<u8>) -> usize {
+        self.0.as_slice().append_param(buf)
+    }
+}
+
+#[test]
+fn t_blob() {
+    use crate::response::FromValue;
+    let bytes = vec![0x00, 0xff, b'\n', b'?'];
+    let blob = Blob::from(bytes.clone());
+    // encoded exactly like the bytes themselves
+    let q = crate::query!("insert into myspace.mymodel(?)", &blob);
+    assert_eq!(
+        q.debug_encode_packet(),
+        crate::query!("insert into myspace.mymodel(?)", bytes.clone()).debug_encode_packet()
+    );
+    // and read back from the binary value
+    let params = q.decode_params().unwrap();
+    assert_eq!(params, [Value::Binary(bytes.clone())]);
+    assert_eq!(Blob::from_value(params[0].clone()).unwrap(), blob);
+    assert!(Blob::from_value(Value::str("not binary")).is_err());
+    assert_eq!(blob.len(), 4);
+    assert_eq!(blob.as_ref(), &bytes[..]);
+    assert_eq!((&blob).into_iter().copied().collect::<Vec<_>>(), bytes);
+    assert_eq!(blob.into_inner(), bytes);
+}
+
+#[test]
+fn t_list_param() {
+    use crate::response::Value;
+    let tags = ["hello", "giant", "world"];
+    let q = query!("insert into apps.social(?, ?)", "sayan", QList::new(&tags));
+    assert_eq!(q.param_cnt(), 2);
+    assert_eq!(
+        q.decode_params().unwrap(),
+        vec![
+            Value::String("sayan".into()),
+            Value::List(vec![
+                Value::String("hello".into()),
+                Value::String("giant".into()),
+                Value::String("world".into())
+            ])
+        ]
+    );
+}
+
+#[test]
+fn t_list_param_from_slice() {
+    use crate::response::Value;
+    let ids: Vec<u64> = vec![1, 2, 3];
+    let nested = [QList::from(&ids[..]), QList::from(&ids[..1])];
+    let q = query!("insert into apps.ids(?)", QList::new(&nested));
+    assert_eq!(q.param_cnt(), 1);
+    assert_eq!(
+        q.decode_params().unwrap(),
+        vec![Value::List(vec![
+            Value::List(vec![Value::UInt64(1), Value::UInt64(2), Value::UInt64(3)]),
+            Value::List(vec![Value::UInt64(1)])
+        ])]
+    );
+}
+
+#[test]
+fn t_decode_params() {
+    struct Broken;
+    impl SQParam for Broken {
+        fn append_param(&self, buf: &mut Vec<u8>) -> usize {
+            buf.extend(b"\x065\nabc");
+            1
+        }
+    }
+    let ids = [1u64, 2];
+    let mut q = query!(
+        "insert into myspace.mymodel(?, ?, ?, ?, ?, ?, ?)",
+        None::<u64>,
+        false,
+        -12i8,
+        1.5f32,
+        b"\x00\xFF".as_ref(),
+        QList::new(&ids),
+        "sayan"
+    );
+    q.push_param(QList::<u64>::new(&[]));
+    assert_eq!(
+        q.decode_params().unwrap(),
+        vec![
+            Value::Null,
+            Value::Bool(false),
+            Value::SInt64(-12),
+            Value::Float64(1.5),
+            Value::Binary(vec![0x00, 0xFF]),
+            Value::List(vec![Value::UInt64(1), Value::UInt64(2)]),
+            Value::String("sayan".into()),
+            Value::List(vec![]),
+        ]
+    );
+    assert_eq!(q.decode_params().unwrap().len(), q.param_cnt());
+    assert_eq!(Query::new("select 1").decode_params().unwrap(), vec![]);
+    // a custom parameter that claims more bytes than it wrote
+    q.push_param(Broken);
+    match q.decode_params() {
+        Err(Error::ProtocolError(e))
+            if *e.kind() == crate::error::ProtocolErrorKind::InvalidServerResponseForData => {}
+        other => panic!("expected an error, got {:?}", other),
+    }
+}
+
+#[test]
+fn t_query_bytes_roundtrip() {
+    let tags = ["hello", "world"];
+    let empty: [u64; 0] = [];
+    let queries = [
+        Query::new("sysctl report status"),
+        Query::new(""),
+        query!(
+            "insert into myspace.mymodel(?, ?, ?, ?, ?)",
+            "sayan",
+            None::<String>,
+            QList::new(&tags),
+            QList::new(&empty),
+            -1.5f64
+        ),
+        query!("select * from myspace.mymodel where username = ?", "😀\n?"),
+    ];
+    for q in queries.iter() {
+        let b = q.to_bytes();
+        assert!(b.starts_with(b"SKYQ\x01"));
+        let loaded = Query::from_bytes(&b).unwrap();
+        assert_eq!(&loaded, q);
+        assert_eq!(loaded.decode_params().unwrap(), q.decode_params().unwrap());
+        assert_eq!(loaded.debug_encode_packet(), q.debug_encode_packet());
+    }
+}
+
+#[test]
+fn t_query_bytes_invalid() {
+    let b = query!("select * from myspace.mymodel where username = ?", "sayan").to_bytes();
+    let invalid = |b: &[u8]| match Query::from_bytes(b) {
+        Err(Error::ParseError(ParseError::Other(e))) => e,
+        r => panic!("expected an error, got {:?}", r),
+    };
+    assert_eq!(invalid(b""), "invalid serialized query: missing header");
+    assert_eq!(
+        invalid(b"SKYQ"),
+        "invalid serialized query: missing version"
+    );
+    assert_eq!(
+        invalid(b"SKYQ\x02"),
+        "invalid serialized query: unsupported version 2"
+    );
+    assert_eq!(
+        invalid(b"SKYQ\x01+1\n0\nx"),
+        "invalid serialized query: bad lengths"
+    );
+    assert_eq!(
+        invalid(b"SKYQ\x0110\n0\nselect"),
+        "invalid serialized query: truncated query string"
+    );
+    assert_eq!(
+        invalid(b"SKYQ\x011\n0\n\xFF"),
+        "invalid serialized query: query string is not UTF-8"
+    );
+    assert_eq!(
+        invalid(b"SKYQ\x011\n2\n?\x00"),
+        "invalid serialized query: wrong parameter count"
+    );
+    // every truncation of a valid query is rejected
+    for len in 0..b.len() {
+        assert!(Query::from_bytes(&b[..len]).is_err(), "{}", len);
+    }
+}
+
+#[test]
+#[cfg(feature = "rust_decimal")]
+fn t_decimal_param() {
+    use {
+        crate::response::{FromValue, Value},
+        rust_decimal::Decimal,
+    };
+    for d in [
+        "123.456789",
+        "-123.456789",
+        "0",
+        "-0.000001",
+        "79228162514264337593543950335",
+    ] {
+        let decimal: Decimal = d.parse().unwrap();
+        let q = query!("insert into apps.payments(?)", decimal);
+        let params = q.decode_params().unwrap();
+        assert_eq!(params, vec![Value::String(d.into())]);
+        assert_eq!(
+            Decimal::from_value(params.into_iter().next().unwrap()).unwrap(),
+            decimal
+        );
+    }
+}
+
+#[test]
+fn t_write_str_packet() {
+    let mut buf = b"leftovers".to_vec();
+    for q in ["create space myspace", "", &"x".repeat(1000)] {
+        write_str_packet(q, &mut buf);
+        assert_eq!(buf, Query::new(q).debug_encode_packet());
+    }
+}
+
+#[test]
+fn t_pipeline_add_queries() {
+    let queries = vec![
+        query!("create space myspace"),
+        query!("create model myspace.mymodel(username: string, followers: uint64)"),
+    ];
+    let mut pipeline = Pipeline::new();
+    assert_eq!(pipeline.add_queries(&queries), 2);
+    assert_eq!(pipeline.add_queries(Vec::<Query>::new()), 0);
+    assert_eq!(
+        pipeline
+            .add_query(&query!(
+                "insert into myspace.mymodel(?, ?)",
+                "sayan",
+                100u64
+            ))
+            .add_query(&query!(
+                "select * from myspace.mymodel where username = ?",
+                "sayan"
+            ))
+            .query_count(),
+        4
+    );
+    // the same as adding them one by one
+    let mut expected = Pipeline::new();
+    expected.extend(&queries);
+    expected.add_query(&query!(
+        "insert into myspace.mymodel(?, ?)",
+        "sayan",
+        100u64
+    ));
+    expected.add_query(&query!(
+        "select * from myspace.mymodel where username = ?",
+        "sayan"
+    ));
+    assert_eq!(pipeline.buf, expected.buf);
+}
+
+#[test]
+#[cfg(feature = "bytes")]
+fn t_bytes_param() {
+    use {
+        crate::response::{FromValue, Value},
+        bytes::Bytes,
+    };
+    let blob = Bytes::from_static(b"\x00\x01binary\xFF");
+    let q = query!("insert into apps.blobs(?)", blob.clone());
+    let params = q.decode_params().unwrap();
+    assert_eq!(params, vec![Value::Binary(blob.to_vec())]);
+    let decoded = Bytes::from_value(params.into_iter().next().unwrap()).unwrap();
+    assert_eq!(decoded, blob);
+    // clones share the same allocation
+    let cloned = decoded.clone();
+    assert_eq!(cloned.as_ptr(), decoded.as_ptr());
+}
+
+#[test]
+fn t_prepared_query() {
+    struct User(&'static str, u64);
+    impl SQParam for User {
+        fn append_param(&self, buf: &mut Vec<u8>) -> usize {
+            self.0.append_param(buf) + self.1.append_param(buf)
+        }
+    }
+    let pq = PreparedQuery::new("insert into myspace.mymodel(?, ?)");
+    assert_eq!(pq.query_str(), "insert into myspace.mymodel(?, ?)");
+    let mut buf = vec![];
+    for user in [User("sayan", 100), User("elana", 2000)] {
+        assert_eq!(pq.write_packet_into(&mut buf, &user), 2);
+        assert_eq!(
+            buf,
+            query!("insert into myspace.mymodel(?, ?)", user.0, user.1).debug_encode_packet()
+        );
+    }
+}
+
+#[test]
+fn t_null_encoding() {
+    use crate::response::Value;
+    let q = query!(
+        "insert into apps.users(?, ?, ?, ?)",
+        Null,
+        None::<u64>,
+        None::<&str>,
+        Some(1u64)
+    );
+    assert_eq!(q.param_cnt(), 4);
+    assert_eq!(&q.buf[q.q_window..], b"\x00\x00\x00\x021\n");
+    assert_eq!(
+        q.decode_params().unwrap(),
+        vec![Value::Null, Value::Null, Value::Null, Value::UInt64(1)]
+    );
+}
+
+#[test]
+fn t_placeholder_count() {
+    let count = |q: &str| Query::new(q).placeholder_count();
+    assert_eq!(count("select * from myspace.mymodel"), 0);
+    assert_eq!(count("insert into myspace.mymodel(?, ?, ?)"), 3);
+    assert_eq!(
+        count("select * from myspace.mymodel where note = 'why?'"),
+        0
+    );
+    assert_eq!(
+        count("update myspace.mymodel set note = 'it''s ok?' where username = ?"),
+        1
+    );
+    assert_eq!(
+        count("insert into myspace.mymodel(?, 'a?b', '''?''', ?)"),
+        2
+    );
+}
+
+#[test]
+fn t_time_params() {
+    use crate::response::{FromValue, Value};
+    let duration = Duration::from_millis(90_500);
+    let time = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+    let q = crate::query!("insert into myspace.mymodel(?, ?)", duration, time);
+    let params = q.decode_params().unwrap();
+    assert_eq!(
+        params,
+        vec![Value::UInt64(90), Value::UInt64(1_700_000_000)]
+    );
+    let mut params = params.into_iter();
+    assert_eq!(
+        Duration::from_value(params.next().unwrap()).unwrap(),
+        Duration::from_secs(90)
+    );
+    assert_eq!(
+        SystemTime::from_value(params.next().unwrap()).unwrap(),
+        time
+    );
+    let q = crate::query!("?", UNIX_EPOCH - Duration::from_secs(60));
+    assert_eq!(q.decode_params().unwrap(), vec![Value::SInt64(-60)]);
+}
+
+#[test]
+fn t_use_entity() {
+    assert_eq!(
+        Query::use_entity("myspace").unwrap().query_str(),
+        "use myspace"
+    );
+    assert_eq!(
+        Query::use_entity("myspace.mymodel").unwrap().query_str(),
+        "use myspace.mymodel"
+    );
+    for bad in [
+        "",
+        "myspace.",
+        "a.b.c",
+        "myspace; drop space myspace",
+        "my space",
+    ] {
+        assert!(
+            matches!(Query::use_entity(bad), Err(Error::InvalidQuery(_))),
+            "{}",
+            bad
+        );
+    }
+}
+
+#[test]
+fn t_128_bit_params() {
+    use crate::response::{FromValue, Value};
+    let big = u64::MAX as u128 + 1;
+    let small = i64::MIN as i128 - 1;
+    let q = crate::query!("insert into myspace.mymodel(?, ?)", big, small);
+    let params = q.decode_params().unwrap();
+    assert_eq!(
+        params,
+        [
+            Value::String("18446744073709551616".into()),
+            Value::String("-9223372036854775809".into())
+        ]
+    );
+    let mut params = params.into_iter();
+    assert_eq!(u128::from_value(params.next().unwrap()).unwrap(), big);
+    assert_eq!(i128::from_value(params.next().unwrap()).unwrap(), small);
+}
+
+#[test]
+fn t_query_builder() {
+    let mut qb = QueryBuilder::new("update myspace.mymodel set followers += ");
+    qb.bind(1u64)
+        .push_sql(" where username = ")
+        .bind("sayan")
+        .push_sql(" and note = 'why?'");
+    let q = qb.build().unwrap();
+    assert_eq!(
+        q.query_str(),
+        "update myspace.mymodel set followers += ? where username = ? and note = 'why?'"
+    );
+    assert_eq!(q.param_cnt(), 2);
+    assert_eq!(
+        q,
+        crate::query!(
+            "update myspace.mymodel set followers += ? where username = ? and note = 'why?'",
+            1u64,
+            "sayan"
+        )
+    );
+    // one placeholder for every parameter that a struct encodes to
+    struct User {
+        username: &'static str,
+        followers: u64,
+    }
+    impl SQParam for User {
+        fn append_param(&self, q: &mut Vec<u8>) -> usize {
+            self.username.append_param(q) + self.followers.append_param(q)
+        }
+    }
+    let mut qb = QueryBuilder::new("insert into myspace.mymodel(");
+    qb.bind(User {
+        username: "sayan",
+        followers: 100,
+    })
+    .push_sql(")");
+    assert_eq!(qb.query_str(), "insert into myspace.mymodel(?, ?)");
+    assert_eq!(qb.build().unwrap().param_cnt(), 2);
+    // a placeholder that wasn't added by `bind`
+    let mut qb = QueryBuilder::new("select * from myspace.mymodel where username = ?");
+    qb.push_sql(" and followers > ").bind(10u64);
+    match qb.build() {
+        Err(Error::InvalidQuery(e)) => {
+            assert_eq!(
+                e,
+                "the query has 2 placeholders, but 1 parameters were bound"
+            )
+        }
+        r => panic!("expected an error, got {:?}", r),
+    }
+}
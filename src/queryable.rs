@@ -0,0 +1,205 @@
+/*
+ * Copyright 2023, Sayan Nandan <nandansayan@outlook.com>
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+
+//! # Generic connections
+//!
+//! Code that runs queries can be written once for every async connection (and for the mock connection from the
+//! `testing` feature) with the [`Queryable`] trait:
+//!
+//! ```no_run
+//! use skytable::{error::ClientResult, query, Config, Queryable};
+//!
+//! async fn followers<Q: Queryable>(db: &mut Q, username: &str) -> ClientResult<u64> {
+//!     db.query_parse(&query!("select followers from myspace.mymodel where username = ?", username))
+//!         .await
+//! }
+//!
+//! # async fn run() -> ClientResult<()> {
+//! let mut db = Config::new_default("username", "password").connect_async().await?;
+//! followers(&mut db, "sayan").await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Sync connections block while a query runs, so they don't implement [`Queryable`] (the blocking would stall the async
+//! runtime). The [`BlockingQueryable`] trait has the same methods for sync connections (and the mock connection)
+//! instead:
+//!
+//! ```no_run
+//! use skytable::{error::ClientResult, query, BlockingQueryable, Config};
+//!
+//! fn followers<Q: BlockingQueryable>(db: &mut Q, username: &str) -> ClientResult<u64> {
+//!     db.query_parse(&query!("select followers from myspace.mymodel where username = ?", username))
+//! }
+//!
+//! # fn run() -> ClientResult<()> {
+//! let mut db = Config::new_default("username", "password").connect()?;
+//! followers(&mut db, "sayan")?;
+//! # Ok(())
+//! # }
+//! ```
+
+use {
+    crate::{
+        aio,
+        error::ClientResult,
+        query::Pipeline,
+        response::{FromResponse, Response},
+        syncio, Query,
+    },
+    std::io::{Read, Write},
+    tokio::io::{AsyncReadExt, AsyncWriteExt},
+};
+
+#[async_trait::async_trait]
+/// An async connection that can run queries (see the [module level docs](self))
+///
+/// Only [`Queryable::query`] and [`Queryable::execute_pipeline`] need to be implemented; the other methods behave like the
+/// connections' own methods with the same name
+pub trait Queryable: Send {
+    /// Run a query and return a raw [`Response`]
+    async fn query(&mut self, q: &Query) -> ClientResult<Response>;
+    /// Execute a pipeline, returning one [`Response`] for every query in it
+    async fn execute_pipeline(&mut self, pipeline: &Pipeline) -> ClientResult<Vec<Response>>;
+    /// Run and parse a query into the indicated type. The type must implement [`FromResponse`]
+    async fn query_parse<T: FromResponse>(&mut self, q: &Query) -> ClientResult<T> {
+        self.query(q).await.and_then(FromResponse::from_response)
+    }
+    /// Run a query, only checking that it succeeded
+    async fn exec(&mut self, q: &Query) -> ClientResult<()> {
+        self.query(q)
+            .await
+            .and_then(Response::into_affected)
+            .map(|_| ())
+    }
+    /// Run a write query (`insert`, `update` or `delete`) and return the number of rows that it changed
+    async fn query_affected(&mut self, q: &Query) -> ClientResult<u64> {
+        self.query(q).await.and_then(Response::into_affected_count)
+    }
+    /// Run a query that selects at most one row, returning `Ok(None)` if no row matches
+    async fn query_row_opt<T: FromResponse>(&mut self, q: &Query) -> ClientResult<Option<T>> {
+        self.query(q).await.and_then(Response::into_row_opt)
+    }
+}
+
+/// A sync connection that can run queries, blocking until they complete (see the [module level docs](self))
+///
+/// This has the same methods as [`Queryable`], and again only [`BlockingQueryable::query`] and
+/// [`BlockingQueryable::execute_pipeline`] need to be implemented
+pub trait BlockingQueryable {
+    /// Run a query and return a raw [`Response`]
+    fn query(&mut self, q: &Query) -> ClientResult<Response>;
+    /// Execute a pipeline, returning one [`Response`] for every query in it
+    fn execute_pipeline(&mut self, pipeline: &Pipeline) -> ClientResult<Vec<Response>>;
+    /// Run and parse a query into the indicated type. The type must implement [`FromResponse`]
+    fn query_parse<T: FromResponse>(&mut self, q: &Query) -> ClientResult<T> {
+        self.query(q).and_then(FromResponse::from_response)
+    }
+    /// Run a query, only checking that it succeeded
+    fn exec(&mut self, q: &Query) -> ClientResult<()> {
+        self.query(q).and_then(Response::into_affected).map(|_| ())
+    }
+    /// Run a write query (`insert`, `update` or `delete`) and return the number of rows that it changed
+    fn query_affected(&mut self, q: &Query) -> ClientResult<u64> {
+        self.query(q).and_then(Response::into_affected_count)
+    }
+    /// Run a query that selects at most one row, returning `Ok(None)` if no row matches
+    fn query_row_opt<T: FromResponse>(&mut self, q: &Query) -> ClientResult<Option<T>> {
+        self.query(q).and_then(Response::into_row_opt)
+    }
+}
+
+impl<C: Read + Write> BlockingQueryable for syncio::TcpConnection<C> {
+    fn query(&mut self, q: &Query) -> ClientResult<Response> {
+        syncio::TcpConnection::query(self, q)
+    }
+    fn execute_pipeline(&mut self, pipeline: &Pipeline) -> ClientResult<Vec<Response>> {
+        syncio::TcpConnection::execute_pipeline(self, pipeline)
+    }
+}
+
+#[async_trait::async_trait]
+impl<C: AsyncWriteExt + AsyncReadExt + Unpin + Send> Queryable for aio::TcpConnection<C> {
+    async fn query(&mut self, q: &Query) -> ClientResult<Response> {
+        aio::TcpConnection::query(self, q).await
+    }
+    async fn execute_pipeline(&mut self, pipeline: &Pipeline) -> ClientResult<Vec<Response>> {
+        aio::TcpConnection::execute_pipeline(self, pipeline).await
+    }
+}
+
+/// The connection types are wrappers that deref to a `TcpConnection`, so they just forward to it
+macro_rules! impl_queryable_deref {
+    ($($(#[$attr:meta])* $ty:ty),* $(,)?) => {
+        $(
+            $(#[$attr])*
+            #[async_trait::async_trait]
+            impl Queryable for $ty {
+                async fn query(&mut self, q: &Query) -> ClientResult<Response> {
+                    Queryable::query(&mut **self, q).await
+                }
+                async fn execute_pipeline(&mut self, pipeline: &Pipeline) -> ClientResult<Vec<Response>> {
+                    Queryable::execute_pipeline(&mut **self, pipeline).await
+                }
+            }
+        )*
+    };
+}
+
+impl_queryable_deref!(
+    aio::ConnectionAsync,
+    aio::ConnectionTlsAsync,
+    #[cfg(feature = "async-std")]
+    aio::ConnectionAsyncStd,
+);
+
+macro_rules! impl_blocking_queryable_deref {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl BlockingQueryable for $ty {
+                fn query(&mut self, q: &Query) -> ClientResult<Response> {
+                    BlockingQueryable::query(&mut **self, q)
+                }
+                fn execute_pipeline(&mut self, pipeline: &Pipeline) -> ClientResult<Vec<Response>> {
+                    BlockingQueryable::execute_pipeline(&mut **self, pipeline)
+                }
+            }
+        )*
+    };
+}
+
+impl_blocking_queryable_deref!(syncio::Connection, syncio::ConnectionTls);
+
+#[cfg(feature = "testing")]
+#[async_trait::async_trait]
+impl Queryable for crate::testing::MockConnection {
+    async fn query(&mut self, q: &Query) -> ClientResult<Response> {
+        crate::testing::MockConnection::query(self, q)
+    }
+    async fn execute_pipeline(&mut self, pipeline: &Pipeline) -> ClientResult<Vec<Response>> {
+        crate::testing::MockConnection::execute_pipeline(self, pipeline)
+    }
+}
+
+#[cfg(feature = "testing")]
+impl BlockingQueryable for crate::testing::MockConnection {
+    fn query(&mut self, q: &Query) -> ClientResult<Response> {
+        crate::testing::MockConnection::query(self, q)
+    }
+    fn execute_pipeline(&mut self, pipeline: &Pipeline) -> ClientResult<Vec<Response>> {
+        crate::testing::MockConnection::execute_pipeline(self, pipeline)
+    }
+}
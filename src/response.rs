@@ -32,7 +32,29 @@
 //! ```
 //!
 
-use crate::error::{ClientResult, Error, ParseError};
+#[cfg(feature = "serde")]
+pub use crate::de::{RowDeserializer, ValueDeserializer};
+use {
+    crate::{
+        error::{ClientResult, Error, ParseError, ERR_OBJECT_ALREADY_EXISTS, ERR_ROW_NOT_FOUND},
+        json::Json,
+        query::{ident, Blob},
+    },
+    std::{
+        collections::{BTreeMap, HashMap},
+        convert::TryFrom,
+        fmt,
+        hash::Hash,
+        io,
+        marker::PhantomData,
+        num::{
+            NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroU16, NonZeroU32, NonZeroU64,
+            NonZeroU8,
+        },
+        str::FromStr,
+        time::{Duration, SystemTime, UNIX_EPOCH},
+    },
+};
 
 /// The value directly returned by the server without any additional type parsing and/or casting
 #[derive(Debug, PartialEq, Clone)]
@@ -76,6 +98,30 @@ impl FromValue for Value {
 }
 
 impl Value {
+    /// Create a [`Value::String`]
+    pub fn str(s: impl Into<String>) -> Self {
+        Self::String(s.into())
+    }
+    /// Create a [`Value::UInt64`]
+    pub fn uint(u: u64) -> Self {
+        Self::UInt64(u)
+    }
+    /// Create a [`Value::SInt64`]
+    pub fn sint(s: i64) -> Self {
+        Self::SInt64(s)
+    }
+    /// Create a [`Value::Float64`]
+    pub fn float(f: f64) -> Self {
+        Self::Float64(f)
+    }
+    /// Create a [`Value::Binary`]
+    pub fn binary(b: impl Into<Vec<u8>>) -> Self {
+        Self::Binary(b.into())
+    }
+    /// Create a [`Value::List`]
+    pub fn list(values: impl IntoIterator<Item = Value>) -> Self {
+        Self::List(values.into_iter().collect())
+    }
     /// Attempt to parse this value into a different type
     pub fn parse<T: FromValue>(self) -> ClientResult<T> {
         T::from_value(self)
@@ -84,6 +130,177 @@ impl Value {
     pub fn parse_cloned<T: FromValue>(&self) -> ClientResult<T> {
         T::from_value(self.clone())
     }
+    /// Consume this value and return it as a [`u64`]. Any unsigned integer is accepted, since the smaller widths convert
+    /// losslessly; everything else is a [`ParseError::TypeMismatchGot`]
+    ///
+    /// ```
+    /// use skytable::{error::{Error, ParseError}, response::Value};
+    ///
+    /// assert_eq!(Value::UInt8(7).into_u64().unwrap(), 7);
+    /// assert_eq!(Value::uint(u64::MAX).into_u64().unwrap(), u64::MAX);
+    /// assert!(matches!(
+    ///     Value::sint(-1).into_u64(),
+    ///     Err(Error::ParseError(ParseError::TypeMismatchGot { .. }))
+    /// ));
+    /// ```
+    pub fn into_u64(self) -> ClientResult<u64> {
+        match self {
+            Self::UInt8(u) => Ok(u as u64),
+            Self::UInt16(u) => Ok(u as u64),
+            Self::UInt32(u) => Ok(u as u64),
+            Self::UInt64(u) => Ok(u),
+            v => Err(type_mismatch("u64", v)),
+        }
+    }
+    /// Consume this value and return it as a [`String`]. This follows the same rules as [`FromValue`] for [`String`], so
+    /// numbers and booleans are formatted, while null, binary data and lists are a [`ParseError::TypeMismatchGot`]
+    ///
+    /// ```
+    /// use skytable::{error::{Error, ParseError}, response::Value};
+    ///
+    /// assert_eq!(Value::str("sayan").into_string().unwrap(), "sayan");
+    /// assert_eq!(Value::uint(42).into_string().unwrap(), "42");
+    /// assert!(matches!(
+    ///     Value::Null.into_string(),
+    ///     Err(Error::ParseError(ParseError::TypeMismatchGot { .. }))
+    /// ));
+    /// ```
+    pub fn into_string(self) -> ClientResult<String> {
+        String::from_value(self)
+    }
+    /// Consume this value and return its binary data. Anything other than a [`Value::Binary`] is a
+    /// [`ParseError::TypeMismatchGot`]
+    ///
+    /// ```
+    /// use skytable::{error::{Error, ParseError}, response::Value};
+    ///
+    /// assert_eq!(Value::binary(b"\x00\xff".to_vec()).into_bytes().unwrap(), [0x00, 0xff]);
+    /// assert!(matches!(
+    ///     Value::str("not binary").into_bytes(),
+    ///     Err(Error::ParseError(ParseError::TypeMismatchGot { .. }))
+    /// ));
+    /// ```
+    pub fn into_bytes(self) -> ClientResult<Vec<u8>> {
+        Vec::from_value(self)
+    }
+    /// Consume this value and return it as a [`bool`]. This follows the same rules as [`FromValue`] for [`bool`], so an
+    /// unsigned integer that is `0` or `1` is accepted, while anything other than a [`Value::Bool`] or an unsigned
+    /// integer is a [`ParseError::TypeMismatchGot`]
+    ///
+    /// ```
+    /// use skytable::{error::{Error, ParseError}, response::Value};
+    ///
+    /// assert!(Value::Bool(true).into_bool().unwrap());
+    /// assert!(Value::uint(1).into_bool().unwrap());
+    /// assert!(matches!(
+    ///     Value::str("true").into_bool(),
+    ///     Err(Error::ParseError(ParseError::TypeMismatchGot { .. }))
+    /// ));
+    /// ```
+    pub fn into_bool(self) -> ClientResult<bool> {
+        bool::from_value(self)
+    }
+    /// Attempt to read this value as a [`String`] (see [`FromValue`] for [`String`]), but also accept binary data,
+    /// replacing any invalid UTF-8 sequences with `U+FFFD`
+    pub fn into_string_lossy(self) -> ClientResult<String> {
+        match self {
+            Self::Binary(b) => Ok(String::from_utf8_lossy(&b).into_owned()),
+            v => String::from_value(v),
+        }
+    }
+}
+
+/// Formats the value like a literal: strings are quoted and escaped, binary data is written as `0x` followed by hex digits
+/// and lists are written in brackets
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Null => f.write_str("null"),
+            Self::Bool(b) => write!(f, "{b}"),
+            Self::UInt8(u) => write!(f, "{u}"),
+            Self::UInt16(u) => write!(f, "{u}"),
+            Self::UInt32(u) => write!(f, "{u}"),
+            Self::UInt64(u) => write!(f, "{u}"),
+            Self::SInt8(s) => write!(f, "{s}"),
+            Self::SInt16(s) => write!(f, "{s}"),
+            Self::SInt32(s) => write!(f, "{s}"),
+            Self::SInt64(s) => write!(f, "{s}"),
+            Self::Float32(fl) => write!(f, "{fl:?}"),
+            Self::Float64(fl) => write!(f, "{fl:?}"),
+            Self::Binary(b) => {
+                f.write_str("0x")?;
+                b.iter().try_for_each(|byte| write!(f, "{byte:02x}"))
+            }
+            Self::String(s) => write!(f, "{s:?}"),
+            Self::List(l) => {
+                f.write_str("[")?;
+                write_separated(f, l)?;
+                f.write_str("]")
+            }
+        }
+    }
+}
+
+impl Value {
+    /// Write this value to `w`, formatted exactly like its [`Display`](fmt::Display) implementation. Integers are formatted
+    /// with `itoa` and nothing is collected into an intermediate [`String`], so this is the cheaper way to stream a large
+    /// number of values to an output (like a file or `stdout`)
+    ///
+    /// ## Example
+    /// ```
+    /// use skytable::response::Value;
+    ///
+    /// let mut out = vec![];
+    /// Value::List(vec![Value::UInt64(1), Value::str("two")]).write_to(&mut out).unwrap();
+    /// assert_eq!(out, b"[1, \"two\"]");
+    /// ```
+    pub fn write_to<W: io::Write>(&self, w: &mut W) -> io::Result<()> {
+        const HEX: &[u8; 16] = b"0123456789abcdef";
+        let mut int = itoa::Buffer::new();
+        match self {
+            Self::Null => w.write_all(b"null"),
+            Self::Bool(true) => w.write_all(b"true"),
+            Self::Bool(false) => w.write_all(b"false"),
+            Self::UInt8(u) => w.write_all(int.format(*u).as_bytes()),
+            Self::UInt16(u) => w.write_all(int.format(*u).as_bytes()),
+            Self::UInt32(u) => w.write_all(int.format(*u).as_bytes()),
+            Self::UInt64(u) => w.write_all(int.format(*u).as_bytes()),
+            Self::SInt8(s) => w.write_all(int.format(*s).as_bytes()),
+            Self::SInt16(s) => w.write_all(int.format(*s).as_bytes()),
+            Self::SInt32(s) => w.write_all(int.format(*s).as_bytes()),
+            Self::SInt64(s) => w.write_all(int.format(*s).as_bytes()),
+            // `write!` formats straight into the writer, so these don't allocate either
+            Self::Float32(fl) => write!(w, "{fl:?}"),
+            Self::Float64(fl) => write!(w, "{fl:?}"),
+            Self::String(s) => write!(w, "{s:?}"),
+            Self::Binary(b) => {
+                w.write_all(b"0x")?;
+                b.iter().try_for_each(|byte| {
+                    w.write_all(&[HEX[(byte >> 4) as usize], HEX[(byte & 0x0f) as usize]])
+                })
+            }
+            Self::List(l) => {
+                w.write_all(b"[")?;
+                for (i, v) in l.iter().enumerate() {
+                    if i != 0 {
+                        w.write_all(b", ")?;
+                    }
+                    v.write_to(w)?;
+                }
+                w.write_all(b"]")
+            }
+        }
+    }
+}
+
+fn write_separated(f: &mut fmt::Formatter<'_>, values: &[Value]) -> fmt::Result {
+    for (i, v) in values.iter().enumerate() {
+        if i != 0 {
+            f.write_str(", ")?;
+        }
+        write!(f, "{v}")?;
+    }
+    Ok(())
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -93,17 +310,68 @@ pub struct Row {
 }
 
 impl Row {
-    pub(crate) fn new(values: Vec<Value>) -> Self {
+    /// Create a new [`Row`] from the given values
+    pub fn new(values: Vec<Value>) -> Self {
         Self { values }
     }
     /// Get a slice of the values in this [`Row`]
     pub fn values(&self) -> &[Value] {
         &self.values
     }
+    /// Returns the value of the column at `index`, or `None` if the row has fewer columns
+    ///
+    /// ## Example
+    /// ```
+    /// use skytable::response::{Row, Value};
+    ///
+    /// let row = Row::new(vec![Value::str("sayan"), Value::UInt8(20)]);
+    /// assert_eq!(row.value(1), Some(&Value::UInt8(20)));
+    /// assert_eq!(row.value(2), None);
+    /// ```
+    pub fn value(&self, index: usize) -> Option<&Value> {
+        self.values.get(index)
+    }
+    /// Returns an iterator over the values in this [`Row`], in column order
+    pub fn iter(&self) -> std::slice::Iter<'_, Value> {
+        self.values.iter()
+    }
+    /// Returns the number of columns in this [`Row`]
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+    /// Returns true if this [`Row`] has no columns
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
     /// Consume the [`Row`], returning a vector of the [`Value`]s in this row
     pub fn into_values(self) -> Vec<Value> {
         self.values
     }
+    #[cfg(feature = "serde")]
+    /// Deserialize this row into any type that implements [`serde::Deserialize`], filling in a struct's fields (or a
+    /// tuple's elements) from the columns, in order. The number of columns must match the number of fields. This is
+    /// available with the `serde` feature
+    ///
+    /// ## Example
+    /// ```
+    /// use serde::Deserialize;
+    /// use skytable::response::{Row, Value};
+    ///
+    /// #[derive(Deserialize)]
+    /// struct User {
+    ///     username: String,
+    ///     age: u8,
+    ///     email: Option<String>,
+    /// }
+    ///
+    /// let row = Row::new(vec![Value::str("sayan"), Value::UInt8(20), Value::Null]);
+    /// let user: User = row.deserialize().unwrap();
+    /// assert_eq!(user.username, "sayan");
+    /// assert_eq!(user.email, None);
+    /// ```
+    pub fn deserialize<T: serde::de::DeserializeOwned>(self) -> ClientResult<T> {
+        T::deserialize(crate::de::RowDeserializer::new(self))
+    }
     /// Returns the first [`Value`] in the [`Row`] if present
     pub fn into_first(mut self) -> ClientResult<Value> {
         if self.values.is_empty() {
@@ -118,6 +386,14 @@ impl Row {
     }
 }
 
+impl<'a> IntoIterator for &'a Row {
+    type Item = &'a Value;
+    type IntoIter = std::slice::Iter<'a, Value>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
 #[derive(Debug, PartialEq, Clone)]
 /// A response returned by the server
 pub enum Response {
@@ -130,9 +406,178 @@ pub enum Response {
     /// A list of rows
     Rows(Vec<Row>),
     /// The server returned an error code
+    ///
+    /// Skyhash 2.0 error responses consist of the code alone; the server doesn't send a message with it.
     Error(u16),
 }
 
+/// Formats the row as its values in parentheses, like `("sayan", 100)`
+impl fmt::Display for Row {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("(")?;
+        write_separated(f, &self.values)?;
+        f.write_str(")")
+    }
+}
+
+/// Formats the response for humans: a value or row as itself (see [`Value`] and [`Row`]), each row of a
+/// [`Response::Rows`] on its own line, and `(empty)` or `error <code>` otherwise
+impl fmt::Display for Response {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => f.write_str("(empty)"),
+            Self::Value(v) => write!(f, "{v}"),
+            Self::Row(r) => write!(f, "{r}"),
+            Self::Rows(rows) => {
+                for (i, row) in rows.iter().enumerate() {
+                    if i != 0 {
+                        f.write_str("\n")?;
+                    }
+                    write!(f, "{row}")?;
+                }
+                Ok(())
+            }
+            Self::Error(e) => write!(f, "error {e}"),
+        }
+    }
+}
+
+impl Response {
+    /// Create a [`Response::Row`] with the given values
+    ///
+    /// ## Example
+    /// ```
+    /// use skytable::response::{Response, Row, Value};
+    ///
+    /// assert_eq!(
+    ///     Response::row([Value::str("sayan"), Value::uint(100)]),
+    ///     Response::Row(Row::new(vec![Value::String("sayan".into()), Value::UInt64(100)]))
+    /// );
+    /// ```
+    pub fn row(values: impl IntoIterator<Item = Value>) -> Self {
+        Self::Row(Row::new(values.into_iter().collect()))
+    }
+    /// Create a [`Response::Rows`], with each item holding the values of a row
+    pub fn rows<R: IntoIterator<Item = Value>>(rows: impl IntoIterator<Item = R>) -> Self {
+        Self::Rows(
+            rows.into_iter()
+                .map(|row| Row::new(row.into_iter().collect()))
+                .collect(),
+        )
+    }
+    /// Lazily parse every row in a [`Response::Rows`] into `T`, without collecting the parsed rows
+    ///
+    /// For any other response, the iterator yields a single error: the server error for a [`Response::Error`], or a
+    /// [`ParseError::ResponseMismatch`] otherwise
+    ///
+    /// ## Example
+    /// ```no_run
+    /// use skytable::{query, Config, Response};
+    ///
+    /// #[derive(Response)]
+    /// struct User {
+    ///     username: String,
+    ///     followers: u64,
+    /// }
+    ///
+    /// let mut db = Config::new_default("username", "password").connect().unwrap();
+    /// let resp = db.query(&query!("select all * from myspace.mymodel limit ?", 1000u64)).unwrap();
+    /// for user in resp.rows_as::<User>() {
+    ///     let user = user.unwrap();
+    ///     println!("{} has {} followers", user.username, user.followers);
+    /// }
+    /// ```
+    pub fn rows_as<T: FromResponse>(self) -> impl Iterator<Item = ClientResult<T>> {
+        let (rows, err) = match self {
+            Self::Rows(rows) => (rows, None),
+            Self::Error(e) => (vec![], Some(Error::ServerError(e))),
+            Self::Empty | Self::Value(_) | Self::Row(_) => (
+                vec![],
+                Some(Error::ParseError(ParseError::ResponseMismatch)),
+            ),
+        };
+        rows.into_iter()
+            .map(|row| T::from_response(Self::Row(row)))
+            .chain(err.map(Err))
+    }
+    /// Returns the number of affected rows if the server returned an unsigned integer, `None` for any other successful
+    /// response, or the server error
+    pub(crate) fn into_affected(self) -> ClientResult<Option<u64>> {
+        match self {
+            Self::Error(e) => Err(Error::ServerError(e)),
+            Self::Value(Value::UInt8(n)) => Ok(Some(n as u64)),
+            Self::Value(Value::UInt16(n)) => Ok(Some(n as u64)),
+            Self::Value(Value::UInt32(n)) => Ok(Some(n as u64)),
+            Self::Value(Value::UInt64(n)) => Ok(Some(n)),
+            _ => Ok(None),
+        }
+    }
+    /// Parses the response, returning `None` if the server reported that the row doesn't exist
+    pub(crate) fn into_row_opt<T: FromResponse>(self) -> ClientResult<Option<T>> {
+        match self {
+            Self::Error(ERR_ROW_NOT_FOUND) => Ok(None),
+            resp => T::from_response(resp).map(Some),
+        }
+    }
+    /// Returns the number of affected rows, counting [`Response::Empty`] as a single row
+    pub(crate) fn into_affected_count(self) -> ClientResult<u64> {
+        match self {
+            Self::Empty => Ok(1),
+            resp => resp
+                .into_affected()?
+                .ok_or(Error::ParseError(ParseError::ResponseMismatch)),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+/// A builder for a [`Response::Rows`], for mocking the response to a query that returns multiple rows (for example, with a
+/// `MockConnection` from the `testing` feature)
+///
+/// ## Example
+/// ```
+/// use skytable::response::{Response, ResponseBuilder, Row, Value};
+///
+/// let resp = ResponseBuilder::new()
+///     .row([Value::str("sayan"), Value::uint(100)])
+///     .row([Value::str("elana"), Value::uint(200)])
+///     .build();
+/// assert_eq!(
+///     resp,
+///     Response::Rows(vec![
+///         Row::new(vec![Value::String("sayan".into()), Value::UInt64(100)]),
+///         Row::new(vec![Value::String("elana".into()), Value::UInt64(200)]),
+///     ])
+/// );
+/// ```
+pub struct ResponseBuilder {
+    rows: Vec<Row>,
+}
+
+impl ResponseBuilder {
+    /// Create a new builder with no rows
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Add a row with the given values
+    pub fn row(mut self, values: impl IntoIterator<Item = Value>) -> Self {
+        self.rows.push(Row::new(values.into_iter().collect()));
+        self
+    }
+    /// Returns the number of rows added so far
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+    /// Returns true if no rows were added
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+    /// Build the [`Response::Rows`]
+    pub fn build(self) -> Response {
+        Response::Rows(self.rows)
+    }
+}
+
 /*
     Response traits
 */
@@ -169,6 +614,28 @@ pub enum Response {
 /// ).unwrap();
 /// assert_eq!(myuser.username, "bob");
 /// ```
+///
+/// ## Using `TryFrom`
+///
+/// The types in this crate that implement [`FromResponse`] (and types that use `#[derive(Response)]`) also implement
+/// [`TryFrom<Response>`](TryFrom), so you can use `try_into` instead. Similarly, the types that implement [`FromValue`]
+/// also implement [`TryFrom<Value>`](TryFrom):
+///
+/// ```
+/// use {
+///     skytable::{response::{Response, Value}, ClientResult},
+///     std::convert::TryInto,
+/// };
+///
+/// fn parse(resp: Response) -> ClientResult<u64> {
+///     let followers: u64 = resp.try_into()?;
+///     Ok(followers)
+/// }
+///
+/// assert_eq!(parse(Response::Value(Value::UInt64(100))).unwrap(), 100);
+/// let username: String = Value::String("sayan".into()).try_into().unwrap();
+/// assert_eq!(username, "sayan");
+/// ```
 pub trait FromResponse: Sized {
     /// Decode the target type from the [`Response`]
     fn from_response(resp: Response) -> ClientResult<Self>;
@@ -188,16 +655,27 @@ impl FromResponse for () {
 pub trait FromValue: Sized {
     /// Attempt to use the value to create an instance of `Self` or throw an error
     fn from_value(v: Value) -> ClientResult<Self>;
+    #[doc(hidden)]
+    /// Called by [`FromResponse`] when the response is not a [`Response::Value`]
+    fn from_non_value_response(resp: Response) -> ClientResult<Self> {
+        match resp {
+            Response::Error(e) => Err(Error::ServerError(e)),
+            _ => Err(Error::ParseError(ParseError::ResponseMismatch)),
+        }
+    }
 }
 
+/// A single value is read from a [`Response::Value`], but also from a row that has exactly one column, which is how the
+/// server returns a single selected column or the result of an aggregate (so `query_parse::<u64>` works for a count)
 impl<V: FromValue> FromResponse for V {
     fn from_response(resp: Response) -> ClientResult<Self> {
         match resp {
             Response::Value(v) => V::from_value(v),
-            Response::Row(_) | Response::Empty | Response::Rows(_) => {
-                Err(Error::ParseError(ParseError::ResponseMismatch))
+            // a single column selected from a single row
+            Response::Row(Row { mut values }) if values.len() == 1 => {
+                V::from_value(values.remove(0))
             }
-            Response::Error(e) => Err(Error::ServerError(e)),
+            resp => V::from_non_value_response(resp),
         }
     }
 }
@@ -206,26 +684,66 @@ impl<V: FromValue> FromValue for Option<V> {
     fn from_value(v: Value) -> ClientResult<Self> {
         match v {
             Value::Null => Ok(None),
-            v => FromValue::from_value(v),
+            v => V::from_value(v).map(Some),
         }
     }
 }
 
+/// Returned by the [`FromValue`] implementations when the value has the wrong type, keeping the value so that the error
+/// can say what was actually received
+fn type_mismatch(expected: &'static str, got: Value) -> Error {
+    Error::ParseError(ParseError::TypeMismatchGot { expected, got })
+}
+
 macro_rules! from_response_direct {
     ($($ty:ty as $var:ident),* $(,)?) => {
         $(impl FromValue for $ty {
             fn from_value(v: Value) -> ClientResult<Self> {
                 match v {
                     Value::$var(capture) => Ok(From::from(capture)),
-                    _ => Err(Error::ParseError(ParseError::TypeMismatch)),
+                    v => Err(type_mismatch(stringify!($ty), v)),
                 }
             }
         })*
     }
 }
 
+/// Apart from a [`Value::Bool`], a [`bool`] can be parsed from an unsigned integer that is `0` (`false`) or `1` (`true`),
+/// for schemas that store booleans as a `uint8`. Any other integer is a [`ParseError::Other`]
+///
+/// A [`bool`] can also be parsed from the responses to DDL queries like `create space`:
+/// - [`Response::Empty`] (the object was created) is `true`
+/// - [`Response::Error`] with [`ERR_OBJECT_ALREADY_EXISTS`] (the object already exists) is `false`
+/// - all other errors are returned as is
+impl FromValue for bool {
+    fn from_value(v: Value) -> ClientResult<Self> {
+        match v {
+            Value::Bool(b) => Ok(b),
+            v @ (Value::UInt8(_) | Value::UInt16(_) | Value::UInt32(_) | Value::UInt64(_)) => {
+                match v.into_u64()? {
+                    0 => Ok(false),
+                    1 => Ok(true),
+                    n => Err(Error::ParseError(ParseError::Other(format!(
+                        "expected 0 or 1 for a bool, got {n}"
+                    )))),
+                }
+            }
+            v => Err(type_mismatch("bool", v)),
+        }
+    }
+    fn from_non_value_response(resp: Response) -> ClientResult<Self> {
+        match resp {
+            Response::Empty => Ok(true),
+            Response::Error(ERR_OBJECT_ALREADY_EXISTS) => Ok(false),
+            Response::Error(e) => Err(Error::ServerError(e)),
+            Response::Value(_) | Response::Row(_) | Response::Rows(_) => {
+                Err(Error::ParseError(ParseError::ResponseMismatch))
+            }
+        }
+    }
+}
+
 from_response_direct!(
-    bool as Bool,
     u8 as UInt8,
     u16 as UInt16,
     u32 as UInt32,
@@ -234,15 +752,216 @@ from_response_direct!(
     i16 as SInt16,
     i32 as SInt32,
     i64 as SInt64,
-    f32 as Float32,
-    f64 as Float64,
     Vec<u8> as Binary,
     Box<[u8]> as Binary,
-    String as String,
+    Blob as Binary,
     Box<str> as String,
     Vec<Value> as List,
 );
 
+/// This also accepts a [`Value::Float64`], which is narrowed with an `as` cast and hence may lose precision. Finite values
+/// outside the finite range of an [`f32`] are rejected instead of being turned into an infinity
+impl FromValue for f32 {
+    fn from_value(v: Value) -> ClientResult<Self> {
+        match v {
+            Value::Float32(f) => Ok(f),
+            Value::Float64(f) if f.is_finite() && f.abs() > f32::MAX as f64 => {
+                Err(Error::ParseError(ParseError::Other(format!(
+                    "float64 value {f} is out of range for f32"
+                ))))
+            }
+            Value::Float64(f) => Ok(f as f32),
+            v => Err(type_mismatch("f32", v)),
+        }
+    }
+}
+
+/// This also accepts a [`Value::Float32`], which is widened losslessly
+impl FromValue for f64 {
+    fn from_value(v: Value) -> ClientResult<Self> {
+        match v {
+            Value::Float64(f) => Ok(f),
+            Value::Float32(f) => Ok(f as f64),
+            v => Err(type_mismatch("f64", v)),
+        }
+    }
+}
+
+/// Apart from [`Value::String`], this also accepts integers, floats and booleans by formatting them. For binary data,
+/// see [`Value::into_string_lossy`]
+impl FromValue for String {
+    fn from_value(v: Value) -> ClientResult<Self> {
+        match v {
+            Value::String(s) => Ok(s),
+            Value::Bool(b) => Ok(b.to_string()),
+            Value::UInt8(u) => Ok(u.to_string()),
+            Value::UInt16(u) => Ok(u.to_string()),
+            Value::UInt32(u) => Ok(u.to_string()),
+            Value::UInt64(u) => Ok(u.to_string()),
+            Value::SInt8(s) => Ok(s.to_string()),
+            Value::SInt16(s) => Ok(s.to_string()),
+            Value::SInt32(s) => Ok(s.to_string()),
+            Value::SInt64(s) => Ok(s.to_string()),
+            Value::Float32(f) => Ok(f.to_string()),
+            Value::Float64(f) => Ok(f.to_string()),
+            v @ (Value::Null | Value::Binary(_) | Value::List(_)) => {
+                Err(type_mismatch("String", v))
+            }
+        }
+    }
+}
+
+/// Accepts a [`Value::String`] holding exactly one character, or a [`Value::UInt8`] or [`Value::UInt32`] holding a Unicode
+/// scalar value
+impl FromValue for char {
+    fn from_value(v: Value) -> ClientResult<Self> {
+        match v {
+            Value::String(s) => {
+                let mut chars = s.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => Ok(c),
+                    _ => Err(Error::ParseError(ParseError::Other(format!(
+                        "expected a single character, got {s:?}"
+                    )))),
+                }
+            }
+            Value::UInt8(u) => Ok(u.into()),
+            Value::UInt32(u) => char::from_u32(u).ok_or_else(|| {
+                Error::ParseError(ParseError::Other(format!(
+                    "{u:#x} is not a valid unicode scalar value"
+                )))
+            }),
+            v => Err(type_mismatch("char", v)),
+        }
+    }
+}
+
+macro_rules! from_value_non_zero {
+    ($($ty:ty as $int:ty),* $(,)?) => {
+        $(
+            /// Parses the underlying integer, failing with [`ParseError::Other`] if it's zero
+            impl FromValue for $ty {
+                fn from_value(v: Value) -> ClientResult<Self> {
+                    <$ty>::new(<$int>::from_value(v)?).ok_or_else(|| {
+                        Error::ParseError(ParseError::Other("unexpected zero".into()))
+                    })
+                }
+            }
+        )*
+    };
+}
+
+from_value_non_zero!(
+    NonZeroU8 as u8,
+    NonZeroU16 as u16,
+    NonZeroU32 as u32,
+    NonZeroU64 as u64,
+    NonZeroI8 as i8,
+    NonZeroI16 as i16,
+    NonZeroI32 as i32,
+    NonZeroI64 as i64,
+);
+
+macro_rules! from_value_wide_int {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            /// Parses the decimal string that this type is stored as (see its [`SQParam`](crate::query::SQParam)
+            /// implementation), and also accepts any integer value that fits
+            impl FromValue for $ty {
+                fn from_value(v: Value) -> ClientResult<Self> {
+                    let out_of_range = |v: &dyn fmt::Display| {
+                        Error::ParseError(ParseError::Other(format!(
+                            "{v} is out of range for {}",
+                            stringify!($ty)
+                        )))
+                    };
+                    match v {
+                        Value::String(s) => s.parse().map_err(|e| {
+                            Error::ParseError(ParseError::Other(format!(
+                                "invalid {} `{s}`: {e}",
+                                stringify!($ty)
+                            )))
+                        }),
+                        Value::UInt8(u) => Ok(u.into()),
+                        Value::UInt16(u) => Ok(u.into()),
+                        Value::UInt32(u) => Ok(u.into()),
+                        Value::UInt64(u) => Ok(u.into()),
+                        Value::SInt8(s) => <$ty>::try_from(s).map_err(|_| out_of_range(&s)),
+                        Value::SInt16(s) => <$ty>::try_from(s).map_err(|_| out_of_range(&s)),
+                        Value::SInt32(s) => <$ty>::try_from(s).map_err(|_| out_of_range(&s)),
+                        Value::SInt64(s) => <$ty>::try_from(s).map_err(|_| out_of_range(&s)),
+                        v => Err(type_mismatch(stringify!($ty), v)),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+from_value_wide_int!(u128, i128);
+
+/// Reads a [`Value::UInt64`] as a number of seconds
+impl FromValue for Duration {
+    fn from_value(v: Value) -> ClientResult<Self> {
+        match v {
+            Value::UInt64(secs) => Ok(Duration::from_secs(secs)),
+            v => Err(type_mismatch("Duration", v)),
+        }
+    }
+}
+
+/// Reads a UNIX timestamp in seconds. A negative [`Value::SInt64`] (a time before the UNIX epoch) and a timestamp that the
+/// platform's [`SystemTime`] can't represent are both errors
+impl FromValue for SystemTime {
+    fn from_value(v: Value) -> ClientResult<Self> {
+        let secs = match v {
+            Value::UInt64(secs) => secs,
+            Value::SInt64(secs) if secs >= 0 => secs as u64,
+            Value::SInt64(secs) => {
+                return Err(Error::ParseError(ParseError::Other(format!(
+                    "timestamp {secs} is before the UNIX epoch"
+                ))))
+            }
+            v => return Err(type_mismatch("SystemTime", v)),
+        };
+        UNIX_EPOCH
+            .checked_add(Duration::from_secs(secs))
+            .ok_or_else(|| {
+                Error::ParseError(ParseError::Other(format!(
+                    "timestamp {secs} is out of range"
+                )))
+            })
+    }
+}
+
+#[cfg(feature = "bytes")]
+/// Accepts both [`Value::Binary`] and [`Value::String`]. The decoded buffer is moved into the [`Bytes`](bytes::Bytes) without
+/// copying; the value is still decoded into its own allocation first (and not sliced out of the connection's read buffer),
+/// so the returned `Bytes` never keeps a whole response alive
+impl FromValue for bytes::Bytes {
+    fn from_value(v: Value) -> ClientResult<Self> {
+        match v {
+            Value::Binary(b) => Ok(b.into()),
+            Value::String(s) => Ok(s.into()),
+            v => Err(type_mismatch("Bytes", v)),
+        }
+    }
+}
+
+#[cfg(feature = "rust_decimal")]
+/// Parses a [`Value::String`] holding a decimal (see the [`SQParam`](crate::query::SQParam) implementation for
+/// [`Decimal`](rust_decimal::Decimal))
+impl FromValue for rust_decimal::Decimal {
+    fn from_value(v: Value) -> ClientResult<Self> {
+        match v {
+            Value::String(s) => s.parse().map_err(|e| {
+                Error::ParseError(ParseError::Other(format!("invalid decimal `{s}`: {e}")))
+            }),
+            v => Err(type_mismatch("Decimal", v)),
+        }
+    }
+}
+
 macro_rules! from_response_row {
     ($(($($elem:ident),*) as $size:literal),* $(,)?) => {
         $(
@@ -254,12 +973,18 @@ macro_rules! from_response_row {
                         Response::Error(e) => return Err(Error::ServerError(e)),
                     };
                     if row.len() != $size {
-                        return Err(Error::ParseError(ParseError::TypeMismatch));
+                        return Err(Error::ParseError(ParseError::ColumnCountMismatch { expected: $size, actual: row.len() }));
                     }
                     let mut values = row.into_iter();
                     Ok(($($elem::from_value(values.next().unwrap())?),*,))
                 }
             }
+            impl<$($elem: FromValue),*> TryFrom<Response> for ($($elem),*,) {
+                type Error = Error;
+                fn try_from(resp: Response) -> ClientResult<Self> {
+                    FromResponse::from_response(resp)
+                }
+            }
         )*
     }
 }
@@ -307,3 +1032,1053 @@ impl FromResponse for Row {
         }
     }
 }
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+/// A space or a model, such as the one that a connection is currently using (see `whereami` on the connection types)
+///
+/// Use [`Entity::new`] (or parse a `space.model` string) to name a model in a query string, instead of formatting names
+/// by hand. The names are validated (see [`ident`]), and the entity is displayed as `space.model`
+///
+/// ## Example
+/// ```
+/// use skytable::{query, response::Entity};
+///
+/// let users = Entity::new("myspace", "users").unwrap();
+/// let q = query!(format!("select * from {users} where username = ?"), "sayan");
+/// assert_eq!(q.query_str(), "select * from myspace.users where username = ?");
+/// assert_eq!("myspace.users".parse::<Entity>().unwrap(), users);
+/// assert!(Entity::new("myspace", "users; drop space myspace").is_err());
+/// ```
+pub enum Entity {
+    /// A space
+    Space(String),
+    /// A model, as `(space, model)`
+    Model(String, String),
+}
+
+impl Entity {
+    /// Create a model entity, validating both names
+    pub fn new(space: &str, model: &str) -> ClientResult<Self> {
+        Ok(Self::Model(ident(space)?, ident(model)?))
+    }
+    /// Create a space entity, validating its name
+    pub fn space(space: &str) -> ClientResult<Self> {
+        ident(space).map(Self::Space)
+    }
+    /// Returns the name of the space (for a model, the space that it belongs to)
+    pub fn space_name(&self) -> &str {
+        match self {
+            Self::Space(space) | Self::Model(space, _) => space,
+        }
+    }
+    /// Returns the name of the model, or `None` for a space
+    pub fn model_name(&self) -> Option<&str> {
+        match self {
+            Self::Space(_) => None,
+            Self::Model(_, model) => Some(model),
+        }
+    }
+}
+
+/// Parses `space` or `space.model`, validating the names
+impl FromStr for Entity {
+    type Err = Error;
+    fn from_str(entity: &str) -> ClientResult<Self> {
+        let mut names = entity.split('.');
+        match (names.next(), names.next(), names.next()) {
+            (Some(space), None, None) => Self::space(space),
+            (Some(space), Some(model), None) => Self::new(space, model),
+            _ => Err(Error::InvalidQuery(format!("invalid entity `{entity}`"))),
+        }
+    }
+}
+
+impl fmt::Display for Entity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Space(space) => write!(f, "{space}"),
+            Self::Model(space, model) => write!(f, "{space}.{model}"),
+        }
+    }
+}
+
+/// Accepts either a string value (`space` or `space.model`) or a row with one (`space`) or two (`space`, `model`) string
+/// columns. A null value means that no space is in use, and is an error
+impl FromResponse for Entity {
+    fn from_response(resp: Response) -> ClientResult<Self> {
+        let mut names = match resp {
+            Response::Value(Value::String(entity)) => entity
+                .splitn(2, '.')
+                .map(ToOwned::to_owned)
+                .collect::<Vec<_>>(),
+            Response::Value(Value::Null) => {
+                return Err(Error::ParseError(ParseError::Other(
+                    "no space or model is in use".into(),
+                )))
+            }
+            Response::Row(row) if row.len() <= 2 => row
+                .into_values()
+                .into_iter()
+                .map(String::from_value)
+                .collect::<ClientResult<_>>()?,
+            Response::Row(row) => {
+                return Err(Error::ParseError(ParseError::ColumnCountMismatch {
+                    expected: 2,
+                    actual: row.len(),
+                }))
+            }
+            Response::Error(e) => return Err(Error::ServerError(e)),
+            Response::Empty | Response::Value(_) | Response::Rows(_) => {
+                return Err(Error::ParseError(ParseError::ResponseMismatch))
+            }
+        }
+        .into_iter();
+        match (names.next(), names.next()) {
+            (Some(space), None) => Ok(Self::Space(space)),
+            (Some(space), Some(model)) => Ok(Self::Model(space, model)),
+            (None, _) => Err(Error::ParseError(ParseError::ColumnCountMismatch {
+                expected: 1,
+                actual: 0,
+            })),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+/// The response to `inspect global` (see `inspect_global` on the connection types): the spaces and the users that exist
+/// on the server
+pub struct GlobalInfo {
+    spaces: Vec<String>,
+    users: Vec<String>,
+}
+
+impl GlobalInfo {
+    /// Returns the names of the spaces on the server
+    pub fn spaces(&self) -> &[String] {
+        &self.spaces
+    }
+    /// Returns the names of the users on the server
+    pub fn users(&self) -> &[String] {
+        &self.users
+    }
+}
+
+/// Accepts the JSON object returned by `inspect global`, like `{"spaces":["myspace"],"users":["root"],"settings":{}}`
+impl FromResponse for GlobalInfo {
+    fn from_response(resp: Response) -> ClientResult<Self> {
+        let description = match resp {
+            Response::Value(Value::String(s)) => s,
+            Response::Error(e) => return Err(Error::ServerError(e)),
+            _ => return Err(Error::ParseError(ParseError::ResponseMismatch)),
+        };
+        let info = Json::parse(&description).and_then(|global| {
+            Some(Self {
+                spaces: global.get("spaces")?.as_strings()?,
+                users: global.get("users")?.as_strings()?,
+            })
+        });
+        info.ok_or_else(|| {
+            Error::ParseError(ParseError::Other(format!(
+                "invalid `inspect global` response `{description}`"
+            )))
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+/// A list value whose elements are read as `T`. This is the read-side counterpart of [`QList`](crate::query::QList)
+///
+/// Parsing a [`SkyList`] only checks that the value is a list; each element is converted when you access it, so you can
+/// check the length or stop iterating early without converting the rest. Use [`SkyList::into_vec`] to convert every
+/// element at once.
+///
+/// ## Example
+/// ```
+/// use skytable::response::{FromValue, SkyList, Value};
+///
+/// let list = SkyList::<u64>::from_value(Value::List(vec![Value::UInt64(1), Value::UInt64(2)])).unwrap();
+/// assert_eq!(list.len(), 2);
+/// assert_eq!(list.iter().collect::<Result<Vec<_>, _>>().unwrap(), [1, 2]);
+/// assert_eq!(list.into_vec().unwrap(), [1, 2]);
+/// ```
+pub struct SkyList<T> {
+    values: Vec<Value>,
+    _t: PhantomData<T>,
+}
+
+impl<T: FromValue> SkyList<T> {
+    /// Returns the number of elements in the list
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+    /// Returns true if the list has no elements
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+    /// Returns an iterator that converts (a copy of) each element to `T`
+    pub fn iter(&self) -> impl Iterator<Item = ClientResult<T>> + '_ {
+        self.values.iter().map(Value::parse_cloned)
+    }
+    /// Converts every element to `T`, returning the first error if any element can't be converted
+    pub fn into_vec(self) -> ClientResult<Vec<T>> {
+        self.into_iter().collect()
+    }
+    /// Returns the elements without converting them
+    pub fn into_values(self) -> Vec<Value> {
+        self.values
+    }
+}
+
+impl<T: FromValue> IntoIterator for SkyList<T> {
+    type Item = ClientResult<T>;
+    type IntoIter = std::iter::Map<std::vec::IntoIter<Value>, fn(Value) -> ClientResult<T>>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.values.into_iter().map(T::from_value)
+    }
+}
+
+impl<T: FromValue> FromValue for SkyList<T> {
+    fn from_value(v: Value) -> ClientResult<Self> {
+        match v {
+            Value::List(values) => Ok(Self {
+                values,
+                _t: PhantomData,
+            }),
+            v => Err(type_mismatch("SkyList", v)),
+        }
+    }
+}
+
+/// Consumes a [`Response::Rows`] and yields each row as a `(key, value)` pair, requiring exactly two columns per row
+fn into_kv_rows<K: FromValue, V: FromValue>(
+    resp: Response,
+) -> ClientResult<impl Iterator<Item = ClientResult<(K, V)>>> {
+    let rows = match resp {
+        Response::Rows(rows) => rows,
+        Response::Error(e) => return Err(Error::ServerError(e)),
+        Response::Empty | Response::Value(_) | Response::Row(_) => {
+            return Err(Error::ParseError(ParseError::ResponseMismatch))
+        }
+    };
+    Ok(rows.into_iter().map(|row| {
+        let mut values = row.into_values();
+        if values.len() != 2 {
+            return Err(Error::ParseError(ParseError::ColumnCountMismatch {
+                expected: 2,
+                actual: values.len(),
+            }));
+        }
+        let v = values.pop().unwrap();
+        let k = values.pop().unwrap();
+        Ok((K::from_value(k)?, V::from_value(v)?))
+    }))
+}
+
+/// Collects a two-column [`Response::Rows`] into a map, using the first column as the key and the second as the value.
+/// If a key is repeated, the last row wins.
+impl<K: FromValue + Eq + Hash, V: FromValue> FromResponse for HashMap<K, V> {
+    fn from_response(resp: Response) -> ClientResult<Self> {
+        into_kv_rows(resp)?.collect()
+    }
+}
+
+/// Collects a two-column [`Response::Rows`] into an ordered map, using the first column as the key and the second as the value.
+/// If a key is repeated, the last row wins.
+impl<K: FromValue + Ord, V: FromValue> FromResponse for BTreeMap<K, V> {
+    fn from_response(resp: Response) -> ClientResult<Self> {
+        into_kv_rows(resp)?.collect()
+    }
+}
+
+/// Collects a two-column [`Response::Rows`] into `(first column, second column)` pairs, keeping the order of the rows and
+/// any repeated keys (unlike the map collectors)
+impl<A: FromValue, B: FromValue> FromResponse for Vec<(A, B)> {
+    fn from_response(resp: Response) -> ClientResult<Self> {
+        into_kv_rows(resp)?.collect()
+    }
+}
+
+/*
+    TryFrom
+
+    a blanket impl over `FromResponse` isn't allowed by the orphan rules, so we implement this for the types that we know
+    about (and the `Response` derive implements it for your types)
+*/
+
+macro_rules! impl_try_from_response {
+    ($($(#[$attr:meta])* $ty:ty),* $(,)?) => {
+        $($(#[$attr])* impl TryFrom<Response> for $ty {
+            type Error = Error;
+            fn try_from(resp: Response) -> ClientResult<Self> {
+                FromResponse::from_response(resp)
+            }
+        })*
+    }
+}
+
+macro_rules! impl_try_from_value {
+    ($($(#[$attr:meta])* $ty:ty),* $(,)?) => {
+        $($(#[$attr])* impl TryFrom<Value> for $ty {
+            type Error = Error;
+            fn try_from(v: Value) -> ClientResult<Self> {
+                FromValue::from_value(v)
+            }
+        })*
+        impl_try_from_response!($($(#[$attr])* $ty),*);
+    }
+}
+
+impl_try_from_value!(
+    u8,
+    u16,
+    u32,
+    u64,
+    i8,
+    i16,
+    i32,
+    i64,
+    f32,
+    f64,
+    bool,
+    String,
+    char,
+    Box<str>,
+    Vec<u8>,
+    Box<[u8]>,
+    Blob,
+    Vec<Value>,
+    u128,
+    i128,
+    NonZeroU8,
+    NonZeroU16,
+    NonZeroU32,
+    NonZeroU64,
+    NonZeroI8,
+    NonZeroI16,
+    NonZeroI32,
+    NonZeroI64,
+    Duration,
+    SystemTime,
+    #[cfg(feature = "bytes")]
+    bytes::Bytes,
+    #[cfg(feature = "rust_decimal")]
+    rust_decimal::Decimal,
+);
+
+impl_try_from_response!((), Value, Row, Entity, GlobalInfo);
+
+impl<T: FromValue> TryFrom<Value> for SkyList<T> {
+    type Error = Error;
+    fn try_from(v: Value) -> ClientResult<Self> {
+        FromValue::from_value(v)
+    }
+}
+
+impl<T: FromValue> TryFrom<Response> for SkyList<T> {
+    type Error = Error;
+    fn try_from(resp: Response) -> ClientResult<Self> {
+        FromResponse::from_response(resp)
+    }
+}
+
+impl<A: FromValue, B: FromValue> TryFrom<Response> for Vec<(A, B)> {
+    type Error = Error;
+    fn try_from(resp: Response) -> ClientResult<Self> {
+        FromResponse::from_response(resp)
+    }
+}
+
+impl<K: FromValue + Eq + Hash, V: FromValue> TryFrom<Response> for HashMap<K, V> {
+    type Error = Error;
+    fn try_from(resp: Response) -> ClientResult<Self> {
+        FromResponse::from_response(resp)
+    }
+}
+
+impl<K: FromValue + Ord, V: FromValue> TryFrom<Response> for BTreeMap<K, V> {
+    type Error = Error;
+    fn try_from(resp: Response) -> ClientResult<Self> {
+        FromResponse::from_response(resp)
+    }
+}
+
+#[test]
+fn t_hashmap() {
+    let resp = Response::Rows(vec![
+        Row::new(vec![Value::String("sayan".into()), Value::UInt64(1)]),
+        Row::new(vec![Value::String("elana".into()), Value::UInt64(2)]),
+        Row::new(vec![Value::String("sayan".into()), Value::UInt64(3)]),
+    ]);
+    let map: HashMap<String, u64> = FromResponse::from_response(resp).unwrap();
+    assert_eq!(map.len(), 2);
+    assert_eq!(map["sayan"], 3);
+    assert_eq!(map["elana"], 2);
+}
+
+#[test]
+fn t_hashmap_bad_shape() {
+    let resp = Response::Rows(vec![Row::new(vec![
+        Value::String("sayan".into()),
+        Value::UInt64(1),
+        Value::Bool(true),
+    ])]);
+    assert!(matches!(
+        HashMap::<String, u64>::from_response(resp),
+        Err(Error::ParseError(ParseError::ColumnCountMismatch {
+            expected: 2,
+            actual: 3
+        }))
+    ));
+    assert!(matches!(
+        HashMap::<String, u64>::from_response(Response::Empty),
+        Err(Error::ParseError(ParseError::ResponseMismatch))
+    ));
+}
+
+#[test]
+fn t_btreemap() {
+    let resp = Response::Rows(vec![
+        Row::new(vec![Value::String("zeta".into()), Value::UInt64(1)]),
+        Row::new(vec![Value::String("alpha".into()), Value::UInt64(2)]),
+        Row::new(vec![Value::String("mu".into()), Value::UInt64(3)]),
+        Row::new(vec![Value::String("alpha".into()), Value::UInt64(4)]),
+    ]);
+    let map: BTreeMap<String, u64> = FromResponse::from_response(resp).unwrap();
+    assert_eq!(
+        map.into_iter().collect::<Vec<_>>(),
+        vec![
+            ("alpha".to_owned(), 4),
+            ("mu".to_owned(), 3),
+            ("zeta".to_owned(), 1)
+        ]
+    );
+    assert!(matches!(
+        BTreeMap::<String, u64>::from_response(Response::Rows(vec![Row::new(vec![
+            Value::String("alpha".into())
+        ])])),
+        Err(Error::ParseError(ParseError::ColumnCountMismatch {
+            expected: 2,
+            actual: 1
+        }))
+    ));
+}
+
+#[test]
+fn t_type_mismatch_keeps_value() {
+    match u64::from_value(Value::str("abc")) {
+        Err(Error::ParseError(ParseError::TypeMismatchGot { expected, got })) => {
+            assert_eq!(expected, "u64");
+            assert_eq!(got, Value::str("abc"));
+        }
+        other => panic!("expected a type mismatch, got {:?}", other),
+    }
+    let e = Vec::<u8>::from_value(Value::sint(-1)).unwrap_err();
+    assert_eq!(
+        e.to_string(),
+        "application parse error: expected Vec<u8>, got SInt64(-1)"
+    );
+    // the value also survives through a row
+    let row = Response::Row(Row::new(vec![Value::uint(1), Value::Null]));
+    match <(u64, String)>::from_response(row) {
+        Err(Error::ParseError(ParseError::TypeMismatchGot { expected, got })) => {
+            assert_eq!(expected, "String");
+            assert_eq!(got, Value::Null);
+        }
+        other => panic!("expected a type mismatch, got {:?}", other),
+    }
+}
+
+#[test]
+fn t_string_from_scalars() {
+    assert_eq!(
+        String::from_value(Value::UInt64(u64::MAX)).unwrap(),
+        "18446744073709551615"
+    );
+    assert_eq!(String::from_value(Value::SInt8(-1)).unwrap(), "-1");
+    assert_eq!(String::from_value(Value::Bool(true)).unwrap(), "true");
+    assert_eq!(String::from_value(Value::Float64(1.5)).unwrap(), "1.5");
+    assert_eq!(
+        String::from_value(Value::String("sayan".into())).unwrap(),
+        "sayan"
+    );
+    assert!(matches!(
+        String::from_value(Value::Binary(b"sayan".to_vec())),
+        Err(Error::ParseError(ParseError::TypeMismatchGot { .. }))
+    ));
+    assert!(String::from_value(Value::Null).is_err());
+}
+
+#[test]
+fn t_response_builder() {
+    let built = ResponseBuilder::new()
+        .row(vec![
+            Value::str("sayan"),
+            Value::uint(100),
+            Value::list(vec![Value::str("rust")]),
+        ])
+        .row(vec![
+            Value::str("elana"),
+            Value::uint(200),
+            Value::list(vec![]),
+        ])
+        .build();
+    let manual = Response::Rows(vec![
+        Row::new(vec![
+            Value::String("sayan".into()),
+            Value::UInt64(100),
+            Value::List(vec![Value::String("rust".into())]),
+        ]),
+        Row::new(vec![
+            Value::String("elana".into()),
+            Value::UInt64(200),
+            Value::List(vec![]),
+        ]),
+    ]);
+    assert_eq!(built, manual);
+    assert_eq!(ResponseBuilder::new().build(), Response::Rows(vec![]));
+}
+
+#[test]
+fn t_char() {
+    assert_eq!(char::from_value(Value::str("a")).unwrap(), 'a');
+    assert_eq!(char::from_value(Value::str("🦀")).unwrap(), '🦀');
+    for s in ["", "ab", "🦀🦀"].iter() {
+        assert!(matches!(
+            char::from_value(Value::str(*s)),
+            Err(Error::ParseError(ParseError::Other(_)))
+        ));
+    }
+    // codepoints
+    assert_eq!(char::from_value(Value::UInt8(b'x')).unwrap(), 'x');
+    assert_eq!(char::from_value(Value::UInt32(0x1F980)).unwrap(), '🦀');
+    for invalid in [0xD800, 0x110000].iter() {
+        assert!(matches!(
+            char::from_value(Value::UInt32(*invalid)),
+            Err(Error::ParseError(ParseError::Other(_)))
+        ));
+    }
+    assert!(matches!(
+        char::from_value(Value::UInt64(120)),
+        Err(Error::ParseError(ParseError::TypeMismatchGot { .. }))
+    ));
+}
+
+#[test]
+fn t_bool_from_int() {
+    assert!(bool::from_value(Value::Bool(true)).unwrap());
+    assert!(!bool::from_value(Value::UInt8(0)).unwrap());
+    assert!(bool::from_value(Value::UInt8(1)).unwrap());
+    assert!(!bool::from_value(Value::UInt64(0)).unwrap());
+    assert!(bool::from_value(Value::UInt64(1)).unwrap());
+    for v in [Value::UInt8(2), Value::UInt64(u64::MAX)] {
+        assert!(matches!(
+            bool::from_value(v),
+            Err(Error::ParseError(ParseError::Other(_)))
+        ));
+    }
+    // signed integers aren't accepted
+    assert!(matches!(
+        bool::from_value(Value::SInt8(1)),
+        Err(Error::ParseError(ParseError::TypeMismatchGot { .. }))
+    ));
+    // in a row
+    let (_, active): (String, bool) =
+        FromResponse::from_response(Response::row([Value::str("sayan"), Value::UInt8(1)])).unwrap();
+    assert!(active);
+}
+
+#[test]
+fn t_string_lossy() {
+    assert_eq!(
+        Value::Binary(b"say\xffan".to_vec())
+            .into_string_lossy()
+            .unwrap(),
+        "say\u{FFFD}an"
+    );
+    assert_eq!(Value::UInt8(1).into_string_lossy().unwrap(), "1");
+    assert!(Value::List(vec![]).into_string_lossy().is_err());
+}
+
+#[test]
+fn t_bool_ddl() {
+    assert!(bool::from_response(Response::Empty).unwrap());
+    assert!(!bool::from_response(Response::Error(ERR_OBJECT_ALREADY_EXISTS)).unwrap());
+    assert!(matches!(
+        bool::from_response(Response::Error(100)),
+        Err(Error::ServerError(100))
+    ));
+    // still works for values
+    assert!(!bool::from_response(Response::Value(Value::Bool(false))).unwrap());
+}
+
+#[test]
+#[cfg(feature = "rust_decimal")]
+fn t_decimal_bad() {
+    use rust_decimal::Decimal;
+    assert!(matches!(
+        Decimal::from_value(Value::String("12.3.4".into())),
+        Err(Error::ParseError(ParseError::Other(_)))
+    ));
+    assert!(matches!(
+        Decimal::from_value(Value::Float64(1.5)),
+        Err(Error::ParseError(ParseError::TypeMismatchGot { .. }))
+    ));
+}
+
+#[test]
+fn t_float_narrowing() {
+    assert_eq!(f32::from_value(Value::Float64(1.5)).unwrap(), 1.5);
+    assert_eq!(f32::from_value(Value::Float64(-1.5e38)).unwrap(), -1.5e38);
+    assert_eq!(
+        f32::from_value(Value::Float64(f64::INFINITY)).unwrap(),
+        f32::INFINITY
+    );
+    assert!(f32::from_value(Value::Float64(f64::NAN)).unwrap().is_nan());
+    for out_of_range in [1e39, -1e39, f64::MAX] {
+        assert!(matches!(
+            f32::from_value(Value::Float64(out_of_range)),
+            Err(Error::ParseError(ParseError::Other(_)))
+        ));
+    }
+    assert_eq!(f64::from_value(Value::Float32(0.5)).unwrap(), 0.5);
+}
+
+#[test]
+fn t_rows_as() {
+    let resp = Response::Rows(vec![
+        Row::new(vec![Value::String("sayan".into()), Value::UInt64(100)]),
+        Row::new(vec![Value::String("elana".into()), Value::Bool(true)]),
+    ]);
+    let mut rows = resp.rows_as::<(String, u64)>();
+    assert_eq!(rows.next().unwrap().unwrap(), ("sayan".to_owned(), 100));
+    assert!(matches!(
+        rows.next(),
+        Some(Err(Error::ParseError(ParseError::TypeMismatchGot { .. })))
+    ));
+    assert!(rows.next().is_none());
+    let mut rows = Response::Error(5).rows_as::<(String, u64)>();
+    assert!(matches!(rows.next(), Some(Err(Error::ServerError(5)))));
+    assert!(rows.next().is_none());
+    assert!(matches!(
+        Response::Empty
+            .rows_as::<(String, u64)>()
+            .collect::<Vec<_>>()[..],
+        [Err(Error::ParseError(ParseError::ResponseMismatch))]
+    ));
+}
+
+#[test]
+#[cfg(feature = "bytes")]
+fn t_bytes_value() {
+    let v = b"sayan".to_vec();
+    let ptr = v.as_ptr();
+    let b = bytes::Bytes::from_value(Value::Binary(v)).unwrap();
+    // no copy
+    assert_eq!(b.as_ptr(), ptr);
+    assert_eq!(
+        bytes::Bytes::from_value(Value::String("sayan".into())).unwrap(),
+        b
+    );
+    assert!(matches!(
+        bytes::Bytes::from_value(Value::UInt8(1)),
+        Err(Error::ParseError(ParseError::TypeMismatchGot { .. }))
+    ));
+}
+
+#[test]
+fn t_row_borrow() {
+    let row = Row::new(vec![Value::str("sayan"), Value::UInt8(20), Value::Null]);
+    assert_eq!(row.value(0), Some(&Value::str("sayan")));
+    assert_eq!(row.value(2), Some(&Value::Null));
+    assert_eq!(row.value(3), None);
+    assert_eq!(row.value(usize::MAX), None);
+    assert_eq!(Row::new(vec![]).value(0), None);
+    assert_eq!(row.iter().count(), 3);
+    let ages: Vec<u8> = row.iter().filter_map(|v| v.parse_cloned().ok()).collect();
+    assert_eq!(ages, [20]);
+    let mut columns = 0;
+    for v in &row {
+        assert_eq!(Some(v), row.value(columns));
+        columns += 1;
+    }
+    assert_eq!(columns, row.len());
+    // the row is still usable
+    assert_eq!(row.into_first_as::<String>().unwrap(), "sayan");
+}
+
+#[test]
+fn t_column_count_mismatch() {
+    let row = Row::new(vec![Value::String("sayan".into()), Value::UInt64(100)]);
+    assert_eq!(row.len(), 2);
+    assert!(!row.is_empty());
+    assert!(Row::new(vec![]).is_empty());
+    let e = <(String, u64, bool)>::from_response(Response::Row(row)).unwrap_err();
+    assert_eq!(
+        e.to_string(),
+        "application parse error: expected 3 columns, got 2"
+    );
+}
+
+#[test]
+fn t_try_from() {
+    use std::convert::TryInto;
+    let row = Row::new(vec![Value::String("sayan".into()), Value::UInt64(100)]);
+    let (username, followers): (String, u64) = Response::Row(row.clone()).try_into().unwrap();
+    assert_eq!((username.as_str(), followers), ("sayan", 100));
+    assert_eq!(Row::try_from(Response::Row(row)).unwrap().len(), 2);
+    assert!(matches!(
+        u64::try_from(Response::Error(5)),
+        Err(Error::ServerError(5))
+    ));
+    assert!(matches!(
+        u64::try_from(Value::String("sayan".into())),
+        Err(Error::ParseError(ParseError::TypeMismatchGot { .. }))
+    ));
+    let () = Response::Empty.try_into().unwrap();
+}
+
+#[test]
+fn t_single_column_row() {
+    let resp = || Response::Row(Row::new(vec![Value::String("sayan".into())]));
+    assert_eq!(String::from_response(resp()).unwrap(), "sayan");
+    assert_eq!(
+        Option::<String>::from_response(resp()).unwrap().as_deref(),
+        Some("sayan")
+    );
+    let bin = Response::Row(Row::new(vec![Value::Binary(vec![0xCA, 0xFE])]));
+    assert_eq!(Vec::<u8>::from_response(bin).unwrap(), [0xCA, 0xFE]);
+    // more than one column is still a mismatch
+    let row = Response::Row(Row::new(vec![Value::UInt64(1), Value::UInt64(2)]));
+    assert!(matches!(
+        u64::from_response(row),
+        Err(Error::ParseError(ParseError::ResponseMismatch))
+    ));
+}
+
+#[test]
+fn t_time() {
+    let duration = Duration::from_secs(90);
+    assert_eq!(Duration::from_value(Value::UInt64(90)).unwrap(), duration);
+    assert!(Duration::from_value(Value::SInt64(90)).is_err());
+    let time = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+    assert_eq!(
+        SystemTime::from_value(Value::UInt64(1_700_000_000)).unwrap(),
+        time
+    );
+    assert_eq!(
+        SystemTime::from_value(Value::SInt64(1_700_000_000)).unwrap(),
+        time
+    );
+    match SystemTime::from_value(Value::SInt64(-1)) {
+        Err(Error::ParseError(ParseError::Other(e))) => {
+            assert_eq!(e, "timestamp -1 is before the UNIX epoch")
+        }
+        other => panic!("expected an error, got {:?}", other),
+    }
+    assert!(matches!(
+        SystemTime::from_value(Value::UInt64(u64::MAX)),
+        Err(Error::ParseError(ParseError::Other(_)))
+    ));
+}
+
+#[test]
+fn t_entity_new() {
+    let model = Entity::new("myspace", "mymodel").unwrap();
+    assert_eq!(model.to_string(), "myspace.mymodel");
+    assert_eq!(model.space_name(), "myspace");
+    assert_eq!(model.model_name(), Some("mymodel"));
+    let space = Entity::space("myspace").unwrap();
+    assert_eq!(space.to_string(), "myspace");
+    assert_eq!(space.model_name(), None);
+    assert_eq!("myspace.mymodel".parse::<Entity>().unwrap(), model);
+    assert_eq!("myspace".parse::<Entity>().unwrap(), space);
+    for (space, model) in [
+        ("myspace", ""),
+        ("", "mymodel"),
+        ("2space", "mymodel"),
+        ("myspace", "my model"),
+        ("myspace", "mymodel; drop space myspace"),
+    ]
+    .iter()
+    {
+        assert!(matches!(
+            Entity::new(space, model),
+            Err(Error::InvalidQuery(_))
+        ));
+    }
+    for bad in ["", "a.b.c", "myspace.", ".mymodel", "my-space"].iter() {
+        assert!(matches!(bad.parse::<Entity>(), Err(Error::InvalidQuery(_))));
+    }
+}
+
+#[test]
+fn t_entity() {
+    let space = || Entity::Space("myspace".into());
+    let model = || Entity::Model("myspace".into(), "mymodel".into());
+    let string = |s: &str| Response::Value(Value::String(s.into()));
+    let row = |names: &[&str]| {
+        Response::Row(Row::new(
+            names.iter().map(|n| Value::String(n.to_string())).collect(),
+        ))
+    };
+    assert_eq!(Entity::from_response(string("myspace")).unwrap(), space());
+    assert_eq!(
+        Entity::from_response(string("myspace.mymodel")).unwrap(),
+        model()
+    );
+    assert_eq!(Entity::from_response(row(&["myspace"])).unwrap(), space());
+    assert_eq!(
+        Entity::from_response(row(&["myspace", "mymodel"])).unwrap(),
+        model()
+    );
+    assert_eq!(model().to_string(), "myspace.mymodel");
+    assert!(matches!(
+        Entity::from_response(row(&["a", "b", "c"])),
+        Err(Error::ParseError(ParseError::ColumnCountMismatch {
+            expected: 2,
+            actual: 3
+        }))
+    ));
+    assert!(matches!(
+        Entity::from_response(row(&[])),
+        Err(Error::ParseError(ParseError::ColumnCountMismatch { .. }))
+    ));
+    assert!(matches!(
+        Entity::from_response(Response::Value(Value::Null)),
+        Err(Error::ParseError(ParseError::Other(_)))
+    ));
+}
+
+#[test]
+fn t_inspect_global() {
+    let string = |s: &str| Response::Value(Value::String(s.into()));
+    let info = GlobalInfo::from_response(string(
+        r#"{"spaces":["myspace","other"],"users":["root"],"settings":{}}"#,
+    ))
+    .unwrap();
+    assert_eq!(info.spaces(), ["myspace", "other"]);
+    assert_eq!(info.users(), ["root"]);
+    let info = GlobalInfo::from_response(string(r#"{"spaces":[],"users":["root"],"settings":{}}"#))
+        .unwrap();
+    assert!(info.spaces().is_empty());
+    // a key inside a nested object or a string isn't mistaken for a field
+    let info = GlobalInfo::from_response(string(
+        r#"{"settings":{"spaces":["nested"]},"users":["\"spaces\":[\"x\"]"],"spaces":["myspace"]}"#,
+    ))
+    .unwrap();
+    assert_eq!(info.spaces(), ["myspace"]);
+    assert_eq!(info.users(), [r#""spaces":["x"]"#]);
+    for bad in [
+        r#"{"spaces":["myspace"]}"#,
+        r#"{"spaces":[1],"users":[]}"#,
+        r#"{"spaces":[],"users":[]"#,
+        "myspace",
+    ]
+    .iter()
+    {
+        assert!(
+            matches!(
+                GlobalInfo::from_response(string(bad)),
+                Err(Error::ParseError(ParseError::Other(_)))
+            ),
+            "{}",
+            bad
+        );
+    }
+    assert!(matches!(
+        GlobalInfo::from_response(Response::Row(Row::new(vec![Value::str("0.8.1")]))),
+        Err(Error::ParseError(ParseError::ResponseMismatch))
+    ));
+    assert!(matches!(
+        GlobalInfo::from_response(Response::Error(5)),
+        Err(Error::ServerError(5))
+    ));
+}
+
+#[test]
+fn t_response_helpers() {
+    let resp = Response::rows([
+        vec![Value::str("sayan"), Value::uint(100), Value::Null],
+        vec![
+            Value::str("elon"),
+            Value::sint(-1),
+            Value::List(vec![Value::float(1.5), Value::Bool(true)]),
+        ],
+    ]);
+    assert_eq!(
+        resp,
+        Response::Rows(vec![
+            Row::new(vec![
+                Value::String("sayan".into()),
+                Value::UInt64(100),
+                Value::Null
+            ]),
+            Row::new(vec![
+                Value::String("elon".into()),
+                Value::SInt64(-1),
+                Value::List(vec![Value::Float64(1.5), Value::Bool(true)])
+            ]),
+        ])
+    );
+    assert_eq!(
+        resp.to_string(),
+        "(\"sayan\", 100, null)\n(\"elon\", -1, [1.5, true])"
+    );
+    assert_eq!(
+        Response::row([Value::binary(vec![0xca, 0xfe]), Value::str("a\"b")]).to_string(),
+        "(0xcafe, \"a\\\"b\")"
+    );
+    assert_eq!(Response::Value(Value::Float32(2.0)).to_string(), "2.0");
+    assert_eq!(Response::Empty.to_string(), "(empty)");
+    assert_eq!(Response::Error(103).to_string(), "error 103");
+}
+
+#[test]
+fn t_pairs() {
+    let resp = Response::rows([
+        [Value::str("sayan"), Value::uint(100)],
+        [Value::str("elon"), Value::uint(5)],
+        [Value::str("sayan"), Value::uint(200)],
+    ]);
+    let pairs: Vec<(String, u64)> = FromResponse::from_response(resp).unwrap();
+    assert_eq!(
+        pairs,
+        [
+            ("sayan".to_owned(), 100),
+            ("elon".to_owned(), 5),
+            ("sayan".to_owned(), 200)
+        ]
+    );
+    let resp = Response::rows([vec![Value::str("sayan")]]);
+    assert!(matches!(
+        Vec::<(String, u64)>::from_response(resp),
+        Err(Error::ParseError(ParseError::ColumnCountMismatch {
+            expected: 2,
+            actual: 1
+        }))
+    ));
+    assert!(matches!(
+        Vec::<(String, u64)>::from_response(Response::Empty),
+        Err(Error::ParseError(ParseError::ResponseMismatch))
+    ));
+}
+
+#[test]
+fn t_128_bit_values() {
+    assert_eq!(u128::from_value(Value::UInt8(5)).unwrap(), 5);
+    assert_eq!(i128::from_value(Value::SInt64(-5)).unwrap(), -5);
+    assert_eq!(
+        u128::from_value(Value::str(u128::MAX.to_string())).unwrap(),
+        u128::MAX
+    );
+    match u128::from_value(Value::SInt8(-1)) {
+        Err(Error::ParseError(ParseError::Other(e))) => {
+            assert_eq!(e, "-1 is out of range for u128")
+        }
+        r => panic!("expected an error, got {:?}", r),
+    }
+    assert!(matches!(
+        i128::from_value(Value::str("12a")),
+        Err(Error::ParseError(ParseError::Other(_)))
+    ));
+    assert!(matches!(
+        u128::from_value(Value::Float64(1.0)),
+        Err(Error::ParseError(ParseError::TypeMismatchGot { .. }))
+    ));
+}
+
+#[test]
+fn t_sky_list() {
+    let ints = || Value::List(vec![Value::UInt64(1), Value::UInt64(2), Value::UInt64(3)]);
+    let list: SkyList<u64> = FromResponse::from_response(Response::Value(ints())).unwrap();
+    assert_eq!(list.len(), 3);
+    assert!(!list.is_empty());
+    assert_eq!(
+        list.iter().collect::<ClientResult<Vec<_>>>().unwrap(),
+        [1, 2, 3]
+    );
+    assert_eq!(list.into_vec().unwrap(), [1, 2, 3]);
+    // elements are only converted when accessed
+    let list =
+        SkyList::<u64>::try_from(Value::List(vec![Value::UInt64(1), Value::str("two")])).unwrap();
+    assert_eq!(list.len(), 2);
+    let mut it = list.into_iter();
+    assert_eq!(it.next().unwrap().unwrap(), 1);
+    assert!(matches!(
+        it.next().unwrap(),
+        Err(Error::ParseError(ParseError::TypeMismatchGot { .. }))
+    ));
+    // but the value itself must be a list
+    assert!(matches!(
+        SkyList::<u64>::from_value(Value::UInt64(1)),
+        Err(Error::ParseError(ParseError::TypeMismatchGot { .. }))
+    ));
+    // nested lists
+    let nested = Value::List(vec![ints(), Value::List(vec![])]);
+    let nested: Vec<Vec<u64>> = SkyList::<SkyList<u64>>::from_value(nested)
+        .unwrap()
+        .into_iter()
+        .map(|l| l.and_then(SkyList::into_vec))
+        .collect::<ClientResult<_>>()
+        .unwrap();
+    assert_eq!(nested, [vec![1, 2, 3], vec![]]);
+}
+
+#[test]
+fn t_non_zero() {
+    assert_eq!(
+        NonZeroU64::from_value(Value::UInt64(42)).unwrap(),
+        NonZeroU64::new(42).unwrap()
+    );
+    assert_eq!(
+        NonZeroI8::try_from(Value::SInt8(-1)).unwrap(),
+        NonZeroI8::new(-1).unwrap()
+    );
+    // the parameter encoding reads back
+    let q = crate::query!("?", NonZeroU32::new(7).unwrap());
+    let v = q.decode_params().unwrap().remove(0);
+    assert_eq!(NonZeroU64::from_value(v).unwrap().get(), 7);
+    match NonZeroU16::from_value(Value::UInt16(0)) {
+        Err(Error::ParseError(ParseError::Other(e))) => assert_eq!(e, "unexpected zero"),
+        other => panic!("expected an error, got {:?}", other),
+    }
+    assert!(matches!(
+        NonZeroI64::from_value(Value::str("1")),
+        Err(Error::ParseError(ParseError::TypeMismatchGot { .. }))
+    ));
+    let v: Option<NonZeroU8> = FromResponse::from_response(Response::Value(Value::Null)).unwrap();
+    assert_eq!(v, None);
+}
+
+#[test]
+fn t_write_to() {
+    let values = [
+        Value::Null,
+        Value::Bool(true),
+        Value::Bool(false),
+        Value::UInt8(u8::MAX),
+        Value::UInt16(0),
+        Value::UInt32(1234567),
+        Value::UInt64(u64::MAX),
+        Value::SInt8(i8::MIN),
+        Value::SInt16(-300),
+        Value::SInt32(i32::MAX),
+        Value::SInt64(i64::MIN),
+        Value::Float32(1.5),
+        Value::Float64(-0.1),
+        Value::Float64(1e300),
+        Value::Float64(f64::NAN),
+        Value::str("say \"hi\"\n"),
+        Value::binary(vec![0x00, 0x0f, 0xa0, 0xff]),
+        Value::List(vec![]),
+        Value::List(vec![
+            Value::UInt8(1),
+            Value::List(vec![Value::str("a"), Value::Null]),
+        ]),
+    ];
+    for v in values {
+        let mut out = vec![];
+        v.write_to(&mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), v.to_string());
+    }
+}
@@ -0,0 +1,556 @@
+/*
+ * Copyright 2023, Sayan Nandan <nandansayan@outlook.com>
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+
+//! # Model schemas
+//!
+//! A [`ModelSchema`] describes the columns of a model. You can fetch it from the server with `describe_model` on a
+//! connection, or build it yourself, and then use [`Query::check_against`] to check the parameters of an `insert`
+//! before sending it. A parameter that doesn't match its column's type is otherwise only rejected by the server, with an
+//! error code that doesn't say which parameter was wrong.
+//!
+//! ## Example
+//! ```
+//! use skytable::{query, schema::ModelSchema};
+//!
+//! let schema = ModelSchema::parse("{ username: string, password: binary, null email: string }").unwrap();
+//! let q = query!("insert into myspace.users(?, ?, ?)", "sayan", "pass", None::<String>);
+//! let e = q.check_against(&schema).unwrap_err();
+//! assert_eq!(
+//!     e.to_string(),
+//!     "invalid query: parameter 2 (`password`): a string can't be stored in a binary column"
+//! );
+//! ```
+
+use {
+    crate::{
+        error::{ClientResult, Error, ParseError},
+        json::Json,
+        query::{ident, Query},
+        response::{Entity, FromResponse, Response, Value},
+    },
+    std::{convert::TryFrom, fmt},
+};
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+/// The type of a column
+pub enum ColumnType {
+    /// `bool`
+    Bool,
+    /// `uint8`
+    UInt8,
+    /// `uint16`
+    UInt16,
+    /// `uint32`
+    UInt32,
+    /// `uint64`
+    UInt64,
+    /// `sint8`
+    SInt8,
+    /// `sint16`
+    SInt16,
+    /// `sint32`
+    SInt32,
+    /// `sint64`
+    SInt64,
+    /// `float32`
+    Float32,
+    /// `float64`
+    Float64,
+    /// `binary`
+    Binary,
+    /// `string`
+    String,
+    /// `list { type: ... }`
+    List(Box<ColumnType>),
+}
+
+impl ColumnType {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "bool" => Self::Bool,
+            "uint8" => Self::UInt8,
+            "uint16" => Self::UInt16,
+            "uint32" => Self::UInt32,
+            "uint64" => Self::UInt64,
+            "sint8" => Self::SInt8,
+            "sint16" => Self::SInt16,
+            "sint32" => Self::SInt32,
+            "sint64" => Self::SInt64,
+            "float32" => Self::Float32,
+            "float64" => Self::Float64,
+            "binary" => Self::Binary,
+            "string" => Self::String,
+            _ => return None,
+        })
+    }
+    /// Check that a decoded parameter can be stored in this column, including whether a number is in the column's
+    /// range, returning a description of the problem if it can't
+    pub(crate) fn check_value(&self, value: &Value) -> Result<(), String> {
+        let in_range = match (self, value) {
+            (Self::Bool, Value::Bool(_))
+            | (Self::UInt64, Value::UInt64(_))
+            | (Self::SInt64, Value::SInt64(_))
+            | (Self::Float64, Value::Float64(_))
+            | (Self::Binary, Value::Binary(_))
+            | (Self::String, Value::String(_)) => true,
+            (Self::UInt8, Value::UInt64(v)) => u8::try_from(*v).is_ok(),
+            (Self::UInt16, Value::UInt64(v)) => u16::try_from(*v).is_ok(),
+            (Self::UInt32, Value::UInt64(v)) => u32::try_from(*v).is_ok(),
+            (Self::SInt8, Value::SInt64(v)) => i8::try_from(*v).is_ok(),
+            (Self::SInt16, Value::SInt64(v)) => i16::try_from(*v).is_ok(),
+            (Self::SInt32, Value::SInt64(v)) => i32::try_from(*v).is_ok(),
+            // infinities and NaN are representable, but finite values beyond `f32::MAX` would become infinite
+            (Self::Float32, Value::Float64(v)) => !v.is_finite() || v.abs() <= f32::MAX as f64,
+            (Self::List(ty), Value::List(elements)) => {
+                return elements.iter().try_for_each(|e| ty.check_value(e))
+            }
+            (column, value) => {
+                return Err(format!(
+                    "{} can't be stored in a {column} column",
+                    describe_param(value)
+                ))
+            }
+        };
+        if in_range {
+            Ok(())
+        } else {
+            Err(format!("{value} is out of range for a {self} column"))
+        }
+    }
+}
+
+impl fmt::Display for ColumnType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Bool => "bool",
+            Self::UInt8 => "uint8",
+            Self::UInt16 => "uint16",
+            Self::UInt32 => "uint32",
+            Self::UInt64 => "uint64",
+            Self::SInt8 => "sint8",
+            Self::SInt16 => "sint16",
+            Self::SInt32 => "sint32",
+            Self::SInt64 => "sint64",
+            Self::Float32 => "float32",
+            Self::Float64 => "float64",
+            Self::Binary => "binary",
+            Self::String => "string",
+            Self::List(ty) => return write!(f, "list {{ type: {ty} }}"),
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+/// A column in a [`ModelSchema`]
+pub struct Column {
+    name: String,
+    ty: ColumnType,
+    nullable: bool,
+}
+
+impl Column {
+    /// Create a new column
+    pub fn new(name: &str, ty: ColumnType, nullable: bool) -> Self {
+        Self {
+            name: name.to_owned(),
+            ty,
+            nullable,
+        }
+    }
+    /// Returns the name of the column
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    /// Returns the type of the column
+    pub fn ty(&self) -> &ColumnType {
+        &self.ty
+    }
+    /// Returns true if the column can be `null`
+    pub fn nullable(&self) -> bool {
+        self.nullable
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+/// The columns of a model, in the order in which they were declared
+pub struct ModelSchema {
+    columns: Vec<Column>,
+}
+
+impl ModelSchema {
+    /// Create a schema from its columns
+    pub fn new(columns: Vec<Column>) -> Self {
+        Self { columns }
+    }
+    /// Parse a model declaration, as in `create model` (without the model name) or as returned by `inspect model`, for
+    /// example `{ username: string, null email: string, tags: list { type: string } }`. A leading `*` (that the server
+    /// uses to mark the primary key) is ignored
+    pub fn parse(decl: &str) -> ClientResult<Self> {
+        let bad = || {
+            Error::ParseError(ParseError::Other(format!(
+                "invalid model declaration `{decl}`"
+            )))
+        };
+        let fields = decl
+            .trim()
+            .strip_prefix('{')
+            .and_then(|d| d.strip_suffix('}'))
+            .ok_or_else(bad)?;
+        let mut columns = vec![];
+        for field in split_top_level(fields).filter(|f| !f.trim().is_empty()) {
+            let (name, ty) = field.split_once(':').ok_or_else(bad)?;
+            let mut name = name.trim().trim_start_matches('*').trim();
+            let nullable = match name.strip_prefix("null ") {
+                Some(n) => {
+                    name = n.trim();
+                    true
+                }
+                None => false,
+            };
+            let ty = parse_type(ty.trim()).ok_or_else(bad)?;
+            columns.push(Column::new(&ident(name).map_err(|_| bad())?, ty, nullable));
+        }
+        Ok(Self::new(columns))
+    }
+    /// Returns the columns
+    pub fn columns(&self) -> &[Column] {
+        &self.columns
+    }
+    /// Returns the column with the given name
+    pub fn column(&self, name: &str) -> Option<&Column> {
+        self.columns.iter().find(|c| c.name == name)
+    }
+}
+
+/// Parses the string returned by `inspect model`, which is either the declaration itself or an object whose `decl` field
+/// holds the declaration
+impl FromResponse for ModelSchema {
+    fn from_response(resp: Response) -> ClientResult<Self> {
+        let description = match resp {
+            Response::Value(Value::String(s)) => s,
+            Response::Error(e) => return Err(Error::ServerError(e)),
+            _ => return Err(Error::ParseError(ParseError::ResponseMismatch)),
+        };
+        let decl = Json::parse(&description)
+            .and_then(|description| description.get("decl")?.as_str().map(str::to_owned));
+        match decl {
+            Some(decl) => Self::parse(&decl),
+            None => Self::parse(&description),
+        }
+    }
+}
+
+/// Build the `inspect model` query for `space.model`, validating both names
+pub(crate) fn inspect_model(entity: &str) -> ClientResult<Query> {
+    match entity.parse()? {
+        model @ Entity::Model(..) => Ok(Query::new_string(format!("inspect model {model}"))),
+        Entity::Space(_) => Err(Error::InvalidQuery(format!(
+            "expected a model as `space.model`, got `{entity}`"
+        ))),
+    }
+}
+
+/// Split on the commas that are not inside braces
+fn split_top_level(s: &str) -> impl Iterator<Item = &str> {
+    let mut depth = 0usize;
+    let mut start = 0;
+    let mut parts = vec![];
+    for (i, c) in s.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                parts.push(&s[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&s[start..]);
+    parts.into_iter()
+}
+
+/// Parse `string` or `list { type: string }`
+fn parse_type(ty: &str) -> Option<ColumnType> {
+    match ty.strip_prefix("list") {
+        Some(props) => {
+            let props = props.trim().strip_prefix('{')?.strip_suffix('}')?.trim();
+            let element = props.strip_prefix("type")?.trim().strip_prefix(':')?;
+            parse_type(element.trim()).map(|ty| ColumnType::List(Box::new(ty)))
+        }
+        None => ColumnType::from_name(ty),
+    }
+}
+
+/*
+    checking parameters
+*/
+
+/// Describe the type of a decoded parameter (see [`Query::decode_params`]) for an error message
+fn describe_param(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a bool",
+        Value::UInt8(_) | Value::UInt16(_) | Value::UInt32(_) | Value::UInt64(_) => {
+            "an unsigned integer"
+        }
+        Value::SInt8(_) | Value::SInt16(_) | Value::SInt32(_) | Value::SInt64(_) => {
+            "a signed integer"
+        }
+        Value::Float32(_) | Value::Float64(_) => "a float",
+        Value::Binary(_) => "a binary",
+        Value::String(_) => "a string",
+        Value::List(_) => "a list",
+    }
+}
+
+impl Query {
+    /// Check that the parameters of this query match the columns of `schema`, in order, without sending it
+    ///
+    /// This is meant for `insert` queries, where every column gets a parameter in declaration order. Each parameter is
+    /// checked like [`Query::push_param_checked`] does: its type must be one that the column can store (for example,
+    /// any unsigned integer for a `uint8` column) and numbers must be within the column's range. `null` is only allowed
+    /// for nullable columns. This returns [`Error::InvalidQuery`] if a parameter doesn't match, or if the parameters
+    /// can't be decoded (which can only happen with a custom [`SQParam`](crate::query::SQParam) implementation)
+    pub fn check_against(&self, schema: &ModelSchema) -> ClientResult<()> {
+        let params = self.decode_params().map_err(|e| {
+            Error::InvalidQuery(format!("the parameters could not be decoded: {e}"))
+        })?;
+        if params.len() != schema.columns.len() {
+            return Err(Error::InvalidQuery(format!(
+                "the model has {} columns, but the query has {} parameters",
+                schema.columns.len(),
+                params.len()
+            )));
+        }
+        for (i, (param, column)) in params.iter().zip(&schema.columns).enumerate() {
+            let checked = match param {
+                Value::Null if column.nullable => Ok(()),
+                Value::Null => Err(format!(
+                    "null can't be stored in a non-nullable {} column",
+                    column.ty
+                )),
+                param => column.ty.check_value(param),
+            };
+            if let Err(e) = checked {
+                return Err(Error::InvalidQuery(format!(
+                    "parameter {} (`{}`): {e}",
+                    i + 1,
+                    column.name
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn t_parse_schema() {
+    let schema = ModelSchema::parse(
+        "{*username: string, password: binary, null email: string, tags: list { type: list { type: uint8 } }, }",
+    )
+    .unwrap();
+    assert_eq!(
+        schema.columns(),
+        [
+            Column::new("username", ColumnType::String, false),
+            Column::new("password", ColumnType::Binary, false),
+            Column::new("email", ColumnType::String, true),
+            Column::new(
+                "tags",
+                ColumnType::List(Box::new(ColumnType::List(Box::new(ColumnType::UInt8)))),
+                false
+            ),
+        ]
+    );
+    assert_eq!(
+        schema.column("tags").unwrap().ty().to_string(),
+        "list { type: list { type: uint8 } }"
+    );
+    // as returned by `inspect model`
+    let resp = Response::Value(Value::String(
+        r#"{"decl":"{*username: string, null email: string}","rows":0,"properties":{}}"#.into(),
+    ));
+    assert_eq!(ModelSchema::from_response(resp).unwrap().columns().len(), 2);
+    // a `decl` key inside a nested object isn't mistaken for the declaration
+    let resp = Response::Value(Value::String(
+        r#"{"properties":{"decl":"{a: bool}"},"decl":"{username: string}","rows":0}"#.into(),
+    ));
+    assert_eq!(
+        ModelSchema::from_response(resp).unwrap().columns(),
+        [Column::new("username", ColumnType::String, false)]
+    );
+    for bad in [
+        "username: string",
+        "{username: str}",
+        "{user name: string}",
+        "{username}",
+    ] {
+        assert!(ModelSchema::parse(bad).is_err(), "{}", bad);
+    }
+}
+
+#[test]
+fn t_check_against() {
+    let schema = ModelSchema::parse(
+        "{ username: string, followers: uint32, null email: string, tags: list { type: string } }",
+    )
+    .unwrap();
+    let q = crate::query!(
+        "insert into myspace.mymodel(?, ?, ?, ?)",
+        "sayan",
+        100u64,
+        None::<String>,
+        crate::query::QList::new(&["rust", "db"])
+    );
+    q.check_against(&schema).unwrap();
+    let check = |q: Query| match q.check_against(&schema) {
+        Err(Error::InvalidQuery(e)) => e,
+        r => panic!("expected an error, got {:?}", r),
+    };
+    assert_eq!(
+        check(crate::query!(
+            "insert into myspace.mymodel(?, ?, ?, ?)",
+            "sayan",
+            -1i64,
+            "a",
+            crate::query::QList::new(&["a"])
+        )),
+        "parameter 2 (`followers`): a signed integer can't be stored in a uint32 column"
+    );
+    assert_eq!(
+        check(crate::query!(
+            "insert into myspace.mymodel(?, ?, ?, ?)",
+            None::<String>,
+            1u64,
+            "a",
+            crate::query::QList::new(&["a"])
+        )),
+        "parameter 1 (`username`): null can't be stored in a non-nullable string column"
+    );
+    assert_eq!(
+        check(crate::query!(
+            "insert into myspace.mymodel(?, ?, ?, ?)",
+            "sayan",
+            1u64,
+            "a",
+            crate::query::QList::new(&[1u8])
+        )),
+        "parameter 4 (`tags`): an unsigned integer can't be stored in a string column"
+    );
+    assert_eq!(
+        check(crate::query!(
+            "insert into myspace.mymodel(?, ?, ?, ?)",
+            "sayan",
+            u64::MAX,
+            "a",
+            crate::query::QList::new(&["a"])
+        )),
+        "parameter 2 (`followers`): 18446744073709551615 is out of range for a uint32 column"
+    );
+    assert_eq!(
+        check(crate::query!(
+            "insert into myspace.mymodel(?, ?)",
+            "sayan",
+            1u64
+        )),
+        "the model has 4 columns, but the query has 2 parameters"
+    );
+    // a custom parameter with an invalid encoding is an error instead of a panic
+    struct Invalid;
+    impl crate::query::SQParam for Invalid {
+        fn append_param(&self, buf: &mut Vec<u8>) -> usize {
+            buf.extend(b"\x02abc");
+            1
+        }
+    }
+    assert!(
+        check(crate::query!("insert into myspace.mymodel(?)", Invalid))
+            .starts_with("the parameters could not be decoded")
+    );
+}
+
+#[test]
+fn t_push_param_checked() {
+    use crate::query::QList;
+    let mut q = Query::new("insert into myspace.mymodel(?, ?, ?, ?, ?, ?, ?)");
+    q.push_param_checked(255u16, &ColumnType::UInt8)
+        .unwrap()
+        .push_param_checked(-128i64, &ColumnType::SInt8)
+        .unwrap()
+        .push_param_checked(u64::MAX, &ColumnType::UInt64)
+        .unwrap()
+        .push_param_checked(1.5f64, &ColumnType::Float32)
+        .unwrap()
+        .push_param_checked(None::<u64>, &ColumnType::UInt8)
+        .unwrap()
+        .push_param_checked("sayan", &ColumnType::String)
+        .unwrap()
+        .push_param_checked(
+            QList::new(&[1u32, 65535]),
+            &ColumnType::List(Box::new(ColumnType::UInt16)),
+        )
+        .unwrap();
+    assert_eq!(q.param_cnt(), 7);
+    assert_eq!(
+        q,
+        crate::query!(
+            "insert into myspace.mymodel(?, ?, ?, ?, ?, ?, ?)",
+            255u16,
+            -128i64,
+            u64::MAX,
+            1.5f64,
+            None::<u64>,
+            "sayan",
+            QList::new(&[1u32, 65535])
+        )
+    );
+    let rejected = |r: ClientResult<&mut Query>| match r {
+        Err(Error::InvalidQuery(e)) => e,
+        r => panic!("expected an error, got {:?}", r),
+    };
+    assert_eq!(
+        rejected(q.push_param_checked(300u16, &ColumnType::UInt8)),
+        "300 is out of range for a uint8 column"
+    );
+    assert_eq!(
+        rejected(q.push_param_checked(-129i16, &ColumnType::SInt8)),
+        "-129 is out of range for a sint8 column"
+    );
+    assert_eq!(
+        rejected(q.push_param_checked(1e39f64, &ColumnType::Float32)),
+        "1e39 is out of range for a float32 column"
+    );
+    assert_eq!(
+        rejected(q.push_param_checked(1i64, &ColumnType::UInt64)),
+        "a signed integer can't be stored in a uint64 column"
+    );
+    assert_eq!(
+        rejected(q.push_param_checked(
+            QList::new(&[1u32, 65536]),
+            &ColumnType::List(Box::new(ColumnType::UInt16))
+        )),
+        "65536 is out of range for a uint16 column"
+    );
+    assert_eq!(
+        rejected(q.push_param_checked(("sayan", "pass"), &ColumnType::String)),
+        "expected a single parameter, got 2"
+    );
+    // the rejected parameters were not added
+    assert_eq!(q.param_cnt(), 7);
+    assert_eq!(q.decode_params().unwrap().len(), 7);
+}
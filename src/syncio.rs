@@ -25,16 +25,23 @@
 use {
     crate::{
         config::Config,
-        error::{ClientResult, ConnectionSetupError, Error},
-        protocol::{ClientHandshake, DecodeState, Decoder, RState, ServerHandshake},
-        response::{FromResponse, Response},
+        error::{ClientResult, ConnectionSetupError, Error, ProtocolError},
+        protocol::{
+            wire, ClientHandshake, DecodeState, Decoder, MRespState, PipelineResult, RState,
+            ServerHandshake,
+        },
+        query::{Pipeline, PreparedQuery, SQParam},
+        response::{Entity, FromResponse, GlobalInfo, Response},
+        schema::ModelSchema,
+        tls::TlsStream,
         Query,
     },
-    native_tls::{Certificate, TlsConnector, TlsStream},
     std::{
         io::{Read, Write},
         net::TcpStream,
-        ops::{Deref, DerefMut},
+        ops::{ControlFlow, Deref, DerefMut},
+        path::Path,
+        time::Duration,
     },
 };
 
@@ -53,7 +60,7 @@ pub struct Connection(TcpConnection<TcpStream>);
 /// - Query mode: `QTDEX-1A/BQL-S1`
 /// - Authentication plugin: `pwd`
 #[derive(Debug)]
-pub struct ConnectionTls(TcpConnection<TlsStream<TcpStream>>);
+pub struct ConnectionTls(TcpConnection<TlsStream>);
 
 impl Deref for Connection {
     type Target = TcpConnection<TcpStream>;
@@ -67,7 +74,7 @@ impl DerefMut for Connection {
     }
 }
 impl Deref for ConnectionTls {
-    type Target = TcpConnection<TlsStream<TcpStream>>;
+    type Target = TcpConnection<TlsStream>;
     fn deref(&self) -> &Self::Target {
         &self.0
     }
@@ -78,47 +85,146 @@ impl DerefMut for ConnectionTls {
     }
 }
 
+impl Connection {
+    /// Close the connection, flushing anything that's still buffered and shutting down the TCP stream, instead of just
+    /// dropping it. Skyhash has no disconnect message, so the server sees the connection close like it would on `Drop`,
+    /// but this lets you handle any error and know that the connection is gone once it returns
+    pub fn close(self) -> ClientResult<()> {
+        let mut con = self.0.con;
+        con.flush()?;
+        ignore_not_connected(con.shutdown(std::net::Shutdown::Both))
+    }
+    /// Run a query and return a raw [`Response`], failing with [`Error::Timeout`] if the response doesn't arrive within
+    /// `timeout`. This overrides the socket's read timeout for this query only, and the previous read timeout is restored
+    /// afterward
+    ///
+    /// The timeout applies to every read while waiting for the response (and not to sending the query). If it expires,
+    /// the response may still arrive later, so the connection is marked as broken (see [`TcpConnection::is_broken`]) and
+    /// you should connect again
+    ///
+    /// ## Example
+    /// ```no_run
+    /// use {skytable::{query, Config}, std::time::Duration};
+    ///
+    /// let mut db = Config::new_default("username", "password").connect().unwrap();
+    /// let q = query!("select all * from myspace.mymodel limit ?", 1000u64);
+    /// let resp = db.query_timeout(&q, Duration::from_secs(30)).unwrap();
+    /// ```
+    pub fn query_timeout(&mut self, q: &Query, timeout: Duration) -> ClientResult<Response> {
+        self.0.query_with_read_timeout(q, timeout, |con| con)
+    }
+}
+
+impl ConnectionTls {
+    /// Close the connection, telling the server that the TLS session is ending (with a `close_notify` alert) and then
+    /// shutting down the TCP stream. See [`Connection::close`]
+    pub fn close(self) -> ClientResult<()> {
+        ignore_not_connected(crate::tls::shutdown(self.0.con))
+    }
+    /// Run a query and return a raw [`Response`], failing with [`Error::Timeout`] if the response doesn't arrive within
+    /// `timeout`. See [`Connection::query_timeout`]
+    pub fn query_timeout(&mut self, q: &Query, timeout: Duration) -> ClientResult<Response> {
+        self.0
+            .query_with_read_timeout(q, timeout, crate::tls::tcp_stream)
+    }
+}
+
+/// The peer may already have closed the connection, in which case there's nothing left to shut down
+pub(crate) fn ignore_not_connected(r: std::io::Result<()>) -> ClientResult<()> {
+    match r {
+        Err(e) if e.kind() == std::io::ErrorKind::NotConnected => Ok(()),
+        r => r.map_err(From::from),
+    }
+}
+
 impl Config {
     /// Establish a connection to the database using the current configuration
+    ///
+    /// ## Example
+    /// ```no_run
+    /// use skytable::Config;
+    ///
+    /// let mut db = Config::new_default("username", "password").connect().unwrap();
+    /// ```
     pub fn connect(&self) -> ClientResult<Connection> {
-        let mut tcpstream = TcpStream::connect((self.host(), self.port()))?;
-        let handshake = ClientHandshake::new(self);
-        tcpstream.write_all(handshake.inner())?;
-        let mut resp = [0u8; 4];
-        tcpstream.read_exact(&mut resp)?;
-        match ServerHandshake::parse(resp)? {
-            ServerHandshake::Error(e) => return Err(ConnectionSetupError::HandshakeError(e).into()),
-            ServerHandshake::Okay(_suggestion) => {
-                return Ok(Connection(TcpConnection::new(tcpstream)))
+        let (tcpstream, _) = self.connect_tcp()?;
+        self.connect_over(tcpstream).map(Connection)
+    }
+    #[cfg(feature = "socks")]
+    /// Establish a connection to the database through the SOCKS5 proxy at `proxy_addr`. This is available with the
+    /// `socks` feature
+    ///
+    /// The proxy resolves the configured host, so it can be a name that only the proxy knows how to reach (like a Tor
+    /// onion service). If there's more than one endpoint (see [`Config::with_hosts`]), the proxy is asked to connect to
+    /// each of them in turn. Proxies that require authentication aren't supported
+    ///
+    /// ## Example
+    /// ```no_run
+    /// use skytable::Config;
+    ///
+    /// let mut db = Config::new("subnetx2_db1", 2008, "username", "password")
+    ///     .connect_via_socks5("127.0.0.1:1080")
+    ///     .unwrap();
+    /// ```
+    pub fn connect_via_socks5(
+        &self,
+        proxy_addr: impl std::net::ToSocketAddrs,
+    ) -> ClientResult<Connection> {
+        let mut last_err = None;
+        for endpoint in self.hosts() {
+            match socks::Socks5Stream::connect(&proxy_addr, endpoint) {
+                Ok(stream) => return self.connect_over(stream.into_inner()).map(Connection),
+                Err(e) => last_err = Some(e),
             }
         }
+        Err(last_err.unwrap().into())
     }
-    /// Establish a TLS connection to the database using the current configuration.
-    /// Pass the certificate in PEM format.
-    pub fn connect_tls(&self, cert: &str) -> ClientResult<ConnectionTls> {
-        let stream = TcpStream::connect((self.host(), self.port()))?;
-        let mut stream = TlsConnector::builder()
-            .add_root_certificate(Certificate::from_pem(cert.as_bytes()).map_err(|e| {
-                ConnectionSetupError::Other(format!("failed to parse certificate: {e}"))
-            })?)
-            .danger_accept_invalid_hostnames(true)
-            .build()
-            .map_err(|e| {
-                ConnectionSetupError::Other(format!("failed to set up TLS acceptor: {e}"))
-            })?
-            .connect(self.host(), stream)
-            .map_err(|e| ConnectionSetupError::Other(format!("TLS handshake failed: {e}")))?;
+    /// Run the handshake on a stream that is already connected to the server
+    fn connect_over<C: Read + Write>(&self, mut stream: C) -> ClientResult<TcpConnection<C>> {
         let handshake = ClientHandshake::new(self);
         stream.write_all(handshake.inner())?;
-        let mut resp = [0u8; 4];
+        let mut resp = [0u8; wire::SERVER_HANDSHAKE_LEN];
         stream.read_exact(&mut resp)?;
         match ServerHandshake::parse(resp)? {
-            ServerHandshake::Error(e) => return Err(ConnectionSetupError::HandshakeError(e).into()),
-            ServerHandshake::Okay(_suggestion) => {
-                return Ok(ConnectionTls(TcpConnection::new(stream)))
-            }
+            ServerHandshake::Error(e) => Err(ConnectionSetupError::from_handshake_code(e).into()),
+            ServerHandshake::Okay(_suggestion) => Ok(TcpConnection::new(stream, self)),
         }
     }
+    /// Establish a TLS connection to the database using the current configuration.
+    /// Pass the certificate in PEM format.
+    ///
+    /// ## Example
+    /// ```no_run
+    /// use skytable::{config::DEFAULT_TLS_PORT, Config};
+    ///
+    /// let cert = std::fs::read_to_string("cert.pem").unwrap();
+    /// let mut db = Config::new("127.0.0.1", DEFAULT_TLS_PORT, "username", "password")
+    ///     .connect_tls(&cert)
+    ///     .unwrap();
+    /// ```
+    pub fn connect_tls(&self, cert: &str) -> ClientResult<ConnectionTls> {
+        let (stream, host) = self.connect_tcp()?;
+        let stream = crate::tls::connect(host, cert, stream)?;
+        self.connect_over(stream).map(ConnectionTls)
+    }
+    /// Establish a TLS connection to the database using the current configuration, reading the PEM certificate from
+    /// the file at the given path
+    ///
+    /// ## Example
+    /// ```no_run
+    /// use skytable::{config::DEFAULT_TLS_PORT, Config};
+    ///
+    /// let mut db = Config::new("127.0.0.1", DEFAULT_TLS_PORT, "username", "password")
+    ///     .connect_tls_from_file("cert.pem")
+    ///     .unwrap();
+    /// ```
+    pub fn connect_tls_from_file(
+        &self,
+        cert_path: impl AsRef<Path>,
+    ) -> ClientResult<ConnectionTls> {
+        let cert = std::fs::read_to_string(cert_path)?;
+        self.connect_tls(&cert)
+    }
 }
 
 #[derive(Debug)]
@@ -128,49 +234,1233 @@ impl Config {
 pub struct TcpConnection<C: Write + Read> {
     con: C,
     buffer: Vec<u8>,
+    redact_logs: bool,
+    max_retained_buffer: Option<usize>,
+    direct_write_threshold: Option<usize>,
+    read_buffer_size: usize,
+    max_response_size: Option<usize>,
+    lossy_utf8: bool,
+    queries_sent: u64,
+    bytes_sent: u64,
+    bytes_received: u64,
+    current_space: Option<String>,
+    broken: bool,
+    /// the number of responses to the last packet that haven't been read yet, and the number of responses at the start of
+    /// the buffer that were already read (see `resync`)
+    unread_responses: usize,
+    read_in_buffer: usize,
 }
 
 impl<C: Write + Read> TcpConnection<C> {
-    fn new(con: C) -> Self {
+    fn new(con: C, cfg: &Config) -> Self {
         Self {
             con,
-            buffer: Vec::with_capacity(crate::BUFSIZE),
+            buffer: Vec::with_capacity(cfg.read_buffer_size()),
+            redact_logs: cfg.redact_logs(),
+            max_retained_buffer: cfg.max_retained_buffer(),
+            direct_write_threshold: cfg.direct_write_threshold(),
+            read_buffer_size: cfg.read_buffer_size(),
+            max_response_size: cfg.max_response_size(),
+            lossy_utf8: cfg.lossy_utf8(),
+            queries_sent: 0,
+            bytes_sent: 0,
+            bytes_received: 0,
+            current_space: None,
+            broken: false,
+            unread_responses: 0,
+            read_in_buffer: 0,
         }
     }
     /// Run a query and return a raw [`Response`]
     pub fn query(&mut self, q: &Query) -> ClientResult<Response> {
+        self.check_broken()?;
         self.buffer.clear();
-        q.write_packet(&mut self.buffer).unwrap();
+        match self.direct_write_threshold {
+            Some(min_size) if q.payload().len() >= min_size => {
+                // only the header goes through the buffer; the payload is written straight from the query
+                q.write_header(&mut self.buffer).unwrap();
+                q.log_unbuffered(self.redact_logs);
+                self.send_packet_then(1, q.payload())?;
+            }
+            _ => {
+                q.write_packet(&mut self.buffer).unwrap();
+                q.log_packet(&self.buffer, self.redact_logs);
+                self.send_packet(1)?;
+            }
+        }
+        self.read_response()
+    }
+    /// Run a query that has no parameters, straight from its query string, and parse the response into the indicated type
+    /// (like [`Self::query_parse`]). The packet is encoded directly into the connection's buffer, so unlike
+    /// `query_parse(&query!(...))`, no [`Query`](struct@Query) is allocated
+    ///
+    /// ## Example
+    /// ```no_run
+    /// use skytable::Config;
+    ///
+    /// let mut db = Config::new_default("username", "password").connect().unwrap();
+    /// db.run_str::<()>("create space x").unwrap();
+    /// ```
+    pub fn run_str<T: FromResponse>(&mut self, q: &str) -> ClientResult<T> {
+        self.check_broken()?;
+        crate::query::write_str_packet(q, &mut self.buffer);
+        crate::query::log_str_packet(&self.buffer, self.redact_logs);
+        self.send_packet(1)?;
+        self.read_response().and_then(FromResponse::from_response)
+    }
+    /// Run a prepared statement with the given parameters and return a raw [`Response`]
+    ///
+    /// This is equivalent to running a [`Query`](struct@Query) with the same query string and parameters, but the statement's query
+    /// string is only encoded once (see [`PreparedQuery`])
+    pub fn run_prepared(
+        &mut self,
+        pq: &PreparedQuery,
+        params: impl SQParam,
+    ) -> ClientResult<Response> {
+        self.check_broken()?;
+        let param_cnt = pq.write_packet_into(&mut self.buffer, params);
+        pq.log_packet(&self.buffer, param_cnt, self.redact_logs);
+        self.send_packet(1)?;
+        self.read_response()
+    }
+    /// Send a hand-crafted packet as is and decode a single response with the standard decoder
+    ///
+    /// **This is an escape hatch** for fuzzing, proxies and protocol experiments. The packet isn't validated in any way,
+    /// so a malformed packet (or one that makes the server send more than one response) can leave the connection in an
+    /// unusable state. Raw packets are also never logged, since their parameters can't be redacted. Use
+    /// [`Self::query`] for everything else
+    ///
+    /// ## Example
+    /// ```no_run
+    /// use skytable::{query, Config};
+    ///
+    /// let mut db = Config::new_default("username", "password").connect().unwrap();
+    /// let packet = query!("sysctl report status").debug_encode_packet();
+    /// db.send_raw(&packet).unwrap();
+    /// ```
+    pub fn send_raw(&mut self, packet: &[u8]) -> ClientResult<Response> {
+        self.check_broken()?;
+        self.buffer.clear();
+        self.buffer.extend_from_slice(packet);
+        self.send_packet(1)?;
+        self.read_response()
+    }
+    /// Run a query with the read timeout of the socket (returned by `socket`) temporarily set to `timeout`
+    fn query_with_read_timeout(
+        &mut self,
+        q: &Query,
+        timeout: Duration,
+        socket: fn(&C) -> &TcpStream,
+    ) -> ClientResult<Response> {
+        let previous = socket(&self.con).read_timeout()?;
+        socket(&self.con).set_read_timeout(Some(timeout))?;
+        let resp = self.query(q).map_err(|e| match e {
+            // depending on the platform, a read that timed out fails with either of these
+            Error::IoError(e)
+                if matches!(
+                    e.kind(),
+                    std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                ) =>
+            {
+                Error::Timeout
+            }
+            e => e,
+        });
+        let restored = socket(&self.con).set_read_timeout(previous);
+        resp.and_then(|resp| restored.map(|_| resp).map_err(From::from))
+    }
+    /// Fail with an I/O error if the connection is broken (see [`Self::is_broken`]), before anything is written to the
+    /// buffer, so that a broken connection's buffer still holds what was read for the failed query
+    fn check_broken(&self) -> ClientResult<()> {
+        if self.broken {
+            return Err(Error::IoError(std::io::Error::new(
+                std::io::ErrorKind::BrokenPipe,
+                "the connection was left in an unknown state by a failed query",
+            )));
+        }
+        Ok(())
+    }
+    /// Write the encoded packet in the buffer to the connection
+    fn send_packet(&mut self, query_count: usize) -> ClientResult<()> {
+        self.send_packet_then(query_count, &[])
+    }
+    /// Write the encoded packet in the buffer to the connection, followed by `tail` (which is written as is, without
+    /// copying it into the buffer)
+    fn send_packet_then(&mut self, query_count: usize, tail: &[u8]) -> ClientResult<()> {
+        self.check_broken()?;
+        self.broken = true;
+        // if the packet is only partly written, the server is still waiting for the rest, so there's no way to resync
+        self.unread_responses = 0;
         self.con.write_all(&self.buffer)?;
+        if !tail.is_empty() {
+            self.con.write_all(tail)?;
+        }
+        self.unread_responses = query_count;
+        self.read_in_buffer = 0;
+        self.queries_sent += query_count as u64;
+        self.bytes_sent += (self.buffer.len() + tail.len()) as u64;
         self.buffer.clear();
+        Ok(())
+    }
+    /// Read and decode a single response
+    fn read_response(&mut self) -> ClientResult<Response> {
         let mut state = RState::default();
         let mut cursor = 0;
         loop {
-            let mut buf = [0u8; crate::BUFSIZE];
-            let n = self.con.read(&mut buf)?;
-            if n == 0 {
-                return Err(Error::IoError(std::io::ErrorKind::ConnectionReset.into()));
-            }
-            self.buffer.extend_from_slice(&buf[..n]);
-            let mut decoder = Decoder::new(&self.buffer, cursor);
+            self.read_more()?;
+            let mut decoder = Decoder::new(&self.buffer, cursor)
+                .lossy_utf8(self.lossy_utf8)
+                .max_size(self.max_response_size);
             match decoder.validate_response(state) {
                 DecodeState::ChangeState(new_state) => {
                     state = new_state;
                     cursor = decoder.position();
                     continue;
                 }
-                DecodeState::Completed(resp) => return Ok(resp),
+                DecodeState::Completed(resp) => {
+                    self.broken = false;
+                    self.release_buffer();
+                    return Ok(resp);
+                }
                 DecodeState::Error(e) => return Err(e.into()),
             }
         }
     }
+    /// Execute a pipeline and return one [`Response`] for every query in it, in the order the queries were added
+    ///
+    /// An empty pipeline is not sent to the server, and returns no responses
+    pub fn execute_pipeline(&mut self, pipeline: &Pipeline) -> ClientResult<Vec<Response>> {
+        let mut resps = Vec::with_capacity(pipeline.query_count());
+        self.run_pipeline(pipeline, |resp| resps.push(resp))?;
+        Ok(resps)
+    }
+    /// Execute a pipeline, calling `on_response` with the index of the query and its [`Response`] as soon as each response
+    /// is decoded, in the order the queries were added. Unlike [`Self::execute_pipeline`], the responses are not collected,
+    /// so the memory used doesn't grow with the number of queries
+    ///
+    /// If `on_response` returns [`ControlFlow::Break`], it won't be called again. The remaining responses are still read
+    /// (and discarded) so that the connection can be used for more queries.
+    pub fn execute_pipeline_streaming(
+        &mut self,
+        pipeline: &Pipeline,
+        mut on_response: impl FnMut(usize, Response) -> ControlFlow<()>,
+    ) -> ClientResult<()> {
+        let mut idx = 0;
+        let mut stopped = false;
+        self.run_pipeline(pipeline, |resp| {
+            if !stopped {
+                stopped = on_response(idx, resp).is_break();
+            }
+            idx += 1;
+        })
+    }
+    /// Send a pipeline and pass every decoded response to `on_response`
+    fn run_pipeline(
+        &mut self,
+        pipeline: &Pipeline,
+        mut on_response: impl FnMut(Response),
+    ) -> ClientResult<()> {
+        if pipeline.query_count() == 0 {
+            return Ok(());
+        }
+        self.check_broken()?;
+        self.buffer.clear();
+        pipeline.write_packet(&mut self.buffer).unwrap();
+        pipeline.log_packet(&self.buffer, self.redact_logs);
+        self.send_packet(pipeline.query_count())?;
+        let mut state = MRespState::default();
+        let mut cursor = 0;
+        loop {
+            self.read_more()?;
+            let mut decoder = Decoder::new(&self.buffer, cursor)
+                .lossy_utf8(self.lossy_utf8)
+                .max_size(self.max_response_size);
+            let decoded = state.decoded();
+            let result =
+                decoder.validate_pipe(pipeline.query_count(), &mut state, &mut on_response);
+            self.unread_responses -= state.decoded() - decoded;
+            self.read_in_buffer += state.decoded() - decoded;
+            match result {
+                PipelineResult::Completed => {
+                    self.broken = false;
+                    self.release_buffer();
+                    return Ok(());
+                }
+                PipelineResult::Pending if state.at_boundary() => {
+                    // everything read so far has been decoded
+                    self.buffer.clear();
+                    self.read_in_buffer = 0;
+                    cursor = 0;
+                }
+                PipelineResult::Pending => cursor = decoder.position(),
+                PipelineResult::Error(e) => return Err(Error::ProtocolError(e)),
+            }
+        }
+    }
+    /// Run a query, only checking that it succeeded. Unlike `query_parse::<()>`, this accepts any successful response
+    /// (such as a count of affected rows) and not just [`Response::Empty`]
+    pub fn exec(&mut self, q: &Query) -> ClientResult<()> {
+        self.exec_affected(q).map(|_| ())
+    }
+    /// Run a query, returning the number of affected rows if the server returned one (see [`Self::exec`])
+    pub fn exec_affected(&mut self, q: &Query) -> ClientResult<Option<u64>> {
+        self.query(q).and_then(Response::into_affected)
+    }
+    /// Run a write query (`insert`, `update` or `delete`) and return the number of rows that it changed
+    ///
+    /// If the server returns a count, it is returned as is. A [`Response::Empty`] is counted as one row, since a DML query
+    /// that doesn't return a count operates on exactly one row. Any other response is a
+    /// [`ParseError::ResponseMismatch`](crate::error::ParseError::ResponseMismatch)
+    pub fn query_affected(&mut self, q: &Query) -> ClientResult<u64> {
+        self.query(q).and_then(Response::into_affected_count)
+    }
     /// Run and parse a query into the indicated type. The type must implement [`FromResponse`]
     pub fn query_parse<T: FromResponse>(&mut self, q: &Query) -> ClientResult<T> {
         self.query(q).and_then(FromResponse::from_response)
     }
+    /// Run a query that selects at most one row (usually by its primary key), and parse the row into the indicated type.
+    ///
+    /// When no row matches, Skytable returns error code [`ERR_ROW_NOT_FOUND`](crate::error::ERR_ROW_NOT_FOUND) (and not
+    /// an empty result), which is returned as `Ok(None)` here. Every other error is returned as is
+    pub fn query_row_opt<T: FromResponse>(&mut self, q: &Query) -> ClientResult<Option<T>> {
+        self.query(q).and_then(Response::into_row_opt)
+    }
+    /// Run a DDL query (like `create space` or `create model`), returning `true` if it created the object and `false` if
+    /// the server reported that it already exists (error code
+    /// [`ERR_OBJECT_ALREADY_EXISTS`](crate::error::ERR_OBJECT_ALREADY_EXISTS)). Every other error is returned as is, so
+    /// this is an easy way to make schema setup idempotent. This is the same as `query_parse::<bool>`
+    pub fn ensure_created(&mut self, q: &Query) -> ClientResult<bool> {
+        self.query_parse::<bool>(q)
+    }
+    /// Switch to the given space, or `space.model` (like running `use myspace`), so that later queries can refer to its
+    /// models without qualifying them. The names are validated first (see [`ident`](crate::query::ident))
+    pub fn use_entity(&mut self, entity: &str) -> ClientResult<()> {
+        self.query_parse::<()>(&Query::use_entity(entity)?)?;
+        self.current_space = entity.split('.').next().map(ToOwned::to_owned);
+        Ok(())
+    }
+    /// Switch to the given space (like running `use myspace`) and remember it as this connection's default space, so
+    /// that later queries can refer to its models without qualifying them. See [`Self::current_space`]
+    pub fn set_default_space(&mut self, space: &str) -> ClientResult<()> {
+        self.query_parse::<()>(&Query::use_space(space)?)?;
+        self.current_space = Some(space.to_owned());
+        Ok(())
+    }
+    /// Returns the space that was last switched to with [`Self::set_default_space`] or [`Self::use_entity`], without
+    /// asking the server. A `use` query that you run yourself isn't tracked, so use [`Self::whereami`] if you do that
+    pub fn current_space(&self) -> Option<&str> {
+        self.current_space.as_deref()
+    }
+    /// Run a query in the given space, first switching to it (and making it the default space) if it isn't already
+    /// the current space
+    pub fn query_in(&mut self, space: &str, q: &Query) -> ClientResult<Response> {
+        if self.current_space() != Some(space) {
+            self.set_default_space(space)?;
+        }
+        self.query(q)
+    }
+    /// Returns the space or model that this connection is currently using (see [`Entity`])
+    pub fn whereami(&mut self) -> ClientResult<Entity> {
+        self.query_parse(&Query::new("use $current"))
+    }
+    /// Run `inspect global`, returning the spaces and users on the server (see [`GlobalInfo`])
+    pub fn inspect_global(&mut self) -> ClientResult<GlobalInfo> {
+        self.query_parse(&Query::new("inspect global"))
+    }
+    /// Fetch the columns of the model `space.model` with `inspect model`, to check queries against them (see
+    /// [`Query::check_against`])
+    pub fn describe_model(&mut self, entity: &str) -> ClientResult<ModelSchema> {
+        self.query_parse(&crate::schema::inspect_model(entity)?)
+    }
+    /// Returns true if a query on this connection failed before its response was completely read
+    ///
+    /// This happens when the connection is lost (for example, because the server was restarted) or the server sends an
+    /// invalid response. Every later query on such a connection fails with an I/O error, so you should discard it and
+    /// connect again. The connection pool does this for you. [`Self::resync`] can sometimes recover the connection
+    /// instead
+    pub fn is_broken(&self) -> bool {
+        self.broken
+    }
+    /// Try to make a broken connection (see [`Self::is_broken`]) usable again, by reading and discarding the rest of the
+    /// responses to the query (or pipeline) that failed. If this succeeds, the connection is no longer broken
+    ///
+    /// This is best-effort, and is meant for advanced use and tests: reconnecting is usually simpler. It only works if
+    /// the responses can still arrive (like after a timeout). It fails, leaving the connection broken, if the data that
+    /// was already read can't be decoded (after a protocol error), if more data follows the last response, or if the
+    /// query was only partly sent. It waits for the server, so it blocks until the responses arrive or the connection
+    /// fails. Nothing happens if the connection isn't broken
+    ///
+    /// ## Example
+    /// ```no_run
+    /// use skytable::{query, Config};
+    ///
+    /// let mut db = Config::new_default("username", "password").connect().unwrap();
+    /// if db.query(&query!("sysctl report status")).is_err() && db.is_broken() && db.resync().is_err() {
+    ///     // the connection can't be used any more
+    ///     db = Config::new_default("username", "password").connect().unwrap();
+    /// }
+    /// ```
+    pub fn resync(&mut self) -> ClientResult<()> {
+        if !self.broken {
+            return Ok(());
+        }
+        if self.unread_responses == 0 {
+            return Err(Error::IoError(std::io::Error::new(
+                std::io::ErrorKind::BrokenPipe,
+                "the query was only partly sent, so the connection can't be resynced",
+            )));
+        }
+        // the buffer starts at a response boundary, so decode from there, skipping the responses that were already read
+        let mut state = RState::default();
+        let mut cursor = 0;
+        let mut decoded = 0;
+        while self.unread_responses != 0 {
+            if cursor == self.buffer.len() {
+                self.read_more()?;
+            }
+            let mut decoder = Decoder::new(&self.buffer, cursor)
+                .lossy_utf8(self.lossy_utf8)
+                .max_size(self.max_response_size);
+            let result = decoder.validate_response(state);
+            cursor = decoder.position();
+            match result {
+                DecodeState::Completed(_) => {
+                    state = RState::default();
+                    decoded += 1;
+                    if decoded > self.read_in_buffer {
+                        self.read_in_buffer = decoded;
+                        self.unread_responses -= 1;
+                    }
+                }
+                DecodeState::ChangeState(new_state) => {
+                    state = new_state;
+                    self.read_more()?;
+                }
+                DecodeState::Error(e) => return Err(e.into()),
+            }
+        }
+        if cursor != self.buffer.len() {
+            return Err(ProtocolError::invalid_data()
+                .locate(&self.buffer, cursor + 1)
+                .into());
+        }
+        self.buffer.clear();
+        self.read_in_buffer = 0;
+        self.broken = false;
+        Ok(())
+    }
+    /// Returns the number of queries sent on this connection, counting every query in a pipeline
+    pub fn queries_sent(&self) -> u64 {
+        self.queries_sent
+    }
+    /// Returns the number of bytes sent on this connection (excluding the handshake)
+    pub fn bytes_sent(&self) -> u64 {
+        self.bytes_sent
+    }
+    /// Returns the number of bytes received on this connection (excluding the handshake)
+    pub fn bytes_received(&self) -> u64 {
+        self.bytes_received
+    }
     /// Call this if the internally allocated buffer is growing too large and impacting your performance. However, normally
     /// you will not need to call this
     pub fn reset_buffer(&mut self) {
         self.buffer.shrink_to_fit()
     }
+    /// Read the next chunk of the response (at most `read_buffer_size` bytes) into the end of the buffer, returning the
+    /// number of bytes read. This fails if the buffered response grows beyond `max_response_size`
+    fn read_more(&mut self) -> ClientResult<usize> {
+        let len = self.buffer.len();
+        self.buffer.resize(len + self.read_buffer_size, 0);
+        let n = self.con.read(&mut self.buffer[len..]);
+        self.buffer.truncate(len + *n.as_ref().unwrap_or(&0));
+        let n = match n? {
+            0 => return Err(Error::IoError(std::io::ErrorKind::ConnectionReset.into())),
+            n => n,
+        };
+        self.bytes_received += n as u64;
+        match self.max_response_size {
+            Some(limit) if self.buffer.len() > limit => {
+                Err(ProtocolError::response_too_large(limit)
+                    .locate(&self.buffer, self.buffer.len())
+                    .into())
+            }
+            _ => Ok(n),
+        }
+    }
+    /// Shrink the buffer if it retains more memory than allowed by the configuration
+    fn release_buffer(&mut self) {
+        if let Some(max) = self.max_retained_buffer {
+            if self.buffer.capacity() > max {
+                self.buffer.clear();
+                self.buffer.shrink_to(max);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+/// An in-memory stream that replays `rx` to the reader and collects everything written into `tx`. Every chunk in `rx` is
+/// returned by separate reads
+pub(crate) struct MockStream {
+    rx: std::collections::VecDeque<std::io::Cursor<Vec<u8>>>,
+    tx: Vec<u8>,
+    reads: usize,
+    max_write: Option<usize>,
+}
+
+#[cfg(test)]
+impl MockStream {
+    pub(crate) fn new(rx: &[u8]) -> Self {
+        Self::chunked(&[rx])
+    }
+    pub(crate) fn chunked(rx: &[&[u8]]) -> Self {
+        Self {
+            rx: rx
+                .iter()
+                .map(|c| std::io::Cursor::new(c.to_vec()))
+                .collect(),
+            tx: vec![],
+            reads: 0,
+            max_write: None,
+        }
+    }
+    /// Accept at most `max_write` bytes per write, like a socket with a full send buffer
+    pub(crate) fn max_write(mut self, max_write: usize) -> Self {
+        self.max_write = Some(max_write);
+        self
+    }
+}
+
+#[cfg(test)]
+impl Read for MockStream {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.reads += 1;
+        while let Some(chunk) = self.rx.front_mut() {
+            match chunk.read(buf)? {
+                0 => {
+                    self.rx.pop_front();
+                }
+                n => return Ok(n),
+            }
+        }
+        Ok(0)
+    }
+}
+
+#[cfg(test)]
+impl Write for MockStream {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let n = self.max_write.map_or(buf.len(), |max| buf.len().min(max));
+        self.tx.write(&buf[..n])
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn t_query_mock() {
+    let q = query!("select * from myspace.mymodel where username = ?", "sayan");
+    let cfg = Config::new_default("username", "password");
+    let mut con = TcpConnection::new(
+        MockStream::new(b"\x115\n\x00\x01\x01\x0D5\nsayan\x0220\n\x0E0\n"),
+        &cfg,
+    );
+    let (_, _, username, age, _): (
+        Option<String>,
+        bool,
+        String,
+        u8,
+        Vec<crate::response::Value>,
+    ) = con.query_parse(&q).unwrap();
+    assert_eq!(username, "sayan");
+    assert_eq!(age, 20);
+    assert_eq!(con.con.tx, q.debug_encode_packet());
+}
+
+#[test]
+fn t_connect_over() {
+    let cfg = Config::new_default("username", "password");
+    let mut con = cfg
+        .connect_over(MockStream::chunked(&[b"H\x00\x00\x00", b"\x12"]))
+        .unwrap();
+    assert_eq!(con.con.tx, ClientHandshake::new(&cfg).inner());
+    assert_eq!(
+        con.query(&query!("sysctl report status")).unwrap(),
+        Response::Empty
+    );
+    assert!(matches!(
+        cfg.connect_over(MockStream::new(b"H\x00\x01\x03")),
+        Err(Error::ConnectionSetupErr(
+            ConnectionSetupError::BadCredentials
+        ))
+    ));
+    assert!(matches!(
+        cfg.connect_over(MockStream::new(b"H\x00\x01\x06")),
+        Err(Error::ConnectionSetupErr(
+            ConnectionSetupError::HandshakeError(6)
+        ))
+    ));
+}
+
+#[cfg(feature = "socks")]
+#[test]
+fn t_connect_via_socks5() {
+    let proxy = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let proxy_addr = proxy.local_addr().unwrap();
+    let cfg = Config::new("skyd.internal", 2003, "username", "password");
+    let handshake = ClientHandshake::new(&cfg).inner().to_vec();
+    let server = std::thread::spawn(move || {
+        let (mut stream, _) = proxy.accept().unwrap();
+        // greeting: version 5, one method, no authentication
+        let mut greeting = [0u8; 3];
+        stream.read_exact(&mut greeting).unwrap();
+        assert_eq!(greeting, [5, 1, 0]);
+        stream.write_all(&[5, 0]).unwrap();
+        // connect to a domain name, which is left to the proxy to resolve
+        let mut request = [0u8; 5 + 13 + 2];
+        stream.read_exact(&mut request).unwrap();
+        assert_eq!(request[..5], [5, 1, 0, 3, 13]);
+        assert_eq!(&request[5..18], b"skyd.internal");
+        assert_eq!(request[18..], 2003u16.to_be_bytes());
+        stream.write_all(&[5, 0, 0, 1, 0, 0, 0, 0, 0, 0]).unwrap();
+        // from here on, the proxy relays the connection to the server
+        let mut client_handshake = vec![0u8; handshake.len()];
+        stream.read_exact(&mut client_handshake).unwrap();
+        assert_eq!(client_handshake, handshake);
+        stream.write_all(b"H\x00\x00\x00").unwrap();
+        let mut buf = [0u8; 256];
+        let _ = stream.read(&mut buf).unwrap();
+        stream.write_all(b"\x12").unwrap();
+    });
+    let mut db = cfg.connect_via_socks5(proxy_addr).unwrap();
+    assert_eq!(
+        db.query(&query!("sysctl report status")).unwrap(),
+        Response::Empty
+    );
+    server.join().unwrap();
+}
+
+#[test]
+fn t_resync() {
+    let cfg = Config::new_default("username", "password");
+    let q = query!("select * from myspace.mymodel");
+    let rest = |con: &mut TcpConnection<MockStream>, rx: &[u8]| {
+        con.con.rx.push_back(std::io::Cursor::new(rx.to_vec()))
+    };
+    // a connection that isn't broken is left alone
+    let mut con = TcpConnection::new(MockStream::new(b""), &cfg);
+    con.resync().unwrap();
+    assert_eq!(con.con.reads, 0);
+    // the rest of the row arrives late
+    let mut con = TcpConnection::new(MockStream::new(b"\x115\n\x00\x01"), &cfg);
+    assert!(con.query(&q).is_err());
+    assert!(con.is_broken());
+    // a query on a broken connection fails without touching what was read
+    assert!(con.query(&q).is_err());
+    rest(&mut con, b"\x01\x0D5\nsayan\x0220\n\x0E0\n");
+    con.resync().unwrap();
+    assert!(!con.is_broken());
+    rest(&mut con, b"\x12");
+    assert_eq!(con.query(&q).unwrap(), Response::Empty);
+    // a pipeline that failed after its first response
+    let pipeline = crate::pipeline! { ("sysctl report status"); ("sysctl report status"); };
+    let mut con = TcpConnection::new(MockStream::new(b"\x12\x0D5\nsa"), &cfg);
+    assert!(con.execute_pipeline(&pipeline).is_err());
+    rest(&mut con, b"yan");
+    con.resync().unwrap();
+    rest(&mut con, b"\x12");
+    assert_eq!(con.query(&q).unwrap(), Response::Empty);
+    // garbage can't be resynced
+    let mut con = TcpConnection::new(MockStream::new(b"\xFFgarbage"), &cfg);
+    assert!(con.query(&q).is_err());
+    rest(&mut con, b"\x12");
+    assert!(matches!(con.resync(), Err(Error::ProtocolError(_))));
+    assert!(con.is_broken());
+    // and neither can a response that's followed by more data
+    let mut con = TcpConnection::new(MockStream::new(b"\x0D5\nsa"), &cfg);
+    assert!(con.query(&q).is_err());
+    rest(&mut con, b"yan\x12");
+    assert!(matches!(con.resync(), Err(Error::ProtocolError(_))));
+    assert!(con.is_broken());
+}
+
+#[test]
+fn t_failover() {
+    // nothing listens on this port once the listener is dropped
+    let dead = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let live = listener.local_addr().unwrap().port();
+    let server = std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut buf = [0u8; 256];
+        let _ = stream.read(&mut buf).unwrap();
+        stream.write_all(b"H\x00\x00\x00").unwrap();
+    });
+    let cfg = Config::new_default("username", "password")
+        .with_hosts(&[("127.0.0.1", dead), ("127.0.0.1", live)]);
+    assert_eq!(cfg.port(), dead);
+    cfg.connect().unwrap();
+    server.join().unwrap();
+    // if every endpoint fails, the last error is returned
+    let cfg = cfg.with_hosts(&[("127.0.0.1", live), ("127.0.0.1", dead)]);
+    match cfg.connect() {
+        Err(Error::IoError(e)) => assert_eq!(e.kind(), std::io::ErrorKind::ConnectionRefused),
+        r => panic!("expected an error, got {:?}", r.map(|_| ())),
+    }
+}
+
+#[test]
+fn t_query_timeout() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let server = std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut buf = [0u8; 256];
+        let _ = stream.read(&mut buf).unwrap();
+        stream.write_all(b"H\x00\x00\x00").unwrap();
+        // answer the first query, but not the second
+        let _ = stream.read(&mut buf).unwrap();
+        stream.write_all(b"\x12").unwrap();
+        let _ = stream.read(&mut buf).unwrap();
+        // wait for the client to hang up
+        while stream.read(&mut buf).unwrap() != 0 {}
+    });
+    let mut db = Config::new("127.0.0.1", port, "username", "password")
+        .connect()
+        .unwrap();
+    let q = query!("sysctl report status");
+    assert_eq!(
+        db.query_timeout(&q, Duration::from_secs(10)).unwrap(),
+        Response::Empty
+    );
+    assert_eq!(db.0.con.read_timeout().unwrap(), None);
+    assert!(matches!(
+        db.query_timeout(&q, Duration::from_millis(50)),
+        Err(Error::Timeout)
+    ));
+    assert_eq!(db.0.con.read_timeout().unwrap(), None);
+    assert!(db.is_broken());
+    drop(db);
+    server.join().unwrap();
+}
+
+#[test]
+fn t_send_raw() {
+    let q = query!("select * from myspace.mymodel where username = ?", "sayan");
+    let resp = b"\x115\n\x00\x01\x01\x0D5\nsayan\x0220\n\x0E0\n";
+    let cfg = Config::new_default("username", "password");
+    let mut queried = TcpConnection::new(MockStream::new(resp), &cfg);
+    let mut raw = TcpConnection::new(MockStream::new(resp), &cfg);
+    assert_eq!(
+        raw.send_raw(&q.debug_encode_packet()).unwrap(),
+        queried.query(&q).unwrap()
+    );
+    assert_eq!(raw.con.tx, queried.con.tx);
+    assert_eq!(raw.queries_sent(), 1);
+}
+
+#[test]
+fn t_direct_write() {
+    let q = query!(
+        "insert into myspace.mymodel(?, ?)",
+        "sayan",
+        vec![0xAAu8; 1024]
+    );
+    for threshold in [0, q.payload().len()] {
+        let cfg = Config::new_default("username", "password").set_direct_write_threshold(threshold);
+        let mut con = TcpConnection::new(MockStream::new(b"\x12").max_write(7), &cfg);
+        assert_eq!(con.query(&q).unwrap(), Response::Empty);
+        assert_eq!(con.con.tx, q.debug_encode_packet());
+        assert_eq!(con.bytes_sent(), q.debug_encode_packet().len() as u64);
+    }
+    // too small to be written directly
+    let cfg = Config::new_default("username", "password")
+        .set_direct_write_threshold(q.payload().len() + 1);
+    let mut con = TcpConnection::new(MockStream::new(b"\x12").max_write(7), &cfg);
+    assert_eq!(con.query(&q).unwrap(), Response::Empty);
+    assert_eq!(con.con.tx, q.debug_encode_packet());
+}
+
+#[test]
+fn t_select_binary_column() {
+    let cfg = Config::new_default("username", "password");
+    let q = query!("select bin from myspace.mymodel where k = ?", "k3");
+    // the server returns a single selected column as a row with one cell
+    let row = b"\x111\n\x0C4\n\x00\x01\xFE\xFF";
+    let mut con = TcpConnection::new(MockStream::chunked(&[row, row, row, b"\x0C0\n"]), &cfg);
+    let bin3: Vec<u8> = con.query_parse(&q).unwrap();
+    assert_eq!(bin3, [0x00, 0x01, 0xFE, 0xFF]);
+    let bin3: Box<[u8]> = con.query_parse(&q).unwrap();
+    assert_eq!(&*bin3, [0x00, 0x01, 0xFE, 0xFF]);
+    let bin3: Option<Vec<u8>> = con.query_parse(&q).unwrap();
+    assert_eq!(bin3.as_deref(), Some(&[0x00, 0x01, 0xFE, 0xFF][..]));
+    // and a plain value works too
+    let empty: Vec<u8> = con.query_parse(&q).unwrap();
+    assert!(empty.is_empty());
+}
+
+#[test]
+fn t_read_buffer_size() {
+    let q = query!("select * from myspace.mymodel where username = ?", "sayan");
+    let resp = b"\x115\n\x00\x01\x01\x0D5\nsayan\x0220\n\x0E0\n";
+    // the whole response is read at once by default
+    let cfg = Config::new_default("username", "password");
+    let mut con = TcpConnection::new(MockStream::new(resp), &cfg);
+    con.query(&q).unwrap();
+    assert_eq!(con.con.reads, 1);
+    // but only 4 bytes at a time here
+    let cfg = cfg.set_read_buffer_size(4);
+    let mut con = TcpConnection::new(MockStream::new(resp), &cfg);
+    let (_, _, username, age, _): (
+        Option<String>,
+        bool,
+        String,
+        u8,
+        Vec<crate::response::Value>,
+    ) = con.query_parse(&q).unwrap();
+    assert_eq!((username.as_str(), age), ("sayan", 20));
+    assert_eq!(con.con.reads, resp.len().div_ceil(4));
+    assert_eq!(con.bytes_received(), resp.len() as u64);
+    // the size can't be zero
+    assert_eq!(cfg.set_read_buffer_size(0).read_buffer_size(), 1);
+}
+
+#[test]
+fn t_broken() {
+    let cfg = Config::new_default("username", "password");
+    let q = query!("select * from myspace.mymodel");
+    // a server error is a complete response
+    let mut con = TcpConnection::new(MockStream::new(b"\x10\x05\x00"), &cfg);
+    assert!(matches!(con.query(&q), Ok(Response::Error(5))));
+    assert!(!con.is_broken());
+    // but a connection that's lost midway (here, with the rest of the row unread) isn't
+    let mut con = TcpConnection::new(MockStream::new(b"\x115\n\x00\x01"), &cfg);
+    match con.query(&q) {
+        Err(Error::IoError(e)) => assert_eq!(e.kind(), std::io::ErrorKind::ConnectionReset),
+        r => panic!("expected an error, got {:?}", r),
+    }
+    assert!(con.is_broken());
+    match con.query(&q) {
+        Err(Error::IoError(e)) => assert_eq!(e.kind(), std::io::ErrorKind::BrokenPipe),
+        r => panic!("expected an error, got {:?}", r),
+    }
+}
+
+#[test]
+fn t_max_response_size() {
+    let q = query!("select * from myspace.mymodel where username = ?", "sayan");
+    let cfg = Config::new_default("username", "password").set_max_response_size(64);
+    // the server claims a huge string, but never sends it
+    let mut con = TcpConnection::new(MockStream::new(b"\x0D99999999999\nsay"), &cfg);
+    match con.query(&q) {
+        Err(Error::ProtocolError(e))
+            if *e.kind() == crate::error::ProtocolErrorKind::ResponseTooLarge { limit: 64 } => {}
+        other => panic!("expected the response to be too large, got {:?}", other),
+    }
+    // the response is made up of small values, but there's just too much of it
+    let mut resp = b"\x0E".to_vec();
+    resp.extend_from_slice(b"50\n");
+    for _ in 0..50 {
+        resp.extend_from_slice(b"\x0220\n");
+    }
+    let mut con = TcpConnection::new(MockStream::new(&resp), &cfg.set_read_buffer_size(16));
+    match con.query(&q) {
+        Err(Error::ProtocolError(e))
+            if *e.kind() == crate::error::ProtocolErrorKind::ResponseTooLarge { limit: 64 } => {}
+        other => panic!("expected the response to be too large, got {:?}", other),
+    }
+    assert!(con.bytes_received() <= 64 + 16);
+}
+
+#[test]
+fn t_run_str() {
+    let cfg = Config::new_default("username", "password");
+    let mut con = TcpConnection::new(MockStream::new(b"\x12"), &cfg);
+    con.run_str::<()>("create space x").unwrap();
+    assert_eq!(con.con.tx, query!("create space x").debug_encode_packet());
+    assert_eq!(con.queries_sent(), 1);
+}
+
+#[test]
+fn t_exec() {
+    let q = query!("delete from myspace.mymodel where username = ?", "sayan");
+    let cfg = Config::new_default("username", "password");
+    for (resp, affected) in [
+        (&b"\x12"[..], None),
+        (b"\x021\n", Some(1)),
+        (b"\x0D5\nsayan", None),
+    ] {
+        let mut con = TcpConnection::new(MockStream::new(resp), &cfg);
+        assert_eq!(con.exec_affected(&q).unwrap(), affected);
+        let mut con = TcpConnection::new(MockStream::new(resp), &cfg);
+        con.exec(&q).unwrap();
+    }
+    let mut con = TcpConnection::new(MockStream::new(b"\x10\x67\x00"), &cfg);
+    assert!(matches!(con.exec(&q), Err(Error::ServerError(103))));
+}
+
+#[test]
+fn t_query_row_opt() {
+    let q = query!(
+        "select username, age from myspace.mymodel where username = ?",
+        "sayan"
+    );
+    let cfg = Config::new_default("username", "password");
+    let mut con = TcpConnection::new(MockStream::new(b"\x112\n\x0D5\nsayan\x0220\n"), &cfg);
+    assert_eq!(
+        con.query_row_opt::<(String, u8)>(&q).unwrap(),
+        Some(("sayan".to_owned(), 20))
+    );
+    // no such row
+    let mut con = TcpConnection::new(MockStream::new(b"\x10\x6f\x00"), &cfg);
+    assert_eq!(con.query_row_opt::<(String, u8)>(&q).unwrap(), None);
+    // an unindexed where clause is a bug in the query, not a missing row
+    let mut con = TcpConnection::new(MockStream::new(b"\x10\x6e\x00"), &cfg);
+    assert!(matches!(
+        con.query_row_opt::<(String, u8)>(&q),
+        Err(Error::ServerError(110))
+    ));
+    // other errors are still errors
+    let mut con = TcpConnection::new(MockStream::new(b"\x10\x64\x00"), &cfg);
+    assert!(matches!(
+        con.query_row_opt::<(String, u8)>(&q),
+        Err(Error::ServerError(100))
+    ));
+}
+
+#[test]
+fn t_ensure_created() {
+    let q = query!("create space myspace");
+    let cfg = Config::new_default("username", "password");
+    let mut con = TcpConnection::new(MockStream::new(b"\x12"), &cfg);
+    assert!(con.ensure_created(&q).unwrap());
+    // already exists
+    let mut con = TcpConnection::new(MockStream::new(b"\x10\x67\x00"), &cfg);
+    assert!(!con.ensure_created(&q).unwrap());
+    // other errors are still errors
+    let mut con = TcpConnection::new(MockStream::new(b"\x10\x64\x00"), &cfg);
+    assert!(matches!(
+        con.ensure_created(&q),
+        Err(Error::ServerError(100))
+    ));
+    // and so is anything that isn't a DDL response or a bool
+    let mut con = TcpConnection::new(MockStream::new(b"\x112\n\x0220\n\x0221\n"), &cfg);
+    assert!(matches!(
+        con.ensure_created(&q),
+        Err(Error::ParseError(
+            crate::error::ParseError::ResponseMismatch
+        ))
+    ));
+}
+
+#[test]
+fn t_query_affected() {
+    let q = query!(
+        "update myspace.mymodel set followers += ? where username = ?",
+        1u64,
+        "sayan"
+    );
+    let cfg = Config::new_default("username", "password");
+    let mut con = TcpConnection::new(MockStream::new(b"\x12"), &cfg);
+    assert_eq!(con.query_affected(&q).unwrap(), 1);
+    let mut con = TcpConnection::new(MockStream::new(b"\x0542\n"), &cfg);
+    assert_eq!(con.query_affected(&q).unwrap(), 42);
+    let mut con = TcpConnection::new(MockStream::new(b"\x0D5\nsayan"), &cfg);
+    assert!(matches!(
+        con.query_affected(&q),
+        Err(Error::ParseError(
+            crate::error::ParseError::ResponseMismatch
+        ))
+    ));
+}
+
+#[test]
+fn t_counters() {
+    let q = query!("select * from myspace.mymodel where username = ?", "sayan");
+    let mut pipeline = crate::query::Pipeline::new();
+    pipeline.add_query(&q);
+    pipeline.add_query(&q);
+    let cfg = Config::new_default("username", "password");
+    let mut con = TcpConnection::new(
+        MockStream::chunked(&[b"\x12", b"\x12", b"\x0542\n", b"\x12\x12"]),
+        &cfg,
+    );
+    assert_eq!(
+        (con.queries_sent(), con.bytes_sent(), con.bytes_received()),
+        (0, 0, 0)
+    );
+    con.exec(&q).unwrap();
+    con.exec(&q).unwrap();
+    assert_eq!(con.query_affected(&q).unwrap(), 42);
+    con.execute_pipeline(&pipeline).unwrap();
+    assert_eq!(con.queries_sent(), 5);
+    assert_eq!(con.bytes_sent(), con.con.tx.len() as u64);
+    assert_eq!(con.bytes_received(), 8);
+}
+
+#[test]
+fn t_lossy_utf8() {
+    let q = query!("select username from myspace.mymodel where id = ?", 1u64);
+    let cfg = Config::new_default("username", "password");
+    let mut con = TcpConnection::new(MockStream::new(b"\x0D5\nsa\xFFan"), &cfg);
+    assert!(matches!(
+        con.query(&q),
+        Err(Error::ProtocolError(e))
+            if *e.kind() == crate::error::ProtocolErrorKind::InvalidServerResponseForData
+                && e.offset() == 7
+    ));
+    let cfg = cfg.set_lossy_utf8(true);
+    let mut con = TcpConnection::new(MockStream::new(b"\x0D5\nsa\xFFan"), &cfg);
+    assert_eq!(con.query_parse::<String>(&q).unwrap(), "sa\u{FFFD}an");
+}
+
+#[test]
+fn t_run_prepared() {
+    let pq = crate::query::PreparedQuery::new("select * from myspace.mymodel where username = ?");
+    let cfg = Config::new_default("username", "password");
+    let mut con = TcpConnection::new(MockStream::chunked(&[b"\x0220\n", b"\x0221\n"]), &cfg);
+    assert_eq!(
+        con.run_prepared(&pq, "sayan").unwrap(),
+        Response::Value(crate::response::Value::UInt8(20))
+    );
+    assert_eq!(
+        con.run_prepared(&pq, "elana").unwrap(),
+        Response::Value(crate::response::Value::UInt8(21))
+    );
+    let mut expected = query!(pq.query_str(), "sayan").debug_encode_packet();
+    expected.extend(query!(pq.query_str(), "elana").debug_encode_packet());
+    assert_eq!(con.con.tx, expected);
+    assert_eq!(con.queries_sent(), 2);
+}
+
+#[test]
+fn t_pipeline_mock() {
+    use crate::query::Pipeline;
+    let mut pipeline = Pipeline::new();
+    pipeline.add_query(&query!("create space myspace"));
+    pipeline.add_query(&query!("create space myspace"));
+    let cfg = Config::new_default("username", "password");
+    let mut con = TcpConnection::new(MockStream::new(b"\x12\x10\x67\x00"), &cfg);
+    assert_eq!(
+        con.execute_pipeline(&pipeline).unwrap(),
+        [Response::Empty, Response::Error(103)]
+    );
+    assert_eq!(
+        con.con.tx,
+        b"P50\n20\n0\ncreate space myspace20\n0\ncreate space myspace"
+    );
+    // nothing is sent for an empty pipeline
+    let mut con = TcpConnection::new(MockStream::new(b""), &cfg);
+    assert_eq!(con.execute_pipeline(&Pipeline::new()).unwrap(), []);
+    assert!(con.con.tx.is_empty());
+}
+
+#[test]
+fn t_pipeline_streaming() {
+    use crate::{query::Pipeline, response::Value};
+    let pipeline: Pipeline = (0..4u64)
+        .map(|i| query!("select * from myspace.mymodel where id = ?", i))
+        .collect();
+    let responses: &[&[u8]] = &[b"\x020\n\x02", b"1\n", b"\x022\n\x023\n", b"\x12"];
+    let cfg = Config::new_default("username", "password");
+    // all
+    let mut con = TcpConnection::new(MockStream::chunked(responses), &cfg);
+    let mut seen = vec![];
+    con.execute_pipeline_streaming(&pipeline, |idx, resp| {
+        seen.push((idx, resp));
+        ControlFlow::Continue(())
+    })
+    .unwrap();
+    assert_eq!(
+        seen,
+        (0..4)
+            .map(|i| (i, Response::Value(Value::UInt8(i as u8))))
+            .collect::<Vec<_>>()
+    );
+    // stop early
+    let mut con = TcpConnection::new(MockStream::chunked(responses), &cfg);
+    let mut seen = vec![];
+    con.execute_pipeline_streaming(&pipeline, |idx, _| {
+        seen.push(idx);
+        if idx == 1 {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    })
+    .unwrap();
+    assert_eq!(seen, [0, 1]);
+    // the rest of the pipeline was drained, so the connection is still in sync
+    assert_eq!(
+        con.query(&query!("sysctl report status")).unwrap(),
+        Response::Empty
+    );
+}
+
+#[test]
+fn t_max_retained_buffer() {
+    let mut big_response = vec![0x0D];
+    pushlen!(big_response, 1024 * 1024);
+    big_response.extend(vec![b'a'; 1024 * 1024]);
+    let q = query!("select * from myspace.mymodel where username = ?", "sayan");
+    // default: keep the buffer
+    let cfg = Config::new_default("username", "password");
+    let mut con = TcpConnection::new(MockStream::new(&big_response), &cfg);
+    con.query(&q).unwrap();
+    assert!(con.buffer.capacity() >= 1024 * 1024);
+    // shrink
+    let cfg = cfg.set_max_retained_buffer(crate::BUFSIZE);
+    let mut con = TcpConnection::new(MockStream::new(&big_response), &cfg);
+    assert_eq!(
+        con.query(&q).unwrap(),
+        Response::Value(crate::response::Value::String("a".repeat(1024 * 1024)))
+    );
+    assert!(con.buffer.capacity() <= crate::BUFSIZE);
+}
+
+#[test]
+#[cfg(feature = "logging")]
+fn t_log_packet() {
+    use std::sync::Mutex;
+    static RECORDS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+    struct TestLogger;
+    impl log::Log for TestLogger {
+        fn enabled(&self, _: &log::Metadata) -> bool {
+            true
+        }
+        fn log(&self, record: &log::Record) {
+            if record.level() == log::Level::Trace {
+                RECORDS.lock().unwrap().push(record.args().to_string());
+            }
+        }
+        fn flush(&self) {}
+    }
+    log::set_logger(&TestLogger).unwrap();
+    log::set_max_level(log::LevelFilter::Trace);
+    let q = query!("select * from myspace.mymodel where username = ?", "sayan");
+    // plain
+    let cfg = Config::new_default("username", "password");
+    let mut con = TcpConnection::new(MockStream::new(b"\x12"), &cfg);
+    assert_eq!(con.query(&q).unwrap(), Response::Empty);
+    // redacted
+    let cfg = cfg.set_redact_logs(true);
+    let mut con = TcpConnection::new(MockStream::new(b"\x12"), &cfg);
+    assert_eq!(con.query(&q).unwrap(), Response::Empty);
+    let records = RECORDS.lock().unwrap();
+    assert_eq!(
+        *records,
+        [
+            "sending packet: S59\\n48\\nselect * from myspace.mymodel where username = ?\\x065\\nsayan",
+            "sending packet: S59\\n48\\nselect * from myspace.mymodel where username = ?<1 params redacted (8 bytes)>"
+        ]
+    );
+}
+
+#[test]
+fn t_use_entity() {
+    let cfg = Config::new_default("username", "password");
+    let mut con = TcpConnection::new(MockStream::new(b"\x12"), &cfg);
+    con.use_entity("myspace").unwrap();
+    assert!(con.con.tx.ends_with(b"use myspace"));
+    let mut con = TcpConnection::new(MockStream::new(b"\x12"), &cfg);
+    assert!(matches!(
+        con.use_entity("myspace; drop space myspace"),
+        Err(Error::InvalidQuery(_))
+    ));
+    assert!(con.con.tx.is_empty());
+}
+
+#[test]
+fn t_whereami() {
+    let cfg = Config::new_default("username", "password");
+    let mut con = TcpConnection::new(MockStream::new(b"\x0D7\nmyspace"), &cfg);
+    assert_eq!(con.whereami().unwrap(), Entity::Space("myspace".into()));
+    assert!(con.con.tx.ends_with(b"use $current"));
+}
+
+#[test]
+fn t_inspect_global() {
+    let cfg = Config::new_default("username", "password");
+    let resp = br#"{"spaces":["myspace"],"users":["root"],"settings":{}}"#;
+    let mut packet = format!("\x0D{}\n", resp.len()).into_bytes();
+    packet.extend(resp);
+    let mut con = TcpConnection::new(MockStream::new(&packet), &cfg);
+    let info = con.inspect_global().unwrap();
+    assert_eq!(info.spaces(), ["myspace"]);
+    assert_eq!(info.users(), ["root"]);
+    assert!(con.con.tx.ends_with(b"inspect global"));
+}
+
+#[test]
+fn t_close() {
+    use std::{net::TcpListener, thread};
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let server = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut buf = [0u8; 64];
+        let _ = stream.read(&mut buf).unwrap();
+        stream.write_all(b"H\x00\x00\x00").unwrap();
+        // returns once the client shuts the stream down
+        stream.read(&mut buf).unwrap()
+    });
+    let db = Config::new("127.0.0.1", port, "username", "password")
+        .connect()
+        .unwrap();
+    db.close().unwrap();
+    assert_eq!(server.join().unwrap(), 0);
+}
+
+#[test]
+fn t_default_space() {
+    let cfg = Config::new_default("username", "password");
+    let q = query!("select * from mymodel");
+    let mut con = TcpConnection::new(
+        MockStream::chunked(&[b"\x12", b"\x12", b"\x12", b"\x12"]),
+        &cfg,
+    );
+    assert_eq!(con.current_space(), None);
+    con.set_default_space("myspace").unwrap();
+    assert!(con.con.tx.ends_with(b"use myspace"));
+    assert_eq!(con.current_space(), Some("myspace"));
+    // already in `myspace`, so only the query is sent
+    con.con.tx.clear();
+    con.query_in("myspace", &q).unwrap();
+    assert!(con.con.tx.ends_with(b"select * from mymodel"));
+    assert!(!con.con.tx.windows(3).any(|w| w == b"use"));
+    // switching happens first
+    con.con.tx.clear();
+    con.query_in("otherspace", &q).unwrap();
+    assert!(con.con.tx.windows(14).any(|w| w == b"use otherspace"));
+    assert_eq!(con.current_space(), Some("otherspace"));
+    // a model is not a space
+    assert!(matches!(
+        con.set_default_space("myspace.mymodel"),
+        Err(Error::InvalidQuery(_))
+    ));
+    // and a failed switch keeps the old space
+    let mut con = TcpConnection::new(MockStream::chunked(&[b"\x12", b"\x10\x64\x00"]), &cfg);
+    con.use_entity("myspace.mymodel").unwrap();
+    assert_eq!(con.current_space(), Some("myspace"));
+    assert!(con.set_default_space("nospace").is_err());
+    assert_eq!(con.current_space(), Some("myspace"));
+}
+
+#[test]
+fn t_describe_model() {
+    let cfg = Config::new_default("username", "password");
+    let resp = b"\x0D39\n{\"decl\":\"{*username: string}\",\"rows\":0}";
+    let mut con = TcpConnection::new(MockStream::new(resp), &cfg);
+    let schema = con.describe_model("myspace.mymodel").unwrap();
+    assert_eq!(schema.columns().len(), 1);
+    assert!(con.con.tx.ends_with(b"inspect model myspace.mymodel"));
+    assert!(matches!(
+        con.describe_model("mymodel"),
+        Err(Error::InvalidQuery(_))
+    ));
 }
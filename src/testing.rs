@@ -0,0 +1,234 @@
+/*
+ * Copyright 2023, Sayan Nandan <nandansayan@outlook.com>
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+
+//! # Testing utilities
+//!
+//! This module (enabled by the `testing` feature) provides a [`MockConnection`] that you can use to unit test your data access
+//! layer without a running Skytable server. Responses are pre-programmed either for a specific query string, or in FIFO order
+//! for any query.
+//!
+//! ## Example
+//!
+//! ```
+//! use skytable::{query, response::{Response, Row, Value}, testing::MockConnection};
+//!
+//! let mut db = MockConnection::builder()
+//!     .respond_to("sysctl report status", Response::Empty)
+//!     .enqueue(Response::Row(Row::new(vec![Value::String("sayan".into()), Value::UInt64(100)])))
+//!     .build();
+//! db.query_parse::<()>(&query!("sysctl report status")).unwrap();
+//! let (username, followers): (String, u64) = db
+//!     .query_parse(&query!("select username, followers from myspace.mymodel where username = ?", "sayan"))
+//!     .unwrap();
+//! assert_eq!(username, "sayan");
+//! assert_eq!(followers, 100);
+//! assert_eq!(db.queries().len(), 2);
+//! ```
+
+use {
+    crate::{
+        error::{ClientResult, Error},
+        query::{Pipeline, PreparedQuery, SQParam},
+        queryable::BlockingQueryable,
+        response::{FromResponse, Response},
+        Query,
+    },
+    std::{
+        collections::{HashMap, VecDeque},
+        ops::ControlFlow,
+    },
+};
+
+#[derive(Debug, Default)]
+/// A builder for a [`MockConnection`]
+pub struct MockConnectionBuilder {
+    keyed: HashMap<Box<str>, Response>,
+    queue: VecDeque<Response>,
+}
+
+impl MockConnectionBuilder {
+    /// Create a new builder with no pre-programmed responses
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Always return `resp` when a query with this exact query string is run
+    pub fn respond_to(mut self, query_str: &str, resp: Response) -> Self {
+        self.keyed.insert(query_str.into(), resp);
+        self
+    }
+    /// Add a response to the queue. Queued responses are returned in FIFO order for any query that doesn't have a response
+    /// set with [`MockConnectionBuilder::respond_to`]
+    pub fn enqueue(mut self, resp: Response) -> Self {
+        self.queue.push_back(resp);
+        self
+    }
+    /// Build the [`MockConnection`]
+    pub fn build(self) -> MockConnection {
+        MockConnection {
+            keyed: self.keyed,
+            queue: self.queue,
+            queries: vec![],
+            pipelines: vec![],
+            current_space: None,
+        }
+    }
+}
+
+#[derive(Debug)]
+/// A mock connection that returns pre-programmed responses, mirroring the query API of a real connection
+pub struct MockConnection {
+    keyed: HashMap<Box<str>, Response>,
+    queue: VecDeque<Response>,
+    queries: Vec<Query>,
+    pipelines: Vec<Pipeline>,
+    current_space: Option<String>,
+}
+
+impl MockConnection {
+    /// Returns a [`MockConnectionBuilder`] to set up the responses
+    pub fn builder() -> MockConnectionBuilder {
+        MockConnectionBuilder::new()
+    }
+    /// Add a response to the back of the queue
+    pub fn enqueue(&mut self, resp: Response) {
+        self.queue.push_back(resp);
+    }
+    /// Returns all the queries run on this connection so far, in order
+    pub fn queries(&self) -> &[Query] {
+        &self.queries
+    }
+    /// Returns all the pipelines executed on this connection so far, in order
+    pub fn pipelines(&self) -> &[Pipeline] {
+        &self.pipelines
+    }
+    /// Run a query and return the pre-programmed [`Response`]
+    ///
+    /// This returns an [`Error::IoError`] if no response was set up for this query
+    pub fn query(&mut self, q: &Query) -> ClientResult<Response> {
+        self.queries.push(q.clone());
+        self.respond(q.query_str())
+    }
+    /// Run a prepared statement and return the pre-programmed [`Response`]. The statement is recorded as a [`Query`]
+    /// (see [`MockConnection::queries`])
+    pub fn run_prepared(
+        &mut self,
+        pq: &PreparedQuery,
+        params: impl SQParam,
+    ) -> ClientResult<Response> {
+        let mut q = Query::new(pq.query_str());
+        q.push_param(params);
+        self.query(&q)
+    }
+    /// Execute a pipeline and return a pre-programmed [`Response`] for every query in it
+    ///
+    /// This returns an [`Error::IoError`] if no response was set up for any of the queries
+    pub fn execute_pipeline(&mut self, pipeline: &Pipeline) -> ClientResult<Vec<Response>> {
+        self.pipelines.push(pipeline.clone());
+        let mut resps = Vec::with_capacity(pipeline.query_count());
+        for (q, _) in pipeline.queries() {
+            resps.push(self.respond(q)?);
+        }
+        Ok(resps)
+    }
+    /// Execute a pipeline, calling `on_response` with the pre-programmed [`Response`] for every query in it until it
+    /// returns [`ControlFlow::Break`]
+    pub fn execute_pipeline_streaming(
+        &mut self,
+        pipeline: &Pipeline,
+        mut on_response: impl FnMut(usize, Response) -> ControlFlow<()>,
+    ) -> ClientResult<()> {
+        for (idx, resp) in self.execute_pipeline(pipeline)?.into_iter().enumerate() {
+            if on_response(idx, resp).is_break() {
+                break;
+            }
+        }
+        Ok(())
+    }
+    fn respond(&mut self, query_str: &str) -> ClientResult<Response> {
+        match self.keyed.get(query_str) {
+            Some(resp) => Ok(resp.clone()),
+            None => self.queue.pop_front().ok_or_else(|| {
+                Error::IoError(std::io::Error::new(
+                    std::io::ErrorKind::UnexpectedEof,
+                    format!("no mock response for query `{}`", query_str),
+                ))
+            }),
+        }
+    }
+    /// See [`TcpConnection::exec`](crate::syncio::TcpConnection::exec)
+    pub fn exec(&mut self, q: &Query) -> ClientResult<()> {
+        BlockingQueryable::exec(self, q)
+    }
+    /// See [`TcpConnection::exec_affected`](crate::syncio::TcpConnection::exec_affected)
+    pub fn exec_affected(&mut self, q: &Query) -> ClientResult<Option<u64>> {
+        self.query(q).and_then(Response::into_affected)
+    }
+    /// See [`TcpConnection::query_affected`](crate::syncio::TcpConnection::query_affected)
+    pub fn query_affected(&mut self, q: &Query) -> ClientResult<u64> {
+        BlockingQueryable::query_affected(self, q)
+    }
+    /// See [`TcpConnection::run_str`](crate::syncio::TcpConnection::run_str)
+    pub fn run_str<T: FromResponse>(&mut self, q: &str) -> ClientResult<T> {
+        self.query_parse(&Query::new(q))
+    }
+    /// See [`TcpConnection::query_parse`](crate::syncio::TcpConnection::query_parse)
+    pub fn query_parse<T: FromResponse>(&mut self, q: &Query) -> ClientResult<T> {
+        BlockingQueryable::query_parse(self, q)
+    }
+    /// See [`TcpConnection::query_row_opt`](crate::syncio::TcpConnection::query_row_opt)
+    pub fn query_row_opt<T: FromResponse>(&mut self, q: &Query) -> ClientResult<Option<T>> {
+        BlockingQueryable::query_row_opt(self, q)
+    }
+    /// See [`TcpConnection::ensure_created`](crate::syncio::TcpConnection::ensure_created)
+    pub fn ensure_created(&mut self, q: &Query) -> ClientResult<bool> {
+        self.query_parse::<bool>(q)
+    }
+    /// See [`TcpConnection::use_entity`](crate::syncio::TcpConnection::use_entity). An invalid entity fails like it would on a real connection
+    pub fn use_entity(&mut self, entity: &str) -> ClientResult<()> {
+        self.query_parse::<()>(&Query::use_entity(entity)?)?;
+        self.current_space = entity.split('.').next().map(ToOwned::to_owned);
+        Ok(())
+    }
+    /// See [`TcpConnection::set_default_space`](crate::syncio::TcpConnection::set_default_space)
+    pub fn set_default_space(&mut self, space: &str) -> ClientResult<()> {
+        self.query_parse::<()>(&Query::use_space(space)?)?;
+        self.current_space = Some(space.to_owned());
+        Ok(())
+    }
+    /// See [`TcpConnection::current_space`](crate::syncio::TcpConnection::current_space)
+    pub fn current_space(&self) -> Option<&str> {
+        self.current_space.as_deref()
+    }
+    /// See [`TcpConnection::query_in`](crate::syncio::TcpConnection::query_in)
+    pub fn query_in(&mut self, space: &str, q: &Query) -> ClientResult<Response> {
+        if self.current_space() != Some(space) {
+            self.set_default_space(space)?;
+        }
+        self.query(q)
+    }
+    /// See [`TcpConnection::describe_model`](crate::syncio::TcpConnection::describe_model)
+    pub fn describe_model(&mut self, entity: &str) -> ClientResult<crate::schema::ModelSchema> {
+        self.query_parse(&crate::schema::inspect_model(entity)?)
+    }
+    /// See [`TcpConnection::whereami`](crate::syncio::TcpConnection::whereami)
+    pub fn whereami(&mut self) -> ClientResult<crate::response::Entity> {
+        self.query_parse(&Query::new("use $current"))
+    }
+    /// See [`TcpConnection::inspect_global`](crate::syncio::TcpConnection::inspect_global)
+    pub fn inspect_global(&mut self) -> ClientResult<crate::response::GlobalInfo> {
+        self.query_parse(&Query::new("inspect global"))
+    }
+}
@@ -0,0 +1,242 @@
+/*
+ * Copyright 2023, Sayan Nandan <nandansayan@outlook.com>
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+
+//! TLS backends
+//!
+//! At least one of the `native-tls` (default) and `rustls` features must be enabled. If both are, `rustls` is used (so
+//! that enabling `rustls` works without disabling the default features). Both backends take the server's
+//! certificate in PEM format and trust it as a root, and neither checks that the certificate was issued for the host
+//! being connected to (Skytable certificates are commonly self-signed for a name other than the server's address).
+
+#[cfg(not(any(feature = "native-tls", feature = "rustls")))]
+compile_error!("a TLS backend is required; enable either the `native-tls` or the `rustls` feature");
+
+#[cfg(all(feature = "native-tls", not(feature = "rustls")))]
+pub(crate) use self::native::*;
+#[cfg(feature = "rustls")]
+pub(crate) use self::rustls_backend::*;
+
+#[cfg(all(feature = "native-tls", not(feature = "rustls")))]
+mod native {
+    use {
+        crate::error::{ClientResult, ConnectionSetupError},
+        native_tls::{Certificate, TlsConnector},
+    };
+
+    pub(crate) type TlsStream = native_tls::TlsStream<std::net::TcpStream>;
+    pub(crate) type TlsStreamAsync = tokio_native_tls::TlsStream<tokio::net::TcpStream>;
+
+    fn connector(cert: &str) -> ClientResult<TlsConnector> {
+        let connector = TlsConnector::builder()
+            .add_root_certificate(Certificate::from_pem(cert.as_bytes()).map_err(|e| {
+                ConnectionSetupError::Other(format!("failed to parse certificate: {e}"))
+            })?)
+            .danger_accept_invalid_hostnames(true)
+            .build()
+            .map_err(|e| {
+                ConnectionSetupError::Other(format!("failed to set up TLS acceptor: {e}"))
+            })?;
+        Ok(connector)
+    }
+
+    pub(crate) fn connect(
+        host: &str,
+        cert: &str,
+        stream: std::net::TcpStream,
+    ) -> ClientResult<TlsStream> {
+        connector(cert)?
+            .connect(host, stream)
+            .map_err(|e| ConnectionSetupError::Other(format!("TLS handshake failed: {e}")).into())
+    }
+
+    pub(crate) async fn connect_async(
+        host: &str,
+        cert: &str,
+        stream: tokio::net::TcpStream,
+    ) -> ClientResult<TlsStreamAsync> {
+        tokio_native_tls::TlsConnector::from(connector(cert)?)
+            .connect(host, stream)
+            .await
+            .map_err(|e| ConnectionSetupError::Other(format!("TLS handshake failed: {e}")).into())
+    }
+
+    /// Returns the TCP stream under the TLS session (to set its read timeout)
+    pub(crate) fn tcp_stream(stream: &TlsStream) -> &std::net::TcpStream {
+        stream.get_ref()
+    }
+
+    /// Send a `close_notify` alert and shut down the TCP stream
+    pub(crate) fn shutdown(mut stream: TlsStream) -> std::io::Result<()> {
+        stream.shutdown()?;
+        stream.get_ref().shutdown(std::net::Shutdown::Both)
+    }
+}
+
+#[cfg(feature = "rustls")]
+mod rustls_backend {
+    use {
+        crate::error::{ClientResult, ConnectionSetupError},
+        rustls::{
+            client::{
+                danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+                WebPkiServerVerifier,
+            },
+            crypto::{ring, CryptoProvider},
+            pki_types::{CertificateDer, ServerName, UnixTime},
+            CertificateError, ClientConfig, ClientConnection, DigitallySignedStruct, Error,
+            RootCertStore, SignatureScheme, StreamOwned,
+        },
+        std::{convert::TryFrom, sync::Arc},
+    };
+
+    pub(crate) type TlsStream = StreamOwned<ClientConnection, std::net::TcpStream>;
+    pub(crate) type TlsStreamAsync = tokio_rustls::client::TlsStream<tokio::net::TcpStream>;
+
+    fn setup_error(e: impl std::fmt::Display) -> ConnectionSetupError {
+        ConnectionSetupError::Other(format!("failed to set up TLS acceptor: {e}"))
+    }
+
+    fn handshake_error(e: impl std::fmt::Display) -> ConnectionSetupError {
+        ConnectionSetupError::Other(format!("TLS handshake failed: {e}"))
+    }
+
+    /// Verifies the certificate chain like webpki does, but accepts a certificate that was issued for a different name
+    /// (the equivalent of native-tls' `danger_accept_invalid_hostnames`)
+    #[derive(Debug)]
+    struct AnyHostname(Arc<WebPkiServerVerifier>);
+
+    impl ServerCertVerifier for AnyHostname {
+        fn verify_server_cert(
+            &self,
+            end_entity: &CertificateDer<'_>,
+            intermediates: &[CertificateDer<'_>],
+            server_name: &ServerName<'_>,
+            ocsp_response: &[u8],
+            now: UnixTime,
+        ) -> Result<ServerCertVerified, Error> {
+            match self.0.verify_server_cert(
+                end_entity,
+                intermediates,
+                server_name,
+                ocsp_response,
+                now,
+            ) {
+                // the chain is only checked against the name after it has been verified
+                Err(Error::InvalidCertificate(
+                    CertificateError::NotValidForName
+                    | CertificateError::NotValidForNameContext { .. },
+                )) => Ok(ServerCertVerified::assertion()),
+                r => r,
+            }
+        }
+        fn verify_tls12_signature(
+            &self,
+            message: &[u8],
+            cert: &CertificateDer<'_>,
+            dss: &DigitallySignedStruct,
+        ) -> Result<HandshakeSignatureValid, Error> {
+            self.0.verify_tls12_signature(message, cert, dss)
+        }
+        fn verify_tls13_signature(
+            &self,
+            message: &[u8],
+            cert: &CertificateDer<'_>,
+            dss: &DigitallySignedStruct,
+        ) -> Result<HandshakeSignatureValid, Error> {
+            self.0.verify_tls13_signature(message, cert, dss)
+        }
+        fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+            self.0.supported_verify_schemes()
+        }
+    }
+
+    fn client_config(cert: &str) -> ClientResult<Arc<ClientConfig>> {
+        let mut roots = RootCertStore::empty();
+        for cert in rustls_pemfile::certs(&mut cert.as_bytes()) {
+            cert.map_err(|e| e.to_string())
+                .and_then(|cert| roots.add(cert).map_err(|e| e.to_string()))
+                .map_err(|e| {
+                    ConnectionSetupError::Other(format!("failed to parse certificate: {e}"))
+                })?;
+        }
+        if roots.is_empty() {
+            return Err(ConnectionSetupError::Other(
+                "failed to parse certificate: no PEM certificate found".into(),
+            )
+            .into());
+        }
+        let provider = Arc::new(ring::default_provider());
+        let verifier = WebPkiServerVerifier::builder_with_provider(roots.into(), provider.clone())
+            .build()
+            .map_err(setup_error)?;
+        let config = ClientConfig::builder_with_provider(provider as Arc<CryptoProvider>)
+            .with_safe_default_protocol_versions()
+            .map_err(setup_error)?
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(AnyHostname(verifier)))
+            .with_no_client_auth();
+        Ok(Arc::new(config))
+    }
+
+    fn server_name(host: &str) -> ClientResult<ServerName<'static>> {
+        ServerName::try_from(host.to_owned()).map_err(|e| {
+            ConnectionSetupError::Other(format!("invalid server name `{host}`: {e}")).into()
+        })
+    }
+
+    pub(crate) fn connect(
+        host: &str,
+        cert: &str,
+        stream: std::net::TcpStream,
+    ) -> ClientResult<TlsStream> {
+        let con =
+            ClientConnection::new(client_config(cert)?, server_name(host)?).map_err(setup_error)?;
+        let mut stream = StreamOwned::new(con, stream);
+        // drive the handshake now so that its errors are reported as such
+        while stream.conn.is_handshaking() {
+            stream
+                .conn
+                .complete_io(&mut stream.sock)
+                .map_err(handshake_error)?;
+        }
+        Ok(stream)
+    }
+
+    /// Returns the TCP stream under the TLS session (to set its read timeout)
+    pub(crate) fn tcp_stream(stream: &TlsStream) -> &std::net::TcpStream {
+        &stream.sock
+    }
+
+    /// Send a `close_notify` alert and shut down the TCP stream
+    pub(crate) fn shutdown(mut stream: TlsStream) -> std::io::Result<()> {
+        stream.conn.send_close_notify();
+        while stream.conn.wants_write() {
+            stream.conn.write_tls(&mut stream.sock)?;
+        }
+        stream.sock.shutdown(std::net::Shutdown::Both)
+    }
+
+    pub(crate) async fn connect_async(
+        host: &str,
+        cert: &str,
+        stream: tokio::net::TcpStream,
+    ) -> ClientResult<TlsStreamAsync> {
+        tokio_rustls::TlsConnector::from(client_config(cert)?)
+            .connect(server_name(host)?, stream)
+            .await
+            .map_err(|e| handshake_error(e).into())
+    }
+}
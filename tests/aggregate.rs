@@ -0,0 +1,26 @@
+use skytable::{query, Config};
+
+#[test]
+#[ignore = "requires a running Skytable server"]
+fn count_against_server() {
+    let mut db = Config::new_default("root", "password").connect().unwrap();
+    db.exec(&query!("create space if not exists agg_test"))
+        .unwrap();
+    db.exec(&query!(
+        "create model if not exists agg_test.users(username: string, followers: uint64)"
+    ))
+    .unwrap();
+    db.exec(&query!("truncate model agg_test.users")).unwrap();
+    for (username, followers) in [("sayan", 100u64), ("elana", 200)].iter() {
+        db.exec(&query!(
+            "insert into agg_test.users(?, ?)",
+            *username,
+            *followers
+        ))
+        .unwrap();
+    }
+    let count: u64 = db
+        .query_parse(&query!("select count(*) from agg_test.users"))
+        .unwrap();
+    assert_eq!(count, 2);
+}
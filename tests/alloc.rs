@@ -0,0 +1,83 @@
+use {
+    skytable::{query, query::PreparedQuery, Query},
+    std::{
+        alloc::{GlobalAlloc, Layout, System},
+        cell::Cell,
+    },
+};
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|a| a.set(a.get() + 1));
+        System.alloc(layout)
+    }
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.with(|a| a.set(a.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+fn count_allocations<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let start = ALLOCATIONS.with(Cell::get);
+    let ret = f();
+    (ret, ALLOCATIONS.with(Cell::get) - start)
+}
+
+const QUERY: &str = "insert into myspace.mymodel(?, ?, ?)";
+
+#[test]
+fn clone_with_params_vs_rebuild() {
+    let mut template = query!(QUERY, "sayan", 100u64);
+    template.reserve(64);
+    let (cloned, clone_allocs) =
+        count_allocations(|| template.clone_with_params("sayan@example.com"));
+    let (rebuilt, rebuild_allocs) =
+        count_allocations(|| query!(QUERY, "sayan", 100u64, "sayan@example.com"));
+    assert_eq!(cloned, rebuilt);
+    assert_eq!(clone_allocs, 1);
+    assert!(rebuild_allocs > clone_allocs);
+    // a plain clone followed by an append has to grow the buffer
+    let (_, plain_allocs) = count_allocations(|| {
+        let mut q: Query = template.clone();
+        q.push_param("sayan@example.com");
+        q
+    });
+    assert!(plain_allocs > clone_allocs);
+}
+
+#[derive(Query)]
+struct User {
+    username: String,
+    followers: u64,
+    email: String,
+}
+
+#[test]
+fn prepared_vs_fresh_query() {
+    let user = User {
+        username: "sayan".into(),
+        followers: 100,
+        email: "sayan@example.com".into(),
+    };
+    let mut buf = Vec::with_capacity(256);
+    let prepared = PreparedQuery::new(QUERY);
+    // warm up
+    prepared.write_packet_into(&mut buf, &user);
+    let (_, prepared_allocs) = count_allocations(|| prepared.write_packet_into(&mut buf, &user));
+    let (fresh, fresh_allocs) = count_allocations(|| query!(QUERY, &user).debug_encode_packet());
+    assert_eq!(buf, fresh);
+    assert_eq!(prepared_allocs, 0);
+    assert!(fresh_allocs > prepared_allocs);
+}
@@ -1,4 +1,10 @@
-use skytable::{query, Query, Response};
+use skytable::{
+    error::{Error, ParseError},
+    query,
+    response::{FromResponse, FromValue, Response as Resp, Row, Value},
+    Query, Response, SkyEnum, SkyModel,
+};
+use std::convert::TryInto;
 
 #[derive(Query, Response)]
 struct User {
@@ -25,3 +31,232 @@ fn test_params() {
     );
     assert_eq!(q.param_cnt(), 3);
 }
+
+#[test]
+fn test_column_count_mismatch() {
+    let resp = Resp::Row(Row::new(vec![
+        Value::String("sayan".into()),
+        Value::String("pass".into()),
+    ]));
+    let e = match User::from_response(resp) {
+        Err(Error::ParseError(e)) => e,
+        _ => panic!("expected a parse error"),
+    };
+    assert_eq!(
+        e,
+        ParseError::ColumnCountMismatch {
+            expected: 3,
+            actual: 2
+        }
+    );
+    assert_eq!(e.to_string(), "expected 3 columns, got 2");
+}
+
+#[test]
+fn test_try_from_response() {
+    let user: User = Resp::Row(Row::new(vec![
+        Value::String("sayan".into()),
+        Value::String("pass".into()),
+        Value::Null,
+    ]))
+    .try_into()
+    .unwrap();
+    assert_eq!(user.username, "sayan");
+    assert_eq!(user.password, "pass");
+    assert_eq!(user.email, None);
+}
+
+#[derive(Query)]
+struct Login {
+    username: String,
+    #[skytable(skip)]
+    #[allow(dead_code)]
+    attempts: u32,
+    password: String,
+}
+
+#[test]
+fn test_query_skip() {
+    let login = Login {
+        username: "sayan".into(),
+        attempts: 3,
+        password: "pass".into(),
+    };
+    let q = query!(
+        "select * from myspace.logins where username = ? and password = ?",
+        login
+    );
+    assert_eq!(q.param_cnt(), 2);
+    assert_eq!(
+        q.decode_params().unwrap(),
+        [Value::str("sayan"), Value::str("pass")]
+    );
+}
+
+#[derive(SkyModel, Debug, PartialEq)]
+struct Session {
+    token: String,
+    #[skytable(skip)]
+    cached: Option<String>,
+    expires: u64,
+}
+
+#[test]
+fn test_skip_roundtrip() {
+    let session = Session {
+        token: "abc".into(),
+        cached: Some("local only".into()),
+        expires: 10,
+    };
+    let q = query!("insert into myspace.sessions(?, ?)", &session);
+    let row = Resp::Row(Row::new(q.decode_params().unwrap()));
+    assert_eq!(
+        Session::from_response(row).unwrap(),
+        Session {
+            cached: None,
+            ..session
+        }
+    );
+    assert!(matches!(
+        Session::from_response(Resp::Row(Row::new(vec![
+            Value::str("abc"),
+            Value::Null,
+            Value::uint(10)
+        ]))),
+        Err(Error::ParseError(ParseError::ColumnCountMismatch {
+            expected: 2,
+            actual: 3
+        }))
+    ));
+}
+
+#[derive(Response, Debug, PartialEq)]
+struct Profile {
+    username: String,
+    followers: u64,
+    #[skytable(default)]
+    bio: String,
+}
+
+#[test]
+fn test_default_fields() {
+    // a row from before `bio` was added to the model
+    let profile = Profile::from_response(Resp::Row(Row::new(vec![
+        Value::str("sayan"),
+        Value::uint(100),
+    ])))
+    .unwrap();
+    assert_eq!(
+        profile,
+        Profile {
+            username: "sayan".into(),
+            followers: 100,
+            bio: String::new(),
+        }
+    );
+    let profile = Profile::from_response(Resp::Row(Row::new(vec![
+        Value::str("sayan"),
+        Value::uint(100),
+        Value::str("hello"),
+    ])))
+    .unwrap();
+    assert_eq!(profile.bio, "hello");
+    // only the defaulted fields can be missing
+    assert!(matches!(
+        Profile::from_response(Resp::Row(Row::new(vec![Value::str("sayan")]))),
+        Err(Error::ParseError(ParseError::ColumnCountMismatch {
+            expected: 3,
+            actual: 1
+        }))
+    ));
+    assert!(matches!(
+        Profile::from_response(Resp::Row(Row::new(vec![
+            Value::str("sayan"),
+            Value::uint(100),
+            Value::str("hello"),
+            Value::Null,
+        ]))),
+        Err(Error::ParseError(ParseError::ColumnCountMismatch {
+            expected: 3,
+            actual: 4
+        }))
+    ));
+}
+
+#[derive(SkyEnum, Debug, PartialEq, Clone, Copy)]
+enum Color {
+    Red,
+    Green,
+    Blue,
+}
+
+#[derive(SkyEnum, Debug, PartialEq, Clone, Copy)]
+enum Status {
+    #[skytable(value = 0)]
+    Active,
+    #[skytable(value = 1)]
+    Suspended,
+    // the short form is accepted too
+    #[sky(value = 20)]
+    Deleted,
+}
+
+#[test]
+fn test_sky_enum_string() {
+    for color in [Color::Red, Color::Green, Color::Blue] {
+        let q = query!("insert into myspace.mymodel(?)", color);
+        assert_eq!(
+            q.debug_encode_packet(),
+            query!("insert into myspace.mymodel(?)", format!("{:?}", color)).debug_encode_packet()
+        );
+        assert_eq!(
+            Color::from_value(Value::String(format!("{:?}", color))).unwrap(),
+            color
+        );
+    }
+    assert!(matches!(
+        Color::from_value(Value::String("Yellow".into())),
+        Err(Error::ParseError(ParseError::TypeMismatchGot {
+            expected: "Color",
+            got: Value::String(s),
+        })) if s == "Yellow"
+    ));
+    assert!(matches!(
+        Color::from_value(Value::UInt8(0)),
+        Err(Error::ParseError(ParseError::TypeMismatchGot {
+            expected: "Color",
+            got: Value::UInt8(0),
+        }))
+    ));
+}
+
+#[test]
+fn test_sky_enum_int() {
+    for (status, value) in [
+        (Status::Active, 0u64),
+        (Status::Suspended, 1),
+        (Status::Deleted, 20),
+    ] {
+        let q = query!("insert into myspace.mymodel(?)", status);
+        assert_eq!(
+            q.debug_encode_packet(),
+            query!("insert into myspace.mymodel(?)", value).debug_encode_packet()
+        );
+        assert_eq!(Status::from_value(Value::UInt64(value)).unwrap(), status);
+        assert_eq!(
+            Status::from_value(Value::UInt8(value as u8)).unwrap(),
+            status
+        );
+    }
+    assert!(matches!(
+        Status::from_value(Value::UInt64(2)),
+        Err(Error::ParseError(ParseError::TypeMismatchGot {
+            expected: "Status",
+            got: Value::UInt64(2),
+        }))
+    ));
+    let status: Status = Resp::Row(Row::new(vec![Value::UInt8(1)]))
+        .try_into()
+        .unwrap();
+    assert_eq!(status, Status::Suspended);
+}
@@ -0,0 +1,144 @@
+#![cfg(feature = "testing")]
+
+use skytable::{
+    error::Error,
+    paginate::Paginator,
+    query,
+    response::{Response, Value},
+    testing::MockConnection,
+};
+
+const SELECT: &str = "select all * from myspace.users where followers > ? limit ? offset ?";
+
+fn page(users: &[(&str, u64)]) -> Response {
+    Response::rows(
+        users
+            .iter()
+            .map(|(username, followers)| vec![Value::str(*username), Value::uint(*followers)]),
+    )
+}
+
+/// The limit and offset that every query was sent with
+fn limits_and_offsets(db: &MockConnection) -> Vec<(u64, u64)> {
+    db.queries()
+        .iter()
+        .map(|q| match &q.decode_params().unwrap()[..] {
+            [Value::UInt64(10), Value::UInt64(limit), Value::UInt64(offset)] => (*limit, *offset),
+            params => panic!("unexpected params: {:?}", params),
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn paginate_until_short_page() {
+    let mut db = MockConnection::builder()
+        .enqueue(page(&[("a", 11), ("b", 12)]))
+        .enqueue(page(&[("c", 13), ("d", 14)]))
+        .enqueue(page(&[("e", 15)]))
+        .build();
+    let mut pages = Paginator::new(query!(SELECT, 10u64), 2).unwrap();
+    let mut users: Vec<(String, u64)> = vec![];
+    while let Some(page) = pages.next_page::<(String, u64), _>(&mut db).await.unwrap() {
+        users.extend(page);
+    }
+    assert_eq!(users.len(), 5);
+    assert_eq!(users[4], ("e".to_owned(), 15));
+    assert!(pages.is_done());
+    assert_eq!(pages.offset(), 5);
+    assert_eq!(limits_and_offsets(&db), [(2, 0), (2, 2), (2, 4)]);
+    // no more queries are sent once done
+    assert!(pages
+        .next_page::<(String, u64), _>(&mut db)
+        .await
+        .unwrap()
+        .is_none());
+    assert_eq!(db.queries().len(), 3);
+}
+
+#[test]
+fn paginate_blocking() {
+    let mut db = MockConnection::builder()
+        .enqueue(page(&[("a", 11), ("b", 12)]))
+        .enqueue(page(&[("c", 13)]))
+        .build();
+    let mut pages = Paginator::new(query!(SELECT, 10u64), 2).unwrap();
+    let mut users: Vec<(String, u64)> = vec![];
+    while let Some(page) = pages
+        .next_page_blocking::<(String, u64), _>(&mut db)
+        .unwrap()
+    {
+        users.extend(page);
+    }
+    assert_eq!(users.len(), 3);
+    assert!(pages.is_done());
+    assert_eq!(limits_and_offsets(&db), [(2, 0), (2, 2)]);
+}
+
+#[tokio::test]
+async fn paginate_empty_last_page() {
+    // the number of rows is a multiple of the limit, so the last page is empty
+    let mut db = MockConnection::builder()
+        .enqueue(page(&[("a", 11), ("b", 12)]))
+        .enqueue(page(&[]))
+        .build();
+    let mut pages = Paginator::new(query!(SELECT, 10u64), 2).unwrap();
+    assert_eq!(
+        pages
+            .next_page::<(String, u64), _>(&mut db)
+            .await
+            .unwrap()
+            .unwrap()
+            .len(),
+        2
+    );
+    assert!(!pages.is_done());
+    assert!(pages
+        .next_page::<(String, u64), _>(&mut db)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(pages.is_done());
+    assert_eq!(limits_and_offsets(&db), [(2, 0), (2, 2)]);
+}
+
+#[tokio::test]
+async fn paginate_error_keeps_offset() {
+    let mut db = MockConnection::builder()
+        .enqueue(Response::Error(5))
+        .enqueue(page(&[("a", 11)]))
+        .build();
+    let mut pages = Paginator::new(query!(SELECT, 10u64), 2).unwrap();
+    assert!(matches!(
+        pages.next_page::<(String, u64), _>(&mut db).await,
+        Err(Error::ServerError(5))
+    ));
+    assert_eq!(pages.offset(), 0);
+    assert_eq!(
+        pages
+            .next_page::<(String, u64), _>(&mut db)
+            .await
+            .unwrap()
+            .unwrap()
+            .len(),
+        1
+    );
+    assert_eq!(limits_and_offsets(&db), [(2, 0), (2, 0)]);
+}
+
+#[test]
+fn paginator_validation() {
+    assert!(matches!(
+        Paginator::new(query!(SELECT, 10u64), 0),
+        Err(Error::InvalidQuery(_))
+    ));
+    // the limit and offset placeholders are missing
+    assert!(matches!(
+        Paginator::new(query!("select all * from myspace.users limit ?"), 10),
+        Err(Error::InvalidQuery(_))
+    ));
+    // the parameter for `followers > ?` is missing
+    assert!(matches!(
+        Paginator::new(query!(SELECT), 10),
+        Err(Error::InvalidQuery(_))
+    ));
+}
@@ -0,0 +1,70 @@
+use skytable::{aio, pool, query, response::FromResponse, Config};
+
+/// Restarts the server with the shell command in `SKYTABLE_RESTART_CMD` (for example, `systemctl restart skyd`), which
+/// must only return once the server accepts connections again
+#[test]
+#[ignore = "requires a running Skytable server and SKYTABLE_RESTART_CMD"]
+fn pool_recovers_after_server_restart() {
+    let restart = std::env::var("SKYTABLE_RESTART_CMD").unwrap();
+    let pool = pool::get(4, Config::new_default("root", "password")).unwrap();
+    pool.get()
+        .unwrap()
+        .query_parse::<()>(&query!("sysctl report status"))
+        .unwrap();
+    let status = std::process::Command::new("sh")
+        .args(["-c", &restart])
+        .status()
+        .unwrap();
+    assert!(status.success());
+    // every pooled connection is dead now, but they're replaced on checkout
+    for _ in 0..4 {
+        let mut db = pool.get().unwrap();
+        db.query_parse::<()>(&query!("sysctl report status"))
+            .unwrap();
+        assert!(!db.is_broken());
+    }
+}
+
+#[tokio::test]
+#[ignore = "requires a running Skytable server"]
+async fn query_all_keeps_order() {
+    let pool = pool::get_async(4, Config::new_default("root", "password"))
+        .await
+        .unwrap();
+    {
+        let mut db = pool.get().await.unwrap();
+        db.exec(&query!("create space if not exists pool_test"))
+            .await
+            .unwrap();
+        db.exec(&query!(
+            "create model if not exists pool_test.users(username: string, followers: uint64)"
+        ))
+        .await
+        .unwrap();
+        db.exec(&query!("truncate model pool_test.users"))
+            .await
+            .unwrap();
+        for i in 0..16u64 {
+            db.exec(&query!(
+                "insert into pool_test.users(?, ?)",
+                format!("user{}", i),
+                i
+            ))
+            .await
+            .unwrap();
+        }
+    }
+    // more queries than connections, so some of them wait for a connection
+    let queries = (0..16u64).map(|i| {
+        query!(
+            "select followers from pool_test.users where username = ?",
+            format!("user{}", i)
+        )
+    });
+    let resps = aio::query_all(&pool, queries).await;
+    assert_eq!(resps.len(), 16);
+    for (i, resp) in resps.into_iter().enumerate() {
+        let followers = u64::from_response(resp.unwrap()).unwrap();
+        assert_eq!(followers, i as u64);
+    }
+}
@@ -1,4 +1,4 @@
-use skytable::query;
+use skytable::{error::Error, pipeline, query, query::Pipeline, response::Entity, Config, Query};
 
 #[test]
 fn param_cnt_zero() {
@@ -13,3 +13,273 @@ fn param_cnt() {
     assert_eq!(q.param_cnt(), 2);
     assert_eq!(q.query_str(), "insert into myspace.mymodel(?, ?)");
 }
+
+#[test]
+fn ident_valid() {
+    for name in ["myspace", "my_model", "_tmp", "model2", &"a".repeat(64)] {
+        assert_eq!(query::ident(name).unwrap(), name);
+    }
+}
+
+#[test]
+fn ident_rejected() {
+    for name in [
+        "",
+        "2model",
+        "my model",
+        "myspace.mymodel",
+        "model;drop",
+        "modèle",
+        &"a".repeat(65),
+    ] {
+        assert!(
+            matches!(query::ident(name), Err(Error::InvalidQuery(_))),
+            "{}",
+            name
+        );
+    }
+}
+
+#[test]
+fn with_entity() {
+    assert_eq!(
+        Query::with_entity("myspace", "mymodel").unwrap(),
+        "myspace.mymodel"
+    );
+    assert!(Query::with_entity("my space", "mymodel").is_err());
+    assert!(Query::with_entity("myspace", "").is_err());
+}
+
+#[test]
+fn lengths() {
+    let q = Query::new("");
+    assert!(q.is_empty());
+    assert_eq!(q.params_len(), 0);
+    assert_eq!(q.encoded_len(), q.debug_encode_packet().len());
+    let q = query!("insert into myspace.mymodel(?, ?)", "sayan", 100u64);
+    assert!(!q.is_empty());
+    // \x065\nsayan + \x02100\n
+    assert_eq!(q.params_len(), 8 + 5);
+    let packet = q.debug_encode_packet();
+    assert_eq!(q.encoded_len(), packet.len());
+    assert!(packet.ends_with(b"\x065\nsayan\x02100\n"));
+    // packet size crossing a power of ten
+    for len in [1, 5, 90, 95, 96, 97, 98, 99, 100, 995, 999, 1000] {
+        let q = query!("x".repeat(len));
+        assert_eq!(q.encoded_len(), q.debug_encode_packet().len());
+    }
+}
+
+#[derive(Query, Clone)]
+struct User {
+    username: String,
+    followers: u64,
+}
+
+fn users() -> Vec<User> {
+    vec![
+        User {
+            username: "sayan".into(),
+            followers: 100,
+        },
+        User {
+            username: "elana".into(),
+            followers: 200,
+        },
+        User {
+            username: "emily".into(),
+            followers: 300,
+        },
+    ]
+}
+
+#[test]
+fn insert_many() {
+    const INSERT: &str = "insert into myspace.mymodel(?, ?)";
+    let users = users();
+    let mut manual = Pipeline::new();
+    for user in &users {
+        manual.add_query(&query!(INSERT, user.username.as_str(), user.followers));
+    }
+    // borrowed
+    let mut pipeline = Pipeline::new();
+    pipeline.insert_many(INSERT, &users);
+    assert_eq!(pipeline.query_count(), users.len());
+    assert_eq!(pipeline, manual);
+    // owned
+    let mut pipeline = Pipeline::new();
+    pipeline.insert_many(INSERT, users);
+    assert_eq!(pipeline, manual);
+    // empty
+    let mut pipeline = Pipeline::new();
+    pipeline.insert_many(INSERT, Vec::<User>::new());
+    assert_eq!(pipeline.query_count(), 0);
+}
+
+#[test]
+fn pipeline_debug_encoded() {
+    let mut pipeline = Pipeline::new();
+    assert_eq!(pipeline.debug_encoded(), b"");
+    assert_eq!(pipeline.len_bytes(), 0);
+    pipeline
+        .add_query(&query!("sysctl report status"))
+        .add_query(&query!(
+            "select * from myspace.mymodel where username = ?",
+            "sayan"
+        ));
+    let expected: &[u8] = b"20\n0\nsysctl report status\
+        48\n8\nselect * from myspace.mymodel where username = ?\x065\nsayan";
+    assert_eq!(pipeline.debug_encoded(), expected);
+    assert_eq!(pipeline.len_bytes(), expected.len());
+}
+
+#[test]
+fn pipeline_from_iter() {
+    let queries = [query!("create space a"), query!("create space b")];
+    let pipeline: Pipeline = queries.iter().collect();
+    let mut manual = Pipeline::new();
+    manual.add_query(&queries[0]);
+    manual.add_query(&queries[1]);
+    assert_eq!(pipeline, manual);
+}
+
+#[test]
+fn pipeline_macro() {
+    let pipeline = pipeline! {
+        ("insert into myspace.mymodel(?, ?)", "sayan", 100u64);
+        ("sysctl report status");
+        ("select * from myspace.mymodel where username = ?", "sayan",);
+    };
+    let mut manual = Pipeline::new();
+    manual.add_query(&query!(
+        "insert into myspace.mymodel(?, ?)",
+        "sayan",
+        100u64
+    ));
+    manual.add_query(&query!("sysctl report status"));
+    manual.add_query(&query!(
+        "select * from myspace.mymodel where username = ?",
+        "sayan"
+    ));
+    assert_eq!(pipeline.query_count(), 3);
+    assert_eq!(pipeline, manual);
+    assert_eq!(pipeline! {}.query_count(), 0);
+    assert_eq!(pipeline! { ("sysctl report status") }.query_count(), 1);
+}
+
+#[test]
+fn named_params() {
+    let q = query!(
+        "insert into myspace.mymodel { id: :id, name: :name }",
+        name = "sayan",
+        id = 1u64,
+    );
+    assert_eq!(
+        q.query_str(),
+        "insert into myspace.mymodel { id: ?, name: ? }"
+    );
+    assert_eq!(
+        q,
+        query!(
+            "insert into myspace.mymodel { id: ?, name: ? }",
+            1u64,
+            "sayan"
+        )
+    );
+}
+
+#[test]
+fn named_params_repeated() {
+    let mut evaluated = 0;
+    let mut by = || {
+        evaluated += 1;
+        2u64
+    };
+    let q = query!(
+        "update myspace.mymodel set a += :by, b += :by where id = :id",
+        id = "sayan",
+        by = by(),
+    );
+    assert_eq!(evaluated, 1);
+    assert_eq!(
+        q,
+        query!(
+            "update myspace.mymodel set a += ?, b += ? where id = ?",
+            2u64,
+            2u64,
+            "sayan"
+        )
+    );
+}
+
+#[test]
+fn named_params_untouched() {
+    // quoted strings aren't searched for placeholders
+    let q = query!(
+        "select * from myspace.mymodel where tag = ':tag' and username = :username",
+        username = "sayan"
+    );
+    assert_eq!(
+        q.query_str(),
+        "select * from myspace.mymodel where tag = ':tag' and username = ?"
+    );
+    assert_eq!(q.param_cnt(), 1);
+    // named parameters also work in pipelines
+    let pipeline = pipeline! {
+        ("select * from myspace.mymodel where username = :username", username = "sayan");
+    };
+    assert_eq!(pipeline.query_count(), 1);
+}
+
+#[test]
+fn tuple_params() {
+    let user = ("sayan".to_owned(), 100u64, true);
+    let q = query!("insert into myspace.mymodel(?, ?, ?)", &user);
+    assert_eq!(q.param_cnt(), 3);
+    assert_eq!(
+        q,
+        query!(
+            "insert into myspace.mymodel(?, ?, ?)",
+            "sayan",
+            100u64,
+            true
+        )
+    );
+    // tuples can be nested and mixed with other parameters
+    let q = query!(
+        "insert into myspace.mymodel(?, ?, ?, ?)",
+        ("sayan", (1u8, None::<String>)),
+        false
+    );
+    assert_eq!(q.param_cnt(), 4);
+    let mut q = Query::new("insert into myspace.mymodel(?)");
+    q.push_param((1u64,));
+    assert_eq!(q.param_cnt(), 1);
+}
+
+#[test]
+fn entity_in_query() {
+    let users = Entity::new("myspace", "users").unwrap();
+    let q = query!(
+        format!("select * from {} where username = ?", users),
+        "sayan"
+    );
+    assert_eq!(
+        q.query_str(),
+        "select * from myspace.users where username = ?"
+    );
+    assert_eq!(
+        q.debug_encode_packet(),
+        query!("select * from myspace.users where username = ?", "sayan").debug_encode_packet()
+    );
+}
+
+#[test]
+#[ignore = "requires a running Skytable server"]
+fn use_entity_against_server() {
+    let mut db = Config::new_default("root", "password").connect().unwrap();
+    db.exec(&query!("create space if not exists use_test"))
+        .unwrap();
+    db.use_entity("use_test").unwrap();
+    assert!(db.use_entity("use_test; drop space use_test").is_err());
+}
@@ -0,0 +1,71 @@
+#![cfg(feature = "testing")]
+
+use {
+    skytable::{
+        error::ClientResult,
+        query,
+        response::{Response, Row, Value},
+        testing::MockConnection,
+        BlockingQueryable, Config, Queryable,
+    },
+    tokio::io::{AsyncReadExt, AsyncWriteExt},
+};
+
+/// The same code, for any kind of connection
+async fn followers<Q: Queryable>(db: &mut Q, username: &str) -> ClientResult<u64> {
+    db.exec(&query!("use myspace")).await?;
+    db.query_parse(&query!(
+        "select followers from mymodel where username = ?",
+        username
+    ))
+    .await
+}
+
+/// The same code, for any kind of sync connection
+fn followers_blocking<Q: BlockingQueryable>(db: &mut Q, username: &str) -> ClientResult<u64> {
+    db.exec(&query!("use myspace"))?;
+    db.query_parse(&query!(
+        "select followers from mymodel where username = ?",
+        username
+    ))
+}
+
+#[test]
+fn queryable_blocking() {
+    let mut db = MockConnection::builder()
+        .enqueue(Response::Empty)
+        .enqueue(Response::Row(Row::new(vec![Value::UInt64(100)])))
+        .build();
+    assert_eq!(followers_blocking(&mut db, "sayan").unwrap(), 100);
+    assert_eq!(db.queries().len(), 2);
+}
+
+#[tokio::test]
+async fn queryable_mock() {
+    let mut db = MockConnection::builder()
+        .enqueue(Response::Empty)
+        .enqueue(Response::Row(Row::new(vec![Value::UInt64(100)])))
+        .build();
+    assert_eq!(followers(&mut db, "sayan").await.unwrap(), 100);
+    assert_eq!(db.queries().len(), 2);
+}
+
+#[tokio::test]
+async fn queryable_async() {
+    let (client, mut server) = tokio::io::duplex(1024);
+    let server = tokio::spawn(async move {
+        let mut buf = [0u8; 256];
+        let _ = server.read(&mut buf).await.unwrap();
+        server.write_all(b"H\x00\x00\x00").await.unwrap();
+        let _ = server.read(&mut buf).await.unwrap();
+        server.write_all(b"\x12").await.unwrap();
+        let _ = server.read(&mut buf).await.unwrap();
+        server.write_all(b"\x05100\n").await.unwrap();
+    });
+    let mut db = Config::new_default("username", "password")
+        .connect_async_over(client)
+        .await
+        .unwrap();
+    assert_eq!(followers(&mut db, "sayan").await.unwrap(), 100);
+    server.await.unwrap();
+}
@@ -0,0 +1,27 @@
+#![cfg(feature = "socks")]
+
+//! Connect through a SOCKS5 proxy listening on `127.0.0.1:1080` (for example, `ssh -D 1080 localhost` or a local Tor
+//! client) to a Skytable server on the default port
+
+use skytable::{query, Config};
+
+const PROXY: &str = "127.0.0.1:1080";
+
+#[test]
+#[ignore = "requires a local SOCKS5 proxy and a running Skytable server"]
+fn connect_via_socks5() {
+    let mut db = Config::new_default("root", "password")
+        .connect_via_socks5(PROXY)
+        .unwrap();
+    db.exec(&query!("sysctl report status")).unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires a local SOCKS5 proxy and a running Skytable server"]
+async fn connect_async_via_socks5() {
+    let mut db = Config::new_default("root", "password")
+        .connect_async_via_socks5(PROXY)
+        .await
+        .unwrap();
+    db.exec(&query!("sysctl report status")).await.unwrap();
+}
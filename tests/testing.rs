@@ -0,0 +1,153 @@
+#![cfg(feature = "testing")]
+
+use skytable::{
+    error::Error,
+    query,
+    query::Pipeline,
+    response::{Response, Row, Value},
+    testing::MockConnection,
+    Query, Response, SkyModel,
+};
+
+#[derive(Query, Response, Debug, PartialEq)]
+struct User {
+    username: String,
+    followers: u64,
+}
+
+#[test]
+fn mock_fifo_row() {
+    let mut db = MockConnection::builder()
+        .enqueue(Response::Empty)
+        .enqueue(Response::Row(Row::new(vec![
+            Value::String("sayan".into()),
+            Value::UInt64(100),
+        ])))
+        .build();
+    let user = User {
+        username: "sayan".into(),
+        followers: 100,
+    };
+    db.query_parse::<()>(&query!(
+        "insert into myspace.mymodel(?, ?)",
+        &user.username,
+        user.followers
+    ))
+    .unwrap();
+    let ret: User = db
+        .query_parse(&query!(
+            "select * from myspace.mymodel where username = ?",
+            "sayan"
+        ))
+        .unwrap();
+    assert_eq!(ret, user);
+    assert_eq!(db.queries().len(), 2);
+    assert_eq!(db.queries()[1].param_cnt(), 1);
+    // nothing left
+    assert!(matches!(
+        db.query(&query!("sysctl report status")),
+        Err(Error::IoError(_))
+    ));
+}
+
+#[test]
+fn mock_keyed() {
+    let mut db = MockConnection::builder()
+        .respond_to("create space myspace", Response::Error(100))
+        .enqueue(Response::Empty)
+        .build();
+    for _ in 0..2 {
+        assert!(matches!(
+            db.query_parse::<()>(&query!("create space myspace")),
+            Err(Error::ServerError(100))
+        ));
+    }
+    db.query_parse::<()>(&query!("sysctl report status"))
+        .unwrap();
+}
+
+#[test]
+fn mock_pipeline() {
+    let mut db = MockConnection::builder()
+        .respond_to("create space myspace", Response::Empty)
+        .enqueue(Response::Error(103))
+        .build();
+    let mut pipeline = Pipeline::new();
+    pipeline.add_query(&query!("create space myspace"));
+    pipeline.add_query(&query!(
+        "create model myspace.mymodel(username: string, followers: uint64)"
+    ));
+    assert_eq!(
+        db.execute_pipeline(&pipeline).unwrap(),
+        [Response::Empty, Response::Error(103)]
+    );
+    assert_eq!(db.pipelines(), [pipeline]);
+}
+
+#[test]
+fn mock_rows_as() {
+    let mut db = MockConnection::builder()
+        .enqueue(Response::Rows(vec![
+            Row::new(vec![Value::String("sayan".into()), Value::UInt64(100)]),
+            Row::new(vec![Value::String("elana".into()), Value::UInt64(200)]),
+        ]))
+        .build();
+    let users: Vec<User> = db
+        .query(&query!("select all * from myspace.mymodel limit ?", 10u64))
+        .unwrap()
+        .rows_as::<User>()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(
+        users,
+        [
+            User {
+                username: "sayan".into(),
+                followers: 100
+            },
+            User {
+                username: "elana".into(),
+                followers: 200
+            }
+        ]
+    );
+}
+
+#[derive(SkyModel, Debug, PartialEq, Clone)]
+struct Account {
+    username: String,
+    email: Option<String>,
+    balance: u64,
+}
+
+#[test]
+fn mock_sky_model_round_trip() {
+    let account = Account {
+        username: "sayan".into(),
+        email: None,
+        balance: 500,
+    };
+    let mut db = MockConnection::builder()
+        .respond_to("insert into myspace.accounts(?, ?, ?)", Response::Empty)
+        .build();
+    db.exec(&query!("insert into myspace.accounts(?, ?, ?)", &account))
+        .unwrap();
+    // the mock returns the row that was inserted
+    let row = db.queries()[0].decode_params().unwrap();
+    assert_eq!(
+        row,
+        [
+            Value::String("sayan".into()),
+            Value::Null,
+            Value::UInt64(500)
+        ]
+    );
+    db.enqueue(Response::Row(Row::new(row)));
+    let ret: Account = db
+        .query_parse(&query!(
+            "select * from myspace.accounts where username = ?",
+            "sayan"
+        ))
+        .unwrap();
+    assert_eq!(ret, account);
+}
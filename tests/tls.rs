@@ -0,0 +1,107 @@
+#![cfg(feature = "rustls")]
+
+//! Connect over TLS with the `rustls` backend, to a local server that presents a certificate issued (by a test CA) for
+//! `skytable.test`
+
+use {
+    rustls::{crypto::ring, ServerConfig},
+    skytable::{error::Error, Config},
+    std::{net::TcpListener, sync::Arc, thread},
+    tokio::io::{AsyncReadExt, AsyncWriteExt},
+    tokio_rustls::TlsAcceptor,
+};
+
+const CA: &str = include_str!("tls/ca.pem");
+const CERT: &str = include_str!("tls/server.pem");
+const KEY: &str = include_str!("tls/server.key");
+
+fn acceptor() -> TlsAcceptor {
+    let certs = rustls_pemfile::certs(&mut CERT.as_bytes())
+        .collect::<Result<_, _>>()
+        .unwrap();
+    let key = rustls_pemfile::private_key(&mut KEY.as_bytes())
+        .unwrap()
+        .unwrap();
+    let config = ServerConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .unwrap();
+    TlsAcceptor::from(Arc::new(config))
+}
+
+/// Start a server that accepts one connection, completes the handshake and answers one query, returning its port. The
+/// server then waits for the client to end the TLS session cleanly
+fn start_server() -> (u16, thread::JoinHandle<()>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let handle = thread::spawn(move || {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        rt.block_on(async move {
+            listener.set_nonblocking(true).unwrap();
+            let listener = tokio::net::TcpListener::from_std(listener).unwrap();
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut stream = match acceptor().accept(stream).await {
+                Ok(stream) => stream,
+                // the client rejected the certificate
+                Err(_) => return,
+            };
+            let mut buf = [0u8; 128];
+            let n = stream.read(&mut buf).await.unwrap();
+            assert_eq!(buf[0], b'H');
+            stream.write_all(b"H\x00\x00\x00").await.unwrap();
+            let _ = stream.read(&mut buf[n..]).await.unwrap();
+            stream.write_all(b"\x0D5\nsayan").await.unwrap();
+            stream.flush().await.unwrap();
+            // a `close_notify` reads as EOF, while a TCP close without one is an error
+            assert_eq!(stream.read(&mut buf).await.unwrap(), 0);
+        })
+    });
+    (port, handle)
+}
+
+#[test]
+fn connect_tls() {
+    let (port, server) = start_server();
+    let mut db = Config::new("127.0.0.1", port, "username", "password")
+        .connect_tls(CA)
+        .unwrap();
+    let username: String = db
+        .query_parse(&skytable::query!("select username from myspace.mymodel"))
+        .unwrap();
+    assert_eq!(username, "sayan");
+    db.close().unwrap();
+    server.join().unwrap();
+}
+
+#[tokio::test]
+async fn connect_tls_async() {
+    let (port, server) = start_server();
+    let mut db = Config::new("127.0.0.1", port, "username", "password")
+        .connect_tls_async(CA)
+        .await
+        .unwrap();
+    let username: String = db
+        .query_parse(&skytable::query!("select username from myspace.mymodel"))
+        .await
+        .unwrap();
+    assert_eq!(username, "sayan");
+    db.close().await.unwrap();
+    server.join().unwrap();
+}
+
+#[test]
+fn connect_tls_untrusted() {
+    let (port, server) = start_server();
+    // the server's own certificate is not a CA, so it can't be used as a trust anchor for itself
+    let e = Config::new("127.0.0.1", port, "username", "password")
+        .connect_tls(CERT)
+        .unwrap_err();
+    assert!(matches!(e, Error::ConnectionSetupErr(_)), "{}", e);
+    assert!(e.to_string().contains("TLS handshake failed"), "{}", e);
+    server.join().unwrap();
+}
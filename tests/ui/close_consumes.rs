@@ -0,0 +1,8 @@
+use skytable::{query, Config};
+
+fn main() {
+    let mut db = Config::new_default("username", "password").connect().unwrap();
+    db.close().unwrap();
+    // the connection was consumed by `close`, so it can't be used anymore
+    let _ = db.query(&query!("sysctl report status"));
+}
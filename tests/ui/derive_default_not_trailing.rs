@@ -0,0 +1,11 @@
+use skytable::Response;
+
+#[derive(Response)]
+struct User {
+    username: String,
+    #[skytable(default)]
+    followers: u64,
+    verified: bool,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use skytable::{Query, Response};
+
+#[derive(Query)]
+struct NoFields {}
+
+#[derive(Response)]
+struct NoColumns {}
+
+fn main() {}
@@ -0,0 +1,14 @@
+use skytable::{Query, Response};
+
+#[derive(Query)]
+enum Param {
+    Username(String),
+}
+
+#[derive(Response)]
+enum Status {
+    Active,
+    Suspended,
+}
+
+fn main() {}
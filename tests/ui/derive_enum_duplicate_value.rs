@@ -0,0 +1,13 @@
+use skytable::SkyEnum;
+
+#[derive(SkyEnum)]
+enum Status {
+    #[skytable(value = 0)]
+    Active,
+    #[skytable(value = 1)]
+    Suspended,
+    #[skytable(value = 1)]
+    Deleted,
+}
+
+fn main() {}
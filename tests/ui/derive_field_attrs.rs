@@ -0,0 +1,15 @@
+use skytable::Query;
+
+#[derive(Query)]
+struct Cached {
+    #[skytable(skip)]
+    hits: u64,
+}
+
+#[derive(Query)]
+struct Unknown {
+    #[skytable(rename = "name")]
+    username: String,
+}
+
+fn main() {}
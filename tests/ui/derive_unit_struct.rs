@@ -0,0 +1,9 @@
+use skytable::{Query, Response};
+
+#[derive(Query)]
+struct Marker;
+
+#[derive(Response)]
+struct Empty;
+
+fn main() {}
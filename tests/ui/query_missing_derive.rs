@@ -0,0 +1,14 @@
+use skytable::query;
+
+struct User {
+    username: String,
+    followers: u64,
+}
+
+fn main() {
+    let user = User {
+        username: "sayan".into(),
+        followers: 100,
+    };
+    let _ = query!("insert into myspace.mymodel(?, ?)", user);
+}
@@ -0,0 +1,10 @@
+use skytable::query;
+
+fn main() {
+    // a placeholder without a value
+    let _ = query!("select * from myspace.mymodel where username = :username and id = :id", id = 1u64);
+    // a value that isn't used, and one that's bound twice
+    let _ = query!("select * from myspace.mymodel where id = :id", id = 1u64, name = "sayan", id = 2u64);
+    // mixing `?` with named parameters
+    let _ = query!("select * from myspace.mymodel where id = :id and username = ?", id = 1u64);
+}